@@ -0,0 +1,40 @@
+// cargo run --release --example formants
+// cargo run --release --example formants -- 800 1200 2500
+
+use benihora::{
+    formant::{fit, TransferFunction},
+    phoneme::{Manner, PHONEMES},
+    Benihora,
+};
+
+fn main() {
+    let tract = Benihora::new(2.0, 48000.0, 1.0, 0, false).tract;
+    let step_rate = tract.step_rate();
+
+    let targets: Vec<f64> = std::env::args()
+        .skip(1)
+        .map(|arg| arg.parse().expect("formant frequencies in Hz"))
+        .collect();
+    if !targets.is_empty() {
+        let fit = fit(&tract.source, 0.01, step_rate, &targets, &[(41.0, 1.5)]);
+        println!("tongue: {:?}", fit.tongue);
+        println!("constrictions: {:?}", fit.constrictions);
+        println!("error: {}", fit.error);
+        for formant in fit.formants.iter().take(targets.len()) {
+            println!("{:.0} Hz ({:.0} Hz)", formant.frequency, formant.bandwidth);
+        }
+        return;
+    }
+
+    for phoneme in PHONEMES.iter().filter(|p| p.manner == Manner::Vowel) {
+        let mut source = tract.source.clone();
+        source.tongue = phoneme.tongue.unwrap();
+        source.other_constrictions = phoneme.constrictions.to_vec();
+        let formants = TransferFunction::from_shape(&source, phoneme.velum, step_rate).formants();
+        print!("{}:", phoneme.symbol);
+        for formant in formants.iter().take(4) {
+            print!(" {:.0} ({:.0})", formant.frequency, formant.bandwidth);
+        }
+        println!();
+    }
+}
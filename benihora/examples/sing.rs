@@ -0,0 +1,44 @@
+// cargo run --release --example sing [score.txt | song.mid] 2> /dev/null > sing.raw
+// sox -t raw -e signed-integer -b 16 -r 48000 -c 1 sing.raw sing.wav
+
+use std::io::Write;
+
+use benihora::{score::Score, singer::Singer};
+
+const SCORE: &str = "
+tempo 100
+sa 69 1
+ku 69 1
+ra 71 2
+_  0  0.5
+sa 69 1
+ku 69 1
+ra 71 1.5
+-  72 0.5
+_  0  0.5
+no 69 1
+ha 71 1
+ma 72 1
+mo 74 1
+sa 72 2
+to 71 2
+";
+
+fn main() {
+    let score = match std::env::args().nth(1) {
+        Some(path) if path.ends_with(".mid") => {
+            Score::from_smf(&std::fs::read(path).unwrap()).unwrap()
+        }
+        Some(path) => Score::parse(&std::fs::read_to_string(path).unwrap()).unwrap(),
+        None => Score::parse(SCORE).unwrap(),
+    };
+    let mut singer = Singer::new(&score, 2.0, 48000.0, 0).unwrap();
+    singer.vocalist.frequency.vibrato_amount = 0.01;
+    singer.vocalist.frequency.vibrato_frequency = 5.0;
+
+    let mut stdout = std::io::stdout();
+    for x in singer.render() {
+        let x = (x * i16::MAX as f64) as i16;
+        stdout.write_all(&x.to_ne_bytes()).unwrap();
+    }
+}
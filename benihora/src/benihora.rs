@@ -1,4 +1,4 @@
-use crate::resample::Resample;
+use resampler::{Quality, Resampler};
 
 use super::glottis::Glottis;
 use super::tract::Tract;
@@ -7,9 +7,10 @@ use super::F;
 pub struct Benihora {
     force_turbulence: bool,
     pub sample_rate: F,
+    inner_sample_rate: F,
     pub glottis: Glottis,
     pub tract: Tract,
-    resample: Resample,
+    resampler: Resampler<F>,
     glottal_output: F,
 }
 
@@ -30,13 +31,24 @@ impl Benihora {
         Self {
             force_turbulence,
             sample_rate,
+            inner_sample_rate,
             glottis: Glottis::new(inner_sample_rate, seed),
             tract: Tract::new(tract_steps_per_process, inner_sample_rate, seed + 1),
-            resample: Resample::new(inner_sample_rate, sample_rate),
+            resampler: Resampler::new(inner_sample_rate, sample_rate, Quality::Medium),
             glottal_output: 0.0,
         }
     }
 
+    /// Rate of the glottis and the tract.
+    pub fn inner_sample_rate(&self) -> F {
+        self.inner_sample_rate
+    }
+
+    /// Output delay in seconds caused by resampling from the inner sample rate.
+    pub fn delay(&self) -> F {
+        self.resampler.delay() / self.inner_sample_rate
+    }
+
     pub fn get_glottal_output(&self) -> F {
         self.glottal_output
     }
@@ -60,7 +72,7 @@ impl Benihora {
             intensity
         };
 
-        self.resample.process(|| {
+        self.resampler.process(|| {
             self.glottal_output =
                 self.glottis
                     .process(frequency, tenseness, intensity, loudness, aspiration_level);
@@ -0,0 +1,211 @@
+//! Several benihora voices singing together.
+
+use std::collections::VecDeque;
+
+use crate::{
+    phoneme::{Articulation, MAX_CONSTRICTIONS},
+    rand_f64,
+    singer::Vocalist,
+    BenihoraManaged, F,
+};
+
+#[derive(Debug, Clone)]
+pub struct ChoirSettings {
+    pub voices: usize,
+    /// Mean `sound_speed` of `BenihoraManaged::new`, i.e. the inverse of the tract length.
+    pub sound_speed: F,
+    /// Relative scatter of `sound_speed`.
+    pub sound_speed_spread: F,
+    /// Maximum pitch scatter in cents.
+    pub detune: F,
+    /// Maximum delay of each voice's note onsets in seconds.
+    pub timing_jitter: F,
+    /// 0.0 is mono, 1.0 places the voices from hard left to hard right.
+    pub stereo_spread: F,
+}
+
+impl Default for ChoirSettings {
+    fn default() -> Self {
+        Self {
+            voices: 4,
+            sound_speed: 2.0,
+            sound_speed_spread: 0.1,
+            detune: 10.0,
+            timing_jitter: 0.03,
+            stereo_spread: 0.8,
+        }
+    }
+}
+
+/// Controls are queued per voice and applied after that voice's delay.
+/// Processing doesn't allocate.
+pub struct Choir {
+    voices: Vec<ChoirVoice>,
+    timing_jitter: F,
+}
+
+struct ChoirVoice {
+    benihora: BenihoraManaged,
+    pitch: F,
+    gain: [F; 2],
+    delay: F,
+    /// Whether the last queued control leaves the voice sounding.
+    voiced: bool,
+    rand: u32,
+    controls: VecDeque<(F, Control)>,
+}
+
+#[derive(Clone, Copy)]
+enum Control {
+    Frequency(F),
+    Articulation(Articulation),
+    Sound(bool),
+}
+
+impl Choir {
+    /// Each voice takes 8 seeds from `seed`, which must stay below 2^16.
+    pub fn new(settings: &ChoirSettings, sample_rate: F, seed: u32) -> Self {
+        let mut rand = scramble(seed);
+        let voices = (0..settings.voices)
+            .map(|i| {
+                let mut bipolar = || rand_f64(&mut rand) * 2.0 - 1.0;
+                let sound_speed =
+                    settings.sound_speed * (1.0 + settings.sound_speed_spread * bipolar());
+                let pitch = 2.0f64.powf(settings.detune * bipolar() / 1200.0);
+                let vibrato_phase = bipolar();
+                let pan = if settings.voices == 1 {
+                    0.0
+                } else {
+                    settings.stereo_spread * (i as F / (settings.voices - 1) as F * 2.0 - 1.0)
+                };
+
+                let mut benihora =
+                    BenihoraManaged::new(sound_speed, sample_rate, seed + i as u32 * 8);
+                benihora.frequency.vibrato_phase = vibrato_phase;
+                benihora
+                    .benihora
+                    .tract
+                    .reserve_constrictions(MAX_CONSTRICTIONS);
+                let angle = (pan + 1.0) * std::f64::consts::FRAC_PI_4;
+                ChoirVoice {
+                    benihora,
+                    pitch,
+                    gain: [angle.cos(), angle.sin()],
+                    delay: 0.0,
+                    voiced: false,
+                    rand: scramble(seed + i as u32 * 8),
+                    controls: VecDeque::with_capacity(64),
+                }
+            })
+            .collect();
+        Self {
+            voices,
+            timing_jitter: settings.timing_jitter,
+        }
+    }
+
+    pub fn voices(&self) -> usize {
+        self.voices.len()
+    }
+
+    pub fn set_sound(&mut self, time: F, sound: bool) {
+        self.push(time, Control::Sound(sound));
+    }
+
+    fn push(&mut self, time: F, control: Control) {
+        for voice in &mut self.voices {
+            voice.push(time, control, self.timing_jitter);
+        }
+    }
+}
+
+impl Vocalist for Choir {
+    type Output = [F; 2];
+
+    fn set_frequency(&mut self, time: F, frequency: F) {
+        self.push(time, Control::Frequency(frequency));
+    }
+
+    fn set_articulation(&mut self, time: F, articulation: &Articulation) {
+        self.push(time, Control::Articulation(*articulation));
+    }
+
+    fn process(&mut self, time: F) -> [F; 2] {
+        let mut output = [0.0; 2];
+        for voice in &mut self.voices {
+            while let Some(&(control_time, control)) = voice.controls.front() {
+                if time < control_time {
+                    break;
+                }
+                voice.controls.pop_front();
+                voice.apply(control);
+            }
+            let y = voice.benihora.process(time);
+            output[0] += y * voice.gain[0];
+            output[1] += y * voice.gain[1];
+        }
+        let normalize = (self.voices.len().max(1) as F).sqrt().recip();
+        [output[0] * normalize, output[1] * normalize]
+    }
+}
+
+impl ChoirVoice {
+    /// A new delay is drawn at each onset.
+    fn push(&mut self, time: F, control: Control, timing_jitter: F) {
+        let voiced = match control {
+            Control::Sound(sound) => sound,
+            Control::Articulation(articulation) => articulation.voicing > 0.0,
+            Control::Frequency(_) => self.voiced,
+        };
+        if voiced && !self.voiced {
+            self.delay = rand_f64(&mut self.rand) * timing_jitter;
+        }
+        self.voiced = voiced;
+
+        let time = (time + self.delay).max(self.controls.back().map_or(F::MIN, |c| c.0));
+        if self.controls.len() == self.controls.capacity() {
+            let (_, control) = self.controls.pop_front().unwrap();
+            self.apply(control);
+        }
+        self.controls.push_back((time, control));
+    }
+
+    fn apply(&mut self, control: Control) {
+        match control {
+            Control::Frequency(frequency) => self.benihora.frequency.set(frequency * self.pitch),
+            Control::Articulation(articulation) => articulation.apply(&mut self.benihora),
+            Control::Sound(sound) => self.benihora.sound = sound,
+        }
+    }
+}
+
+/// Spreads small seeds over the range of `rand_f64`.
+fn scramble(seed: u32) -> u32 {
+    (seed.wrapping_add(1).wrapping_mul(0x9e37_79b9) % ((1 << 31) - 1)).max(1)
+}
+
+#[test]
+fn test() {
+    use crate::{score::Score, singer::Singer};
+
+    let settings = ChoirSettings {
+        voices: 3,
+        ..Default::default()
+    };
+    let choir = Choir::new(&settings, 24000.0, 0);
+    assert!(choir.voices.iter().all(|v| v.pitch != 1.0));
+    assert!(choir.voices[0].gain[0] > choir.voices[0].gain[1]);
+
+    let score = Score::parse("tempo 240\nma 60 1").unwrap();
+    let mut singer = Singer::with_vocalist(&score, choir, 24000.0).unwrap();
+    let output = singer.render();
+    assert!(output.iter().flatten().all(|x| x.is_finite()));
+    assert!(output.iter().any(|[l, r]| l.abs() > 0.01 && l != r));
+
+    // Each voice starts within the jitter.
+    let delays: Vec<_> = singer.vocalist.voices.iter().map(|v| v.delay).collect();
+    assert!(delays
+        .iter()
+        .all(|d| (0.0..settings.timing_jitter).contains(d)));
+    assert!(delays[0] != delays[1]);
+}
@@ -0,0 +1,311 @@
+//! Formant analysis of tract shapes and fitting of shapes to target formants.
+
+use std::f64::consts::TAU;
+
+use crate::{
+    reflections_impulse_response,
+    tract::{Diameter, OtherParams, Reflections, ShapeSource, Tract},
+    F,
+};
+
+/// Length of the impulse responses analysed, in seconds.
+const IMPULSE_RESPONSE_DURATION: F = 0.02;
+/// Spacing of the peak search, in Hz.
+const SCAN_STEP: F = 20.0;
+/// Formants are searched below this frequency.
+const MAX_FREQUENCY: F = 5500.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Formant {
+    /// Hz.
+    pub frequency: F,
+    /// -3 dB bandwidth in Hz.
+    pub bandwidth: F,
+}
+
+/// Frequency response of the tract from glottis to lips and nostrils.
+pub struct TransferFunction {
+    impulse_response: Vec<F>,
+    sample_rate: F,
+}
+
+impl TransferFunction {
+    pub fn new(impulse_response: Vec<F>, sample_rate: F) -> Self {
+        Self {
+            impulse_response,
+            sample_rate,
+        }
+    }
+
+    /// The current shape of `tract`, which lags behind `ShapeSource` while moving.
+    pub fn from_tract(tract: &Tract) -> Self {
+        let n = (tract.step_rate() * IMPULSE_RESPONSE_DURATION) as usize;
+        Self::new(crate::tract_impulse_response(n, tract), tract.step_rate())
+    }
+
+    /// The shape `source` settles into, with the velum diameter `velum`.
+    pub fn from_shape(source: &ShapeSource, velum: F, step_rate: F) -> Self {
+        let mut diameter = Diameter::new(source);
+        source.compute_diameter(&mut diameter);
+        diameter.nose[0] = velum;
+        let mut reflections = Reflections::new(source.length, source.nose_length);
+        diameter.compute_reflections(&mut reflections);
+        let params = OtherParams::new(source.nose_start, step_rate);
+
+        let n = (step_rate * IMPULSE_RESPONSE_DURATION) as usize;
+        Self::new(
+            reflections_impulse_response(n, source, &params, &reflections),
+            step_rate,
+        )
+    }
+
+    /// `(re, im)` at `frequency` Hz.
+    pub fn response(&self, frequency: F) -> (F, F) {
+        let (sin, cos) = (-TAU * frequency / self.sample_rate).sin_cos();
+        let (mut re, mut im) = (0.0, 0.0);
+        let (mut rot_re, mut rot_im) = (1.0, 0.0);
+        for &x in &self.impulse_response {
+            re += x * rot_re;
+            im += x * rot_im;
+            (rot_re, rot_im) = (rot_re * cos - rot_im * sin, rot_re * sin + rot_im * cos);
+        }
+        (re, im)
+    }
+
+    pub fn magnitude(&self, frequency: F) -> F {
+        let (re, im) = self.response(frequency);
+        re.hypot(im)
+    }
+
+    pub fn magnitude_db(&self, frequency: F) -> F {
+        20.0 * self.magnitude(frequency).log10()
+    }
+
+    /// Resonance peaks below 5.5 kHz in ascending order.
+    /// Shoulders without a -3 dB dip on either side are skipped.
+    pub fn formants(&self) -> Vec<Formant> {
+        let max_frequency = MAX_FREQUENCY.min(self.sample_rate * 0.5);
+        let frequencies: Vec<F> = (1..)
+            .map(|i| i as F * SCAN_STEP)
+            .take_while(|f| *f < max_frequency)
+            .collect();
+        let magnitudes: Vec<F> = frequencies.iter().map(|f| self.magnitude(*f)).collect();
+        if magnitudes.len() < 3 {
+            return Vec::new();
+        }
+
+        let mut formants = Vec::new();
+        for i in 1..magnitudes.len() - 1 {
+            if !(magnitudes[i - 1] < magnitudes[i] && magnitudes[i] >= magnitudes[i + 1]) {
+                continue;
+            }
+            let frequency =
+                self.refine_peak(frequencies[i] - SCAN_STEP, frequencies[i] + SCAN_STEP);
+            let half_power = self.magnitude(frequency) * 0.5f64.sqrt();
+            let lower = self.half_power_point(frequency, -SCAN_STEP, half_power);
+            let upper = self.half_power_point(frequency, SCAN_STEP, half_power);
+            let bandwidth = match (lower, upper) {
+                (Some(lower), Some(upper)) => upper - lower,
+                (Some(edge), None) | (None, Some(edge)) => 2.0 * (frequency - edge).abs(),
+                (None, None) => continue,
+            };
+            formants.push(Formant {
+                frequency,
+                bandwidth,
+            });
+        }
+        formants
+    }
+
+    /// Golden-section search for the maximum magnitude.
+    fn refine_peak(&self, mut low: F, mut high: F) -> F {
+        let ratio = (5.0f64.sqrt() - 1.0) * 0.5;
+        while high - low > 0.1 {
+            let a = high - (high - low) * ratio;
+            let b = low + (high - low) * ratio;
+            if self.magnitude(a) < self.magnitude(b) {
+                low = a;
+            } else {
+                high = b;
+            }
+        }
+        (low + high) * 0.5
+    }
+
+    /// Walks from `peak` by `step` until the magnitude falls below `half_power`.
+    /// `None` if it rises again or the search leaves the analysed range first.
+    fn half_power_point(&self, peak: F, step: F, half_power: F) -> Option<F> {
+        let mut prev = (peak, self.magnitude(peak));
+        loop {
+            let frequency = prev.0 + step;
+            if !(0.0..self.sample_rate * 0.5).contains(&frequency) {
+                return None;
+            }
+            let magnitude = self.magnitude(frequency);
+            if magnitude < half_power {
+                // Bisect between the last point above and the first point below.
+                let (mut above, mut below) = (prev.0, frequency);
+                while (below - above).abs() > 0.1 {
+                    let middle = (above + below) * 0.5;
+                    if self.magnitude(middle) < half_power {
+                        below = middle;
+                    } else {
+                        above = middle;
+                    }
+                }
+                return Some((above + below) * 0.5);
+            }
+            if magnitude > prev.1 {
+                return None;
+            }
+            prev = (frequency, magnitude);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Fit {
+    pub tongue: (F, F),
+    pub constrictions: Vec<(F, F)>,
+    pub formants: Vec<Formant>,
+    /// Sum of squared errors in octaves. A missing formant counts as one octave.
+    pub error: F,
+}
+
+/// Searches the tongue and the diameters of `constrictions` so that the lowest formants
+/// approach `targets` in Hz. The indices of `constrictions` stay fixed and their diameters
+/// are only starting points. Other fields of `source` define the tract geometry.
+pub fn fit(
+    source: &ShapeSource,
+    velum: F,
+    step_rate: F,
+    targets: &[F],
+    constrictions: &[(F, F)],
+) -> Fit {
+    const CONSTRICTION_RANGE: (F, F) = (0.4, 3.0);
+    let mut source = source.clone();
+    let tongue_index = (source.blade_start as F + 2.0, source.tip_start as F - 3.0);
+    let tongue_diameter = (2.05, 3.5);
+
+    // Parameters normalized to 0..1.
+    let denormalize = |range: (F, F), x: F| range.0 + (range.1 - range.0) * x.clamp(0.0, 1.0);
+    let normalize = |range: (F, F), x: F| ((x - range.0) / (range.1 - range.0)).clamp(0.0, 1.0);
+    let mut evaluate = |params: &[F]| {
+        source.tongue = source.tongue_clamp(
+            denormalize(tongue_index, params[0]),
+            denormalize(tongue_diameter, params[1]),
+        );
+        source.other_constrictions.clear();
+        source.other_constrictions.extend(
+            constrictions
+                .iter()
+                .zip(&params[2..])
+                .map(|(c, x)| (c.0, denormalize(CONSTRICTION_RANGE, *x))),
+        );
+        let formants = TransferFunction::from_shape(&source, velum, step_rate).formants();
+        let error = targets
+            .iter()
+            .enumerate()
+            .map(|(i, target)| {
+                formants
+                    .get(i)
+                    .map_or(1.0, |f| (f.frequency / target).log2().powi(2))
+            })
+            .sum();
+        Fit {
+            tongue: source.tongue,
+            constrictions: source.other_constrictions.clone(),
+            formants,
+            error,
+        }
+    };
+
+    // A coarse grid over the tongue, then a compass search over all parameters.
+    let mut params: Vec<F> = [0.5, 0.5]
+        .into_iter()
+        .chain(
+            constrictions
+                .iter()
+                .map(|c| normalize(CONSTRICTION_RANGE, c.1)),
+        )
+        .collect();
+    let mut best = evaluate(&params);
+    for i in 0..=6 {
+        for j in 0..=4 {
+            let mut candidate = params.clone();
+            candidate[0] = i as F / 6.0;
+            candidate[1] = j as F / 4.0;
+            let fit = evaluate(&candidate);
+            if fit.error < best.error {
+                best = fit;
+                params = candidate;
+            }
+        }
+    }
+    let mut step = 0.1;
+    while step > 0.002 {
+        let mut improved = false;
+        for k in 0..params.len() {
+            for direction in [-1.0, 1.0] {
+                let mut candidate = params.clone();
+                candidate[k] = (candidate[k] + direction * step).clamp(0.0, 1.0);
+                let fit = evaluate(&candidate);
+                if fit.error < best.error {
+                    best = fit;
+                    params = candidate;
+                    improved = true;
+                }
+            }
+        }
+        if !improved {
+            step *= 0.5;
+        }
+    }
+    best
+}
+
+#[test]
+fn test() {
+    use crate::{phoneme::phoneme, Benihora};
+
+    let tract = Benihora::new(2.0, 48000.0, 1.0, 0, false).tract;
+    let step_rate = tract.step_rate();
+    let formants_of = |symbol| {
+        let p = phoneme(symbol).unwrap();
+        let mut source = tract.source.clone();
+        source.tongue = p.tongue.unwrap();
+        source.other_constrictions = p.constrictions.to_vec();
+        TransferFunction::from_shape(&source, p.velum, step_rate).formants()
+    };
+
+    // The tract at rest is already in the shape of `a`.
+    let a = formants_of("a");
+    let rest = TransferFunction::from_tract(&tract).formants();
+    assert_eq!(a.len(), rest.len());
+    for (a, rest) in a.iter().zip(&rest) {
+        assert!((a.frequency - rest.frequency).abs() < 1.0);
+    }
+
+    // Catches unintended changes to `ShapeSource::compute_diameter` and `compute_reflections`.
+    assert!(a.len() >= 4);
+    for (formant, expected) in a.iter().zip([818.0, 1225.0, 3015.0, 3863.0]) {
+        assert!((formant.frequency - expected).abs() < 5.0, "{:?}", a);
+    }
+    assert!(a.windows(2).all(|w| w[0].frequency < w[1].frequency));
+    assert!(a
+        .iter()
+        .all(|f| 0.0 < f.bandwidth && f.bandwidth < f.frequency));
+
+    // `i` is close and front: low F1, high F2.
+    let i = formants_of("i");
+    assert!(i[0].frequency < a[0].frequency);
+    assert!(i[1].frequency > a[1].frequency);
+
+    // Recover the shape of `i` from its formants.
+    let targets: Vec<_> = i[..3].iter().map(|f| f.frequency).collect();
+    let fit = fit(&tract.source, 0.01, step_rate, &targets, &[]);
+    assert!(fit.error < 1e-3, "{:?}", fit);
+
+    // Too low a sample rate to scan.
+    assert!(TransferFunction::new(vec![1.0], 40.0).formants().is_empty());
+}
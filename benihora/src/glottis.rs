@@ -1,4 +1,6 @@
-use std::f64::consts::PI;
+use std::{collections::VecDeque, f64::consts::PI};
+
+use resampler::{Quality, Resampler};
 
 use crate::{lerp, noise::Noise, wiggle::Wiggle};
 
@@ -7,20 +9,18 @@ use super::F;
 pub struct Glottis {
     pub(crate) aspiration_noise: Noise,
     phase: F,
-    waveform: WaveformIntegral,
+    /// `TensenessLf` unless replaced.
+    pub source: Box<dyn GlottalSource>,
     sample_rate: F,
     wiggle: Wiggle,
-    last_integral: F,
 }
 
 impl Glottis {
     pub fn new(sample_rate: F, seed: u32) -> Self {
-        let waveform = WaveformIntegral::new(&Waveform::new(0.6));
         Self {
             aspiration_noise: Noise::new(seed + 1, sample_rate, 500.0),
             phase: 0.0,
-            last_integral: waveform.compute(0.0),
-            waveform,
+            source: Box::new(TensenessLf::new()),
             sample_rate,
             wiggle: Wiggle::new(1.0 / sample_rate, 10.0, seed + 2),
         }
@@ -44,14 +44,10 @@ impl Glottis {
         self.phase += d;
         if 1.0 < self.phase {
             self.phase -= 1.0;
-            self.waveform = WaveformIntegral::new(&Waveform::new(tenseness));
-            self.last_integral = self.waveform.compute(0.0);
+            self.source.start_period(tenseness);
         }
 
-        // let out = intensity * loudness * self.waveform.normalized_lf_waveform(self.phase);
-        let integral = self.waveform.compute(self.phase);
-        let out = intensity * loudness * (integral - self.last_integral) / d;
-        self.last_integral = integral;
+        let out = intensity * loudness * self.source.process(self.phase, d);
 
         let noise = self.get_noise_modulator(tenseness * intensity) * noise;
         let aspiration = intensity
@@ -69,6 +65,344 @@ impl Glottis {
     }
 }
 
+/// Voiced excitation of the tract, i.e. the derivative of the glottal flow.
+pub trait GlottalSource: Send + Sync {
+    /// Called when a new period begins.
+    fn start_period(&mut self, _tenseness: F) {}
+
+    /// Mean of the flow derivative from `phase - d` to `phase`, where `phase` is in 0..1
+    /// and `d` is its increment per sample. Peaks around -1 at the glottal closure.
+    fn process(&mut self, phase: F, d: F) -> F;
+
+    /// Next sample of a source driven from outside. Others ignore it.
+    fn set_input(&mut self, _input: F) {}
+}
+
+/// Band-limits a pulse shape by differencing its flow, the integral of the derivative.
+#[derive(Debug, Clone, Default)]
+struct FlowDifferentiator {
+    last_flow: F,
+}
+
+impl FlowDifferentiator {
+    fn reset(&mut self, flow_at_zero: F) {
+        self.last_flow = flow_at_zero;
+    }
+
+    fn process(&mut self, flow: F, d: F) -> F {
+        let out = (flow - self.last_flow) / d;
+        self.last_flow = flow;
+        out
+    }
+}
+
+/// The original model: an approximate LF pulse whose shape follows tenseness.
+pub struct TensenessLf {
+    waveform: WaveformIntegral,
+    differentiator: FlowDifferentiator,
+}
+
+impl TensenessLf {
+    pub fn new() -> Self {
+        let waveform = WaveformIntegral::new(&Waveform::new(0.6));
+        let mut differentiator = FlowDifferentiator::default();
+        differentiator.reset(waveform.compute(0.0));
+        Self {
+            waveform,
+            differentiator,
+        }
+    }
+}
+
+impl Default for TensenessLf {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GlottalSource for TensenessLf {
+    fn start_period(&mut self, tenseness: F) {
+        self.waveform = WaveformIntegral::new(&Waveform::new(tenseness));
+        self.differentiator.reset(self.waveform.compute(0.0));
+    }
+
+    fn process(&mut self, phase: F, d: F) -> F {
+        self.differentiator.process(self.waveform.compute(phase), d)
+    }
+}
+
+/// Rosenberg's trigonometric pulse. Ignores tenseness.
+pub struct Rosenberg {
+    /// Fraction of the period the glottis is open.
+    pub open_quotient: F,
+    /// Opening time over closing time.
+    pub speed_quotient: F,
+    differentiator: FlowDifferentiator,
+}
+
+impl Rosenberg {
+    pub fn new(open_quotient: F, speed_quotient: F) -> Self {
+        Self {
+            open_quotient,
+            speed_quotient,
+            differentiator: FlowDifferentiator::default(),
+        }
+    }
+
+    /// Scaled so that the derivative falls to -1 at the closure.
+    fn flow(&self, t: F) -> F {
+        let open_quotient = self.open_quotient.clamp(0.05, 1.0);
+        let tn = open_quotient / (1.0 + self.speed_quotient.max(0.0));
+        let tp = open_quotient - tn;
+        let flow = if t < tp {
+            0.5 * (1.0 - (PI * t / tp).cos())
+        } else if t < open_quotient {
+            (PI * 0.5 * (t - tp) / tn).cos()
+        } else {
+            0.0
+        };
+        flow * 2.0 * tn / PI
+    }
+}
+
+impl GlottalSource for Rosenberg {
+    fn start_period(&mut self, _tenseness: F) {
+        self.differentiator.reset(self.flow(0.0));
+    }
+
+    fn process(&mut self, phase: F, d: F) -> F {
+        self.differentiator.process(self.flow(phase), d)
+    }
+}
+
+/// Timing of an LF pulse relative to its period.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LfShape {
+    /// Open quotient, the instant of the main excitation `Te`.
+    pub oq: F,
+    /// Asymmetry `Tp / Te`.
+    pub alpha: F,
+    /// Return phase `Ta`.
+    pub ta: F,
+}
+
+impl LfShape {
+    /// Fant's regression from the shape parameter Rd, which ranges from about 0.3 (pressed)
+    /// to 2.7 (breathy).
+    pub fn from_rd(rd: F) -> Self {
+        let rd = rd.clamp(0.3, 2.7);
+        let ra = -0.01 + 0.048 * rd;
+        let rk = 0.224 + 0.118 * rd;
+        let rg = (rk / 4.0) * (0.5 + 1.2 * rk) / (0.11 * rd - ra * (0.5 + 1.2 * rk));
+        let tp = 1.0 / (2.0 * rg);
+        let te = tp * (1.0 + rk);
+        Self {
+            oq: te,
+            alpha: tp / te,
+            ta: ra,
+        }
+    }
+}
+
+/// The Liljencrants-Fant model with its growth and return constants solved exactly.
+pub struct Lf {
+    /// Takes effect at the next period.
+    pub shape: LfShape,
+    /// Follow tenseness through `LfShape::from_rd` instead of `shape`.
+    pub follow_tenseness: bool,
+    coefficients: LfCoefficients,
+    differentiator: FlowDifferentiator,
+}
+
+impl Lf {
+    pub fn new(shape: LfShape) -> Self {
+        let coefficients = LfCoefficients::new(&shape);
+        let mut differentiator = FlowDifferentiator::default();
+        differentiator.reset(coefficients.flow(0.0));
+        Self {
+            shape,
+            follow_tenseness: false,
+            coefficients,
+            differentiator,
+        }
+    }
+}
+
+impl GlottalSource for Lf {
+    fn start_period(&mut self, tenseness: F) {
+        if self.follow_tenseness {
+            self.shape = LfShape::from_rd(3.0 * (1.0 - tenseness));
+        }
+        if self.coefficients.shape != self.shape {
+            self.coefficients = LfCoefficients::new(&self.shape);
+        }
+        self.differentiator.reset(self.coefficients.flow(0.0));
+    }
+
+    fn process(&mut self, phase: F, d: F) -> F {
+        self.differentiator
+            .process(self.coefficients.flow(phase), d)
+    }
+}
+
+struct LfCoefficients {
+    shape: LfShape,
+    te: F,
+    ta: F,
+    omega: F,
+    alpha: F,
+    epsilon: F,
+    e0: F,
+}
+
+impl LfCoefficients {
+    fn new(shape: &LfShape) -> Self {
+        let te = shape.oq.clamp(0.1, 0.95);
+        let tp = (shape.alpha * te).clamp(0.1 * te, 0.99 * te);
+        let ta = shape.ta.clamp(1.0e-4, (1.0 - te) * 0.5);
+        let omega = PI / tp;
+
+        // epsilon * ta = 1 - exp(-epsilon * (1 - te)), by fixed-point iteration from 1 / ta.
+        let mut epsilon = 1.0 / ta;
+        for _ in 0..50 {
+            epsilon = (1.0 - (-epsilon * (1.0 - te)).exp()) / ta;
+        }
+
+        let mut coefficients = Self {
+            shape: *shape,
+            te,
+            ta,
+            omega,
+            alpha: 0.0,
+            epsilon,
+            e0: 0.0,
+        };
+        // The flow must return to zero at the end of the period; the net flow decreases
+        // monotonically with alpha.
+        let (mut low, mut high) = (-50.0 / te, 50.0 / te);
+        for _ in 0..100 {
+            coefficients.set_alpha((low + high) * 0.5);
+            if coefficients.flow(1.0) > 0.0 {
+                low = coefficients.alpha;
+            } else {
+                high = coefficients.alpha;
+            }
+        }
+        coefficients
+    }
+
+    /// `e0` follows from the derivative being -1 at `te`.
+    fn set_alpha(&mut self, alpha: F) {
+        self.alpha = alpha;
+        self.e0 = -1.0 / ((alpha * self.te).exp() * (self.omega * self.te).sin());
+    }
+
+    fn flow(&self, t: F) -> F {
+        let open = |t: F| {
+            self.e0
+                * ((self.alpha * t).exp()
+                    * (self.alpha * (self.omega * t).sin() - self.omega * (self.omega * t).cos())
+                    + self.omega)
+                / (self.alpha.powi(2) + self.omega.powi(2))
+        };
+        if t <= self.te {
+            open(t)
+        } else {
+            let t = t - self.te;
+            let end = (-self.epsilon * (1.0 - self.te)).exp();
+            open(self.te)
+                - ((1.0 - (-self.epsilon * t).exp()) / self.epsilon - t * end)
+                    / (self.epsilon * self.ta)
+        }
+    }
+}
+
+/// One period of flow derivative, played at the glottal frequency.
+pub struct Sampled {
+    /// Cumulative flow at each sample boundary, with the mean removed from the derivative.
+    flow: Vec<F>,
+    differentiator: FlowDifferentiator,
+}
+
+impl Sampled {
+    pub fn new(samples: &[F]) -> Self {
+        assert!(!samples.is_empty());
+        let mean = samples.iter().sum::<F>() / samples.len() as F;
+        let dt = 1.0 / samples.len() as F;
+        let mut flow = Vec::with_capacity(samples.len() + 1);
+        flow.push(0.0);
+        for x in samples {
+            flow.push(flow.last().unwrap() + (x - mean) * dt);
+        }
+        Self {
+            flow,
+            differentiator: FlowDifferentiator::default(),
+        }
+    }
+
+    fn flow(&self, t: F) -> F {
+        let position = t.clamp(0.0, 1.0) * (self.flow.len() - 1) as F;
+        let i = (position as usize).min(self.flow.len() - 2);
+        lerp(self.flow[i], self.flow[i + 1], position - i as F)
+    }
+}
+
+impl GlottalSource for Sampled {
+    fn start_period(&mut self, _tenseness: F) {
+        self.differentiator.reset(self.flow(0.0));
+    }
+
+    fn process(&mut self, phase: F, d: F) -> F {
+        self.differentiator.process(self.flow(phase), d)
+    }
+}
+
+/// Passes through samples given by `set_input`, e.g. another synth for talk-box effects.
+/// Frequency and tenseness only affect the aspiration noise.
+pub struct External {
+    resampler: Resampler<F>,
+    /// Inputs not yet taken by the resampler.
+    inputs: VecDeque<F>,
+    primed: usize,
+    input_sample_rate: F,
+}
+
+impl External {
+    /// `set_input` is called at `input_sample_rate` and resampled to the glottis `sample_rate`.
+    pub fn new(input_sample_rate: F, sample_rate: F) -> Self {
+        // The glottis may take a few inputs ahead of `set_input`; the primed zeros cover that.
+        let primed = (input_sample_rate / sample_rate).ceil() as usize + 1;
+        let mut inputs = VecDeque::with_capacity(primed * 2 + 2);
+        inputs.extend(std::iter::repeat_n(0.0, primed));
+        Self {
+            resampler: Resampler::new(input_sample_rate, sample_rate, Quality::Medium),
+            inputs,
+            primed,
+            input_sample_rate,
+        }
+    }
+
+    /// Delay of the input in seconds.
+    pub fn delay(&self) -> F {
+        (self.resampler.delay() + self.primed as F) / self.input_sample_rate
+    }
+}
+
+impl GlottalSource for External {
+    fn process(&mut self, _phase: F, _d: F) -> F {
+        let inputs = &mut self.inputs;
+        self.resampler
+            .process(|| inputs.pop_front().unwrap_or_default())
+    }
+
+    fn set_input(&mut self, input: F) {
+        if self.inputs.len() == self.inputs.capacity() {
+            self.inputs.pop_front();
+        }
+        self.inputs.push_back(input);
+    }
+}
+
 /// Liljencrants-Fant waveform
 struct Waveform {
     alpha: F,
@@ -183,3 +517,57 @@ impl WaveformIntegral {
         }
     }
 }
+
+#[test]
+fn test() {
+    // One period at 100 samples per period.
+    let period = |source: &mut dyn GlottalSource| {
+        source.start_period(0.6);
+        let d = 0.01;
+        (1..=100)
+            .map(|i| source.process(i as F * d, d))
+            .collect::<Vec<_>>()
+    };
+    let sources: [Box<dyn GlottalSource>; 4] = [
+        Box::new(TensenessLf::new()),
+        Box::new(Rosenberg::new(0.6, 2.0)),
+        Box::new(Lf::new(LfShape::from_rd(1.0))),
+        Box::new(Sampled::new(&[1.0, 0.0, -1.0, 0.0])),
+    ];
+    for mut source in sources {
+        let y = period(source.as_mut());
+        // Hardly any net flow over a period, and the closure dips to about -1.
+        assert!(y.iter().sum::<F>().abs() * 0.01 < 0.01);
+        let min = y.iter().cloned().fold(F::INFINITY, F::min);
+        assert!(-1.2 < min && min < -0.4, "{}", min);
+    }
+
+    // The exact model stays close to the original approximation.
+    let mut lf = Lf::new(LfShape::from_rd(2.0));
+    lf.follow_tenseness = true;
+    let a = period(&mut lf);
+    let b = period(&mut TensenessLf::new());
+    let error = a.iter().zip(&b).map(|(a, b)| (a - b).powi(2)).sum::<F>();
+    let energy = b.iter().map(|b| b.powi(2)).sum::<F>();
+    assert!(error < energy * 0.05, "{} {}", error, energy);
+    assert!((lf.shape.oq - LfShape::from_rd(1.2).oq).abs() < 1e-9);
+
+    // Driven at the output rate, as `Benihora` pulls the glottis at its inner rate.
+    for (input_sample_rate, sample_rate) in [(44100.0, 32000.0), (44100.0, 96000.0)] {
+        let mut external = External::new(input_sample_rate, sample_rate);
+        let delay = external.delay();
+        let sine = |t: F| (t * 1000.0 * std::f64::consts::TAU).sin();
+        let mut m = 0;
+        for n in 0..4410 {
+            external.set_input(sine(n as F / input_sample_rate));
+            while (m as F) < (n + 1) as F * sample_rate / input_sample_rate {
+                let y = external.process(0.5, 0.01);
+                if 400 < m {
+                    let expected = sine(m as F / sample_rate - delay);
+                    assert!((y - expected).abs() < 1e-3, "{} {}", y, expected);
+                }
+                m += 1;
+            }
+        }
+    }
+}
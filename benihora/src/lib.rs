@@ -1,9 +1,13 @@
 mod benihora;
+pub mod choir;
+pub mod formant;
 pub mod glottis;
 mod interval_timer;
 pub mod managed;
 mod noise;
-pub mod resample;
+pub mod phoneme;
+pub mod score;
+pub mod singer;
 pub mod tract;
 pub mod wiggle;
 
@@ -25,20 +29,19 @@ pub fn rand_f64(seed: &mut u32) -> f64 {
 }
 
 pub fn tract_impulse_response(n: usize, tract: &tract::Tract) -> Vec<F> {
-    let mut state = tract::State::new(tract.source.length, tract.source.nose_length);
+    reflections_impulse_response(n, &tract.source, &tract.params, &tract.new_reflections)
+}
+
+pub(crate) fn reflections_impulse_response(
+    n: usize,
+    source: &tract::ShapeSource,
+    params: &tract::OtherParams,
+    reflections: &tract::Reflections,
+) -> Vec<F> {
+    let mut state = tract::State::new(source.length, source.nose_length);
     impulse_response(n, |x| {
-        let lip_output = state.process_mouth(
-            &tract.params,
-            &tract.new_reflections,
-            &tract.new_reflections,
-            0.0,
-            x,
-        );
-        let nose_out = state.process_nose(
-            &tract.params,
-            &tract.new_reflections,
-            tract.new_reflections.nose[0],
-        );
+        let lip_output = state.process_mouth(params, reflections, reflections, 0.0, x);
+        let nose_out = state.process_nose(params, reflections, reflections.nose[0]);
         lip_output + nose_out
     })
 }
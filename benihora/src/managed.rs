@@ -8,6 +8,7 @@ pub struct BenihoraManaged {
     tenseness: Tenseness,
     pub intensity: Intensity,
     loudness: Loudness,
+    voicing: F,
     pub benihora: Benihora,
     update_timer: IntervalTimer,
     dtime: F,
@@ -23,6 +24,7 @@ impl BenihoraManaged {
             tenseness: Tenseness::new(interval, seed + 2, 0.6),
             intensity: Intensity::new(0.0),
             loudness: Loudness::new(0.6f64.powf(0.25)),
+            voicing: 1.0,
             benihora: Benihora::new(sound_speed, sample_rate, 1.0, seed, true),
             update_timer: IntervalTimer::new_overflowed(interval),
             dtime: 1.0 / sample_rate,
@@ -34,7 +36,13 @@ impl BenihoraManaged {
     pub fn set_tenseness(&mut self, tenseness: F) {
         let tenseness = tenseness.clamp(0.0, 1.0);
         self.tenseness.target_tenseness = tenseness;
-        self.loudness.target = tenseness.powf(0.25);
+        self.loudness.target = tenseness.powf(0.25) * self.voicing;
+    }
+
+    /// Scales the voiced part of the glottal source, 0 - 1. Ramped like the loudness.
+    pub fn set_voicing(&mut self, voicing: F) {
+        self.voicing = voicing.clamp(0.0, 1.0);
+        self.set_tenseness(self.tenseness.target_tenseness);
     }
 
     pub fn process(&mut self, current_time: F) -> F {
@@ -64,6 +72,8 @@ pub struct Frequency {
 
     pub vibrato_amount: F,
     pub vibrato_frequency: F,
+    /// In cycles.
+    pub vibrato_phase: F,
     pub wobble_amount: F,
     wiggles: [Wiggle; 2],
 }
@@ -77,6 +87,7 @@ impl Frequency {
             smooth_frequency: frequency,
             vibrato_amount,
             vibrato_frequency,
+            vibrato_phase: 0.0,
             wobble_amount: 1.0,
             wiggles: [
                 Wiggle::new(dtime / 4.0, 4.07 * 5.0, seed + 1),
@@ -90,7 +101,8 @@ impl Frequency {
     }
 
     pub fn update(&mut self, time: F) {
-        let mut vibrato = self.vibrato_amount * (TAU * time * self.vibrato_frequency).sin();
+        let mut vibrato = self.vibrato_amount
+            * (TAU * (time * self.vibrato_frequency + self.vibrato_phase)).sin();
         vibrato += self.wobble_amount
             * (0.01 * self.wiggles[0].process() + 0.02 * self.wiggles[1].process());
         for _ in 0..3 {
@@ -145,6 +157,8 @@ impl Tenseness {
 pub struct Intensity {
     old_intensity: F,
     new_intensity: F,
+    /// Reached while sounding, 0 - 1.
+    pub target: F,
     pub up_velocity: F,
     pub down_velocity: F,
 }
@@ -154,6 +168,7 @@ impl Intensity {
         Self {
             old_intensity: intensity,
             new_intensity: intensity,
+            target: 1.0,
             up_velocity: 3.25,
             down_velocity: 5.0,
         }
@@ -161,8 +176,12 @@ impl Intensity {
 
     pub fn update(&mut self, sound: bool, interval: f64) {
         self.old_intensity = self.new_intensity;
-        if sound {
-            self.new_intensity += interval * self.up_velocity;
+        if sound && self.new_intensity < self.target {
+            self.new_intensity =
+                (self.new_intensity + interval * self.up_velocity).min(self.target);
+        } else if sound {
+            self.new_intensity =
+                (self.new_intensity - interval * self.down_velocity).max(self.target);
         } else {
             self.new_intensity -= interval * self.down_velocity;
         }
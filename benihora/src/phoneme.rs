@@ -0,0 +1,374 @@
+//! Phoneme inventory and articulation over time.
+
+use crate::{lerp, tract::DEFAULT_TONGUE, BenihoraManaged, F};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Manner {
+    Silence,
+    Vowel,
+    Nasal,
+    Fricative,
+    Plosive,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Phoneme {
+    pub symbol: &'static str,
+    pub manner: Manner,
+    /// `ShapeSource::tongue`. `None` follows the neighbouring vowel.
+    pub tongue: Option<(F, F)>,
+    /// `ShapeSource::other_constrictions`.
+    pub constrictions: &'static [(F, F)],
+    /// `Tract::set_velum_target`.
+    pub velum: F,
+    pub voiced: bool,
+    pub tenseness: F,
+    /// Seconds to move from the previous phoneme.
+    pub transition: F,
+    /// Default length in seconds, including the transition.
+    pub duration: F,
+}
+
+const LIPS: F = 41.0;
+const TEETH: F = 41.5;
+const ALVEOLAR: F = 36.0;
+const POSTALVEOLAR: F = 33.0;
+const VELAR: F = 26.0;
+const VELUM_CLOSED: F = 0.01;
+const VELUM_OPEN: F = 0.4;
+/// Diameter at which a constriction leaves the tract unchanged.
+const OPEN: F = 3.3;
+
+/// Constrictions an `Articulation` can hold; later ones of a phoneme are ignored.
+pub const MAX_CONSTRICTIONS: usize = 2;
+
+const fn vowel(symbol: &'static str, tongue: (F, F), lips: &'static [(F, F)]) -> Phoneme {
+    Phoneme {
+        symbol,
+        manner: Manner::Vowel,
+        tongue: Some(tongue),
+        constrictions: lips,
+        velum: VELUM_CLOSED,
+        voiced: true,
+        tenseness: 0.6,
+        transition: 0.08,
+        duration: 0.25,
+    }
+}
+
+const fn consonant(
+    symbol: &'static str,
+    manner: Manner,
+    constrictions: &'static [(F, F)],
+    voiced: bool,
+) -> Phoneme {
+    let (velum, transition, duration) = match manner {
+        Manner::Nasal => (VELUM_OPEN, 0.03, 0.08),
+        Manner::Fricative => (VELUM_CLOSED, 0.03, 0.1),
+        _ => (VELUM_CLOSED, 0.02, 0.06),
+    };
+    Phoneme {
+        symbol,
+        manner,
+        tongue: None,
+        constrictions,
+        velum,
+        voiced,
+        tenseness: 0.6,
+        transition,
+        duration,
+    }
+}
+
+pub const PHONEMES: &[Phoneme] = &[
+    Phoneme {
+        symbol: "_",
+        manner: Manner::Silence,
+        tongue: None,
+        constrictions: &[],
+        velum: VELUM_CLOSED,
+        voiced: false,
+        tenseness: 0.6,
+        transition: 0.05,
+        duration: 0.2,
+    },
+    vowel("a", DEFAULT_TONGUE, &[]),
+    vowel("e", (19.4, 3.43), &[]),
+    vowel("i", (27.2, 2.2), &[]),
+    vowel("o", (14.0, 2.09), &[(LIPS, 1.4)]),
+    vowel("u", (22.8, 2.05), &[(LIPS, 1.0)]),
+    consonant("m", Manner::Nasal, &[(LIPS, 0.0)], true),
+    consonant("n", Manner::Nasal, &[(ALVEOLAR, 0.0)], true),
+    consonant("ng", Manner::Nasal, &[(VELAR, 0.0)], true),
+    consonant("f", Manner::Fricative, &[(TEETH, 0.5)], false),
+    consonant("v", Manner::Fricative, &[(TEETH, 0.5)], true),
+    consonant("s", Manner::Fricative, &[(ALVEOLAR, 0.45)], false),
+    consonant("z", Manner::Fricative, &[(ALVEOLAR, 0.45)], true),
+    consonant("sh", Manner::Fricative, &[(POSTALVEOLAR, 0.5)], false),
+    consonant("zh", Manner::Fricative, &[(POSTALVEOLAR, 0.5)], true),
+    // Breathy voice rather than a constriction.
+    Phoneme {
+        tenseness: 0.0,
+        ..consonant("h", Manner::Fricative, &[], true)
+    },
+    consonant("p", Manner::Plosive, &[(LIPS, 0.0)], false),
+    consonant("b", Manner::Plosive, &[(LIPS, 0.0)], true),
+    consonant("t", Manner::Plosive, &[(ALVEOLAR, 0.0)], false),
+    consonant("d", Manner::Plosive, &[(ALVEOLAR, 0.0)], true),
+    consonant("k", Manner::Plosive, &[(VELAR, 0.0)], false),
+    consonant("g", Manner::Plosive, &[(VELAR, 0.0)], true),
+];
+
+impl Phoneme {
+    /// Targets when held on its own.
+    pub fn articulation(&'static self) -> Articulation {
+        articulation(self, self.tongue.unwrap_or(DEFAULT_TONGUE))
+    }
+}
+
+pub fn phoneme(symbol: &str) -> Option<&'static Phoneme> {
+    PHONEMES.iter().find(|p| p.symbol == symbol)
+}
+
+/// Splits a romanized syllable such as `"shi"` into phonemes by longest match.
+/// Returns the unrecognized remainder on failure.
+pub fn split(text: &str) -> Result<Vec<&'static Phoneme>, String> {
+    let mut rest = text;
+    let mut phonemes = Vec::new();
+    while !rest.is_empty() {
+        let phoneme = PHONEMES
+            .iter()
+            .filter(|p| rest.starts_with(p.symbol))
+            .max_by_key(|p| p.symbol.len())
+            .ok_or_else(|| rest.to_string())?;
+        rest = &rest[phoneme.symbol.len()..];
+        phonemes.push(phoneme);
+    }
+    Ok(phonemes)
+}
+
+/// Tract and glottis targets at an instant.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Articulation {
+    pub tongue: (F, F),
+    /// Unused ones are fully open.
+    pub constrictions: [(F, F); MAX_CONSTRICTIONS],
+    pub velum: F,
+    /// Amplitude of the voice, 0 - 1.
+    pub voicing: F,
+    pub tenseness: F,
+}
+
+impl Articulation {
+    /// Constrictions that aren't fully open.
+    pub fn closed_constrictions(&self) -> impl Iterator<Item = (F, F)> + '_ {
+        self.constrictions.iter().copied().filter(|c| c.1 < OPEN)
+    }
+
+    /// The tract is only reshaped when the tongue or constrictions have changed.
+    /// Doesn't allocate once `Tract::reserve_constrictions` covers `MAX_CONSTRICTIONS`.
+    pub fn apply(&self, benihora: &mut BenihoraManaged) {
+        let tract = &mut benihora.benihora.tract;
+        let tongue = tract.source.tongue_clamp(self.tongue.0, self.tongue.1);
+        if tract.source.tongue != tongue
+            || !tract
+                .source
+                .other_constrictions
+                .iter()
+                .copied()
+                .eq(self.closed_constrictions())
+        {
+            tract.source.tongue = tongue;
+            tract.source.other_constrictions.clear();
+            tract
+                .source
+                .other_constrictions
+                .extend(self.closed_constrictions());
+            tract.update_diameter();
+        }
+        tract.set_velum_target(self.velum);
+        benihora.set_tenseness(self.tenseness);
+        benihora.set_voicing(self.voicing);
+        benihora.sound = self.voicing > 0.0;
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Segment {
+    phoneme: &'static Phoneme,
+    start: F,
+    duration: F,
+    tongue: (F, F),
+}
+
+/// A sequence of phonemes laid out in time.
+#[derive(Debug, Clone, Default)]
+pub struct PhonemeTrack {
+    segments: Vec<Segment>,
+}
+
+impl PhonemeTrack {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whitespace separated symbols, e.g. `"m a n a b u"`.
+    /// `speed` scales the default durations; 1.0 is normal.
+    /// Returns the first unknown symbol on failure.
+    pub fn parse(text: &str, speed: F) -> Result<Self, String> {
+        let mut track = Self::new();
+        for symbol in text.split_whitespace() {
+            let phoneme = phoneme(symbol).ok_or_else(|| symbol.to_string())?;
+            track.push(phoneme, phoneme.duration / speed);
+        }
+        Ok(track)
+    }
+
+    pub fn push(&mut self, phoneme: &'static Phoneme, duration: F) {
+        let start = self.duration();
+        self.segments.push(Segment {
+            phoneme,
+            start,
+            duration,
+            tongue: DEFAULT_TONGUE,
+        });
+        self.resolve_tongues();
+    }
+
+    pub fn duration(&self) -> F {
+        self.segments.last().map_or(0.0, |s| s.start + s.duration)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// Interpolated targets at `time` seconds. Silent past the end.
+    pub fn articulation(&self, time: F) -> Articulation {
+        let Some(last) = self.segments.last() else {
+            return articulation(&PHONEMES[0], DEFAULT_TONGUE);
+        };
+        if time >= self.duration() {
+            return Articulation {
+                voicing: 0.0,
+                ..articulation(last.phoneme, last.tongue)
+            };
+        }
+        let i = self
+            .segments
+            .partition_point(|s| s.start <= time)
+            .saturating_sub(1);
+        let segment = &self.segments[i];
+        let current = articulation(segment.phoneme, segment.tongue);
+        let Some(prev) = i.checked_sub(1).map(|i| &self.segments[i]) else {
+            return current;
+        };
+        let prev = articulation(prev.phoneme, prev.tongue);
+
+        let transition = segment.phoneme.transition.min(segment.duration);
+        let t = if transition > 0.0 {
+            ((time - segment.start) / transition).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        let t = t * t * (3.0 - 2.0 * t);
+        let mut constrictions = current.constrictions;
+        for (c, p) in constrictions.iter_mut().zip(prev.constrictions) {
+            // A closing constriction starts at its target and an opening one stays in place.
+            let index = if c.1 >= OPEN {
+                p.0
+            } else if p.1 >= OPEN {
+                c.0
+            } else {
+                lerp(p.0, c.0, t)
+            };
+            *c = (index, lerp(p.1, c.1, t));
+        }
+        Articulation {
+            tongue: (
+                lerp(prev.tongue.0, current.tongue.0, t),
+                lerp(prev.tongue.1, current.tongue.1, t),
+            ),
+            constrictions,
+            velum: lerp(prev.velum, current.velum, t),
+            voicing: lerp(prev.voicing, current.voicing, t),
+            tenseness: lerp(prev.tenseness, current.tenseness, t),
+        }
+    }
+
+    /// Consonants take the tongue of the next vowel, or the previous one at the end.
+    fn resolve_tongues(&mut self) {
+        let mut prev = DEFAULT_TONGUE;
+        for segment in self.segments.iter_mut() {
+            prev = segment.phoneme.tongue.unwrap_or(prev);
+            segment.tongue = prev;
+        }
+        let mut next = None;
+        for segment in self.segments.iter_mut().rev() {
+            next = segment.phoneme.tongue.or(next);
+            if let Some(tongue) = next {
+                segment.tongue = tongue;
+            }
+        }
+    }
+}
+
+fn articulation(phoneme: &'static Phoneme, tongue: (F, F)) -> Articulation {
+    let mut constrictions = [(0.0, OPEN); MAX_CONSTRICTIONS];
+    for (c, p) in constrictions.iter_mut().zip(phoneme.constrictions) {
+        *c = *p;
+    }
+    Articulation {
+        tongue,
+        constrictions,
+        velum: phoneme.velum,
+        voicing: if phoneme.voiced { 1.0 } else { 0.0 },
+        tenseness: phoneme.tenseness,
+    }
+}
+
+#[test]
+fn test() {
+    let track = PhonemeTrack::parse("k a m i s", 1.0).unwrap();
+    assert!((track.duration() - (0.06 + 0.25 + 0.08 + 0.25 + 0.1)).abs() < 1e-9);
+    assert_eq!(PhonemeTrack::parse("a x", 1.0).unwrap_err(), "x");
+
+    // The plosive anticipates the vowel and the trailing fricative keeps the last one.
+    assert_eq!(track.articulation(0.0).tongue, DEFAULT_TONGUE);
+    assert_eq!(track.articulation(0.7).tongue, (27.2, 2.2));
+    assert_eq!(track.articulation(0.0).voicing, 0.0);
+
+    // The velar closure opens into the vowel while the voice fades in.
+    let a = track.articulation(0.06 + 0.02);
+    let b = track.articulation(0.06 + 0.04);
+    assert!(0.0 < a.voicing && a.voicing < b.voicing && b.voicing < 1.0);
+    assert_eq!(a.constrictions[0].0, VELAR);
+    assert!(0.0 < a.constrictions[0].1 && a.constrictions[0].1 < b.constrictions[0].1);
+    let open = track.articulation(0.06 + 0.1);
+    assert!(open.closed_constrictions().next().is_none());
+
+    // The velum opens gradually into the nasal.
+    let a = track.articulation(0.31 + 0.01).velum;
+    let b = track.articulation(0.31 + 0.02).velum;
+    assert!(VELUM_CLOSED < a && a < b && b < VELUM_OPEN);
+    assert_eq!(track.articulation(0.31 + 0.05).velum, VELUM_OPEN);
+
+    // The lips close gradually.
+    let a = track.articulation(0.31 + 0.01).constrictions[0];
+    let b = track.articulation(0.31 + 0.02).constrictions[0];
+    assert_eq!(a.0, LIPS);
+    assert!(0.0 < b.1 && b.1 < a.1 && a.1 < OPEN);
+
+    assert_eq!(track.articulation(10.0).voicing, 0.0);
+
+    let symbols = |text| {
+        split(text)
+            .unwrap()
+            .iter()
+            .map(|p| p.symbol)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(symbols("shin"), ["sh", "i", "n"]);
+    assert_eq!(symbols("ngo"), ["ng", "o"]);
+    assert_eq!(split("kya").unwrap_err(), "ya");
+}
@@ -0,0 +1,307 @@
+//! Notes with lyrics, read from a text score or a Standard MIDI File.
+
+use crate::F;
+
+/// Lyric that continues the previous vowel.
+pub const MELISMA: &str = "-";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Note {
+    /// Seconds.
+    pub start: F,
+    /// Seconds.
+    pub duration: F,
+    pub notenum: u8,
+    /// Romanized syllable such as `"ka"`, or `MELISMA`.
+    pub syllable: String,
+}
+
+impl Note {
+    pub fn end(&self) -> F {
+        self.start + self.duration
+    }
+
+    pub fn frequency(&self) -> F {
+        440.0 * 2.0f64.powf((self.notenum as F - 69.0) / 12.0)
+    }
+}
+
+/// Monophonic notes sorted by start time.
+#[derive(Debug, Clone, Default)]
+pub struct Score {
+    pub notes: Vec<Note>,
+}
+
+impl Score {
+    /// One note per line: `<syllable> <notenum> <beats>`.
+    /// A `_` syllable is a rest, `-` continues the previous vowel,
+    /// `tempo <bpm>` changes the tempo and `#` starts a comment.
+    ///
+    /// ```text
+    /// tempo 90
+    /// sa 67 1
+    /// ku 67 1
+    /// ra 69 2
+    /// -  71 1
+    /// _  0  1
+    /// ```
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut notes = Vec::new();
+        let mut seconds_per_beat = 0.5;
+        let mut time = 0.0;
+        for (i, line) in text.lines().enumerate() {
+            let error = |message: &str| format!("line {}: {}", i + 1, message);
+            let line = line.split('#').next().unwrap();
+            let words: Vec<_> = line.split_whitespace().collect();
+            match words.as_slice() {
+                [] => {}
+                ["tempo", bpm] => {
+                    let bpm: F = bpm.parse().map_err(|_| error("invalid tempo"))?;
+                    if bpm <= 0.0 {
+                        return Err(error("invalid tempo"));
+                    }
+                    seconds_per_beat = 60.0 / bpm;
+                }
+                [syllable, notenum, beats] => {
+                    let notenum = notenum.parse().map_err(|_| error("invalid notenum"))?;
+                    let beats: F = beats.parse().map_err(|_| error("invalid length"))?;
+                    let duration = beats * seconds_per_beat;
+                    if *syllable != "_" {
+                        notes.push(Note {
+                            start: time,
+                            duration,
+                            notenum,
+                            syllable: syllable.to_string(),
+                        });
+                    }
+                    time += duration;
+                }
+                _ => return Err(error("expected `<syllable> <notenum> <beats>`")),
+            }
+        }
+        Ok(Self { notes })
+    }
+
+    /// Reads notes from all tracks and attaches lyric meta events at the same tick.
+    /// Notes without a lyric become `MELISMA`.
+    pub fn from_smf(data: &[u8]) -> Result<Self, String> {
+        let mut reader = Reader { data, position: 0 };
+        if reader.take(4)? != b"MThd" || reader.u32()? != 6 {
+            return Err("not a standard MIDI file".to_string());
+        }
+        let _format = reader.u16()?;
+        let track_count = reader.u16()?;
+        let division = reader.u16()?;
+        if division & 0x8000 != 0 {
+            return Err("SMPTE time division is not supported".to_string());
+        }
+
+        let mut events = Vec::new();
+        for _ in 0..track_count {
+            if reader.take(4)? != b"MTrk" {
+                return Err("missing track chunk".to_string());
+            }
+            let len = reader.u32()? as usize;
+            let mut track = Reader {
+                data: reader.take(len)?,
+                position: 0,
+            };
+            read_track(&mut track, &mut events)?;
+        }
+        // Stable, so events at the same tick keep their order within a track.
+        events.sort_by_key(|(tick, _)| *tick);
+
+        let mut notes: Vec<Note> = Vec::new();
+        let mut lyric: Option<(u64, String)> = None;
+        let mut tempo_tick = 0;
+        let mut tempo_time = 0.0;
+        let mut seconds_per_tick = 0.5 / division as F;
+        let time = |tick: u64, tempo_tick: u64, tempo_time: F, seconds_per_tick: F| {
+            tempo_time + (tick - tempo_tick) as F * seconds_per_tick
+        };
+        for (tick, event) in &events {
+            let now = time(*tick, tempo_tick, tempo_time, seconds_per_tick);
+            match event {
+                SmfEvent::Tempo(micros) => {
+                    tempo_time = now;
+                    tempo_tick = *tick;
+                    seconds_per_tick = *micros as F * 1.0e-6 / division as F;
+                }
+                SmfEvent::Lyric(text) => {
+                    // A lyric also applies to a note already started at this tick.
+                    match notes.last_mut() {
+                        Some(note) if note.start == now && note.syllable == MELISMA => {
+                            note.syllable = text.clone();
+                        }
+                        _ => lyric = Some((*tick, text.clone())),
+                    }
+                }
+                SmfEvent::NoteOn(notenum) => {
+                    if let Some(note) = notes.last_mut() {
+                        if note.duration.is_nan() {
+                            note.duration = now - note.start;
+                        }
+                    }
+                    let syllable = match lyric.take() {
+                        Some((lyric_tick, text)) if lyric_tick <= *tick => text,
+                        _ => MELISMA.to_string(),
+                    };
+                    notes.push(Note {
+                        start: now,
+                        duration: F::NAN,
+                        notenum: *notenum,
+                        syllable,
+                    });
+                }
+                SmfEvent::NoteOff(notenum) => {
+                    if let Some(note) = notes.last_mut() {
+                        if note.notenum == *notenum && note.duration.is_nan() {
+                            note.duration = now - note.start;
+                        }
+                    }
+                }
+            }
+        }
+        notes.retain(|note| !note.duration.is_nan() && note.duration > 0.0);
+        Ok(Self { notes })
+    }
+
+    pub fn duration(&self) -> F {
+        self.notes.last().map_or(0.0, |note| note.end())
+    }
+}
+
+enum SmfEvent {
+    Tempo(u32),
+    Lyric(String),
+    NoteOn(u8),
+    NoteOff(u8),
+}
+
+fn read_track(track: &mut Reader, events: &mut Vec<(u64, SmfEvent)>) -> Result<(), String> {
+    let mut tick = 0;
+    let mut running_status = 0;
+    while track.position < track.data.len() {
+        tick += track.var_len()? as u64;
+        let mut status = track.u8()?;
+        if status < 0x80 {
+            // Running status: the byte read is the first data byte.
+            track.position -= 1;
+            status = running_status;
+        }
+        match status {
+            0xff => {
+                let kind = track.u8()?;
+                let len = track.var_len()? as usize;
+                let data = track.take(len)?;
+                match kind {
+                    0x51 if len == 3 => {
+                        let micros = u32::from_be_bytes([0, data[0], data[1], data[2]]);
+                        events.push((tick, SmfEvent::Tempo(micros)));
+                    }
+                    0x05 => {
+                        let text = String::from_utf8_lossy(data).trim().to_lowercase();
+                        events.push((tick, SmfEvent::Lyric(text)));
+                    }
+                    0x2f => break,
+                    _ => {}
+                }
+            }
+            0xf0 | 0xf7 => {
+                let len = track.var_len()? as usize;
+                track.take(len)?;
+            }
+            0x80..=0xef => {
+                running_status = status;
+                let data_len = if matches!(status & 0xf0, 0xc0 | 0xd0) {
+                    1
+                } else {
+                    2
+                };
+                let data = track.take(data_len)?;
+                match status & 0xf0 {
+                    0x90 if data[1] > 0 => events.push((tick, SmfEvent::NoteOn(data[0]))),
+                    0x80 | 0x90 => events.push((tick, SmfEvent::NoteOff(data[0]))),
+                    _ => {}
+                }
+            }
+            _ => return Err(format!("unexpected status byte {:#x}", status)),
+        }
+    }
+    Ok(())
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let data = self
+            .data
+            .get(self.position..self.position + len)
+            .ok_or_else(|| "unexpected end of data".to_string())?;
+        self.position += len;
+        Ok(data)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        let data = self.take(2)?;
+        Ok(u16::from_be_bytes([data[0], data[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let data = self.take(4)?;
+        Ok(u32::from_be_bytes([data[0], data[1], data[2], data[3]]))
+    }
+
+    fn var_len(&mut self) -> Result<u32, String> {
+        let mut value = 0;
+        for _ in 0..4 {
+            let byte = self.u8()?;
+            value = (value << 7) | (byte & 0x7f) as u32;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err("invalid variable length quantity".to_string())
+    }
+}
+
+#[test]
+fn test() {
+    let score = Score::parse("tempo 120\nka 60 1 # comment\n\n_ 0 1\n- 62 0.5\n").unwrap();
+    assert_eq!(score.notes.len(), 2);
+    assert_eq!(score.notes[1].start, 1.0);
+    assert_eq!(score.notes[1].duration, 0.25);
+    assert_eq!(score.notes[1].syllable, MELISMA);
+    assert!((score.notes[0].frequency() - 261.626).abs() < 1e-3);
+    assert!(Score::parse("ka 60").unwrap_err().starts_with("line 1"));
+
+    // Format 0, 480 ticks per beat, 120 bpm.
+    #[rustfmt::skip]
+    let track = [
+        0x00, 0xff, 0x51, 0x03, 0x07, 0xa1, 0x20,
+        0x00, 0xff, 0x05, 0x02, b'K', b'a',
+        0x00, 0x90, 60, 100,
+        0x83, 0x60, 60, 0, // running status
+        0x00, 0x90, 62, 100,
+        0x81, 0x70, 0x80, 62, 0,
+        0x00, 0xff, 0x2f, 0x00,
+    ];
+    let mut data = b"MThd\0\0\0\x06\0\0\0\x01\x01\xe0MTrk".to_vec();
+    data.extend((track.len() as u32).to_be_bytes());
+    data.extend(track);
+    let score = Score::from_smf(&data).unwrap();
+    assert_eq!(score.notes.len(), 2);
+    assert_eq!(score.notes[0].syllable, "ka");
+    assert_eq!(score.notes[0].duration, 0.5);
+    assert_eq!(score.notes[1].start, 0.5);
+    assert_eq!(score.notes[1].duration, 0.25);
+    assert_eq!(score.notes[1].syllable, MELISMA);
+}
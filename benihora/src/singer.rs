@@ -0,0 +1,258 @@
+//! Sings a `Score` through `BenihoraManaged`.
+
+use crate::{
+    phoneme::{self, Articulation, Manner, Phoneme, PhonemeTrack},
+    score::{Note, Score, MELISMA},
+    BenihoraManaged, IntervalTimer, F,
+};
+
+/// Notes closer than this are sung legato.
+const LEGATO_GAP: F = 0.01;
+
+/// Something a `Singer` can drive.
+pub trait Vocalist {
+    type Output;
+
+    fn set_frequency(&mut self, time: F, frequency: F);
+    fn set_articulation(&mut self, time: F, articulation: &Articulation);
+    fn process(&mut self, time: F) -> Self::Output;
+}
+
+impl Vocalist for BenihoraManaged {
+    type Output = F;
+
+    fn set_frequency(&mut self, _time: F, frequency: F) {
+        self.frequency.set(frequency);
+    }
+
+    fn set_articulation(&mut self, _time: F, articulation: &Articulation) {
+        articulation.apply(self);
+    }
+
+    fn process(&mut self, time: F) -> F {
+        BenihoraManaged::process(self, time)
+    }
+}
+
+pub struct Singer<V: Vocalist = BenihoraManaged> {
+    pub vocalist: V,
+    track: PhonemeTrack,
+    pitches: Vec<(F, F)>,
+    next_pitch: usize,
+    offset: F,
+    time: F,
+    control_timer: IntervalTimer,
+    dtime: F,
+}
+
+impl Singer {
+    pub fn new(score: &Score, sound_speed: F, sample_rate: F, seed: u32) -> Result<Self, String> {
+        Self::with_vocalist(
+            score,
+            BenihoraManaged::new(sound_speed, sample_rate, seed),
+            sample_rate,
+        )
+    }
+}
+
+impl<V: Vocalist> Singer<V> {
+    pub fn with_vocalist(score: &Score, vocalist: V, sample_rate: F) -> Result<Self, String> {
+        let Schedule {
+            track,
+            pitches,
+            offset,
+        } = schedule(score)?;
+        Ok(Self {
+            vocalist,
+            track,
+            pitches,
+            next_pitch: 0,
+            offset,
+            time: 0.0,
+            control_timer: IntervalTimer::new_overflowed(0.01),
+            dtime: 1.0 / sample_rate,
+        })
+    }
+
+    /// Seconds the output is delayed so that consonants can precede the first note.
+    pub fn offset(&self) -> F {
+        self.offset
+    }
+
+    /// Length of the performance in seconds, including the release.
+    pub fn duration(&self) -> F {
+        self.track.duration() + 0.5
+    }
+
+    pub fn process(&mut self) -> V::Output {
+        if self.control_timer.overflowed() {
+            while let Some(&(time, frequency)) = self.pitches.get(self.next_pitch) {
+                if self.time < time {
+                    break;
+                }
+                self.vocalist.set_frequency(self.time, frequency);
+                self.next_pitch += 1;
+            }
+            let articulation = self.track.articulation(self.time);
+            self.vocalist.set_articulation(self.time, &articulation);
+        }
+        self.control_timer.update(self.dtime);
+        let y = self.vocalist.process(self.time);
+        self.time += self.dtime;
+        y
+    }
+
+    pub fn render(&mut self) -> Vec<V::Output> {
+        let len = (self.duration() / self.dtime) as usize;
+        (0..len).map(|_| self.process()).collect()
+    }
+}
+
+/// Lays out phonemes so that onset consonants end at the note start,
+/// vowels sustain through the note and coda consonants end at the note end.
+fn schedule(score: &Score) -> Result<Schedule, String> {
+    let silence = phoneme::phoneme("_").unwrap();
+    let default_vowel = phoneme::phoneme("a").unwrap();
+
+    let mut events: Vec<(F, &'static Phoneme)> = Vec::new();
+    let mut pitches = Vec::new();
+    let mut prev: Option<&Note> = None;
+    for (i, note) in score.notes.iter().enumerate() {
+        let legato = prev.is_some_and(|prev| note.start - prev.end() < LEGATO_GAP);
+        let phonemes = if note.syllable == MELISMA {
+            if legato {
+                vec![]
+            } else {
+                vec![default_vowel]
+            }
+        } else {
+            phoneme::split(&note.syllable)
+                .map_err(|rest| format!("unknown phoneme `{}` in `{}`", rest, note.syllable))?
+        };
+        let is_vowel = |p: &&Phoneme| p.manner == Manner::Vowel;
+        let (onset, nucleus, coda) = match (
+            phonemes.iter().position(is_vowel),
+            phonemes.iter().rposition(is_vowel),
+        ) {
+            (Some(first), Some(last)) => (
+                &phonemes[..first],
+                &phonemes[first..=last],
+                &phonemes[last + 1..],
+            ),
+            // A syllabic consonant such as `n` is sustained itself.
+            _ => (&[][..], &phonemes[..], &[][..]),
+        };
+
+        // Onset consonants may take up to half of the previous note.
+        let limit = events
+            .last()
+            .map_or(F::NEG_INFINITY, |e| e.0)
+            .max(prev.map_or(F::NEG_INFINITY, |p| p.start + p.duration * 0.5));
+        let onset_len: F = onset.iter().map(|p| p.duration).sum();
+        let onset_start = (note.start - onset_len).max(limit);
+
+        match prev {
+            Some(prev) if !legato => {
+                if prev.end() < onset_start {
+                    events.push((prev.end(), silence));
+                }
+                // Change the pitch while silent to avoid an audible glide.
+                pitches.push((prev.end().min(onset_start), note.frequency()));
+            }
+            Some(_) => pitches.push((note.start, note.frequency())),
+            None => pitches.push((onset_start, note.frequency())),
+        }
+
+        let mut time = onset_start;
+        for p in onset {
+            events.push((time, p));
+            time += p.duration * (note.start - onset_start) / onset_len;
+        }
+
+        // The vowel carries on through following melismas.
+        let mut end = note.end();
+        for next in &score.notes[i + 1..] {
+            if next.syllable != MELISMA || next.start - end >= LEGATO_GAP {
+                break;
+            }
+            end = next.end();
+        }
+        let coda_len: F = coda.iter().map(|p| p.duration).sum();
+        let coda_start = (end - coda_len).max((note.start + end) * 0.5);
+        for (k, p) in nucleus.iter().enumerate() {
+            let t = k as F / nucleus.len() as F;
+            events.push((note.start + (coda_start - note.start) * t, p));
+        }
+        let mut time = coda_start;
+        for p in coda {
+            events.push((time, p));
+            time += p.duration * (end - coda_start) / coda_len;
+        }
+
+        prev = Some(note);
+    }
+    if let Some(prev) = prev {
+        events.push((prev.end(), silence));
+    }
+
+    let offset = events.first().map_or(0.0, |e| (-e.0).max(0.0));
+    let mut track = PhonemeTrack::new();
+    if let Some(&(start, _)) = events.first() {
+        if start + offset > 0.0 {
+            track.push(silence, start + offset);
+        }
+    }
+    for (i, (start, p)) in events.iter().enumerate() {
+        let duration = events.get(i + 1).map_or(p.duration, |next| next.0 - start);
+        track.push(p, duration);
+    }
+    for pitch in &mut pitches {
+        pitch.0 += offset;
+    }
+    Ok(Schedule {
+        track,
+        pitches,
+        offset,
+    })
+}
+
+struct Schedule {
+    track: PhonemeTrack,
+    /// `(time, frequency)`
+    pitches: Vec<(F, F)>,
+    /// Applied to both the track and the pitches.
+    offset: F,
+}
+
+#[test]
+fn test() {
+    let score = Score::parse("tempo 120\nka 60 1\nsan 62 1\n- 64 1\n_ 0 1\nmi 60 1").unwrap();
+    let Schedule {
+        track,
+        pitches,
+        offset,
+    } = schedule(&score).unwrap();
+
+    // `k` precedes the first note.
+    assert_eq!(offset, 0.06);
+    assert_eq!(track.articulation(0.0).voicing, 0.0);
+    assert_eq!(track.articulation(offset + 0.2).tongue, (12.9, 2.43));
+
+    // `s` ends at the second note and `n` closes the melisma.
+    let s = track.articulation(offset + 0.45);
+    assert!(s.voicing == 0.0 && s.closed_constrictions().next().is_some());
+    let open = track.articulation(offset + 1.2);
+    assert!(open.closed_constrictions().next().is_none());
+    assert!((track.articulation(offset + 1.45).velum - 0.4).abs() < 1e-9);
+
+    // Silent during the rest, then `m` of the last note.
+    assert_eq!(track.articulation(offset + 1.7).voicing, 0.0);
+    assert!((track.articulation(offset + 1.98).velum - 0.4).abs() < 1e-9);
+    assert_eq!(track.articulation(offset + 2.2).voicing, 1.0);
+
+    let times = [-0.06, 0.5, 1.0, 1.5];
+    assert_eq!(pitches.len(), times.len());
+    for (pitch, time) in pitches.iter().zip(times) {
+        assert!((pitch.0 - offset - time).abs() < 1e-9);
+    }
+}
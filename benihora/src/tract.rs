@@ -5,6 +5,10 @@ use crate::{noise::Noise, IntervalTimer};
 use super::{lerp, F};
 
 pub const DEFAULT_TONGUE: (f64, f64) = (12.9, 2.43);
+/// Segments of the oral tract of `Tract`.
+pub const MOUTH_LENGTH: usize = 44;
+/// Segments of the nasal tract of `Tract`.
+pub const NOSE_LENGTH: usize = 28;
 
 pub struct Tract {
     pub(crate) params: OtherParams,
@@ -25,8 +29,8 @@ pub struct Tract {
 
 impl Tract {
     pub fn new(steps_per_process: usize, sample_rate: F, seed: u32) -> Self {
-        let mouth_length = 44;
-        let nose_length = 28;
+        let mouth_length = MOUTH_LENGTH;
+        let nose_length = NOSE_LENGTH;
         let nose_start = mouth_length - nose_length + 1;
         let source = ShapeSource::new(mouth_length, nose_length);
         let mut diameter = Diameter::new(&source);
@@ -128,38 +132,47 @@ impl Tract {
             .compute_reflections(&mut self.new_reflections);
     }
 
+    /// Each constriction keeps one turbulence, moved in place as the constriction moves.
+    /// Doesn't allocate once `reserve_constrictions` covers the constrictions.
     pub fn update_diameter(&mut self) {
         self.source.compute_diameter(&mut self.target_diameter);
 
         self.state.turbulences.iter_mut().for_each(|t| t.on = false);
-        for constriction in &self.source.other_constrictions {
+        for (slot, constriction) in self.source.other_constrictions.iter().enumerate() {
             let diameter_range = 0.3..0.7;
             if !(1.0..(self.source.length - 1) as F).contains(&constriction.0)
                 || !diameter_range.contains(&constriction.1)
             {
                 continue;
             }
-            if let Some(t) = self
-                .state
-                .turbulences
-                .iter_mut()
-                .find(|t| t.index == constriction.0 && t.diameter == constriction.1)
-            {
-                t.on = true;
+            if let Some(t) = self.state.turbulences.iter_mut().find(|t| t.slot == slot) {
+                t.set(constriction.0, constriction.1);
             } else {
                 self.state
                     .turbulences
-                    .push(Turbulence::new(constriction.0, constriction.1));
+                    .push(Turbulence::new(slot, constriction.0, constriction.1));
             }
         }
     }
 
+    /// Makes room for `n` constrictions and their turbulences.
+    pub fn reserve_constrictions(&mut self, n: usize) {
+        self.source.other_constrictions.reserve(n);
+        self.state.turbulences.reserve(n);
+    }
+
     /// value: 0.01 - 0.4
     pub fn set_velum_target(&mut self, velum_target: F) {
         self.target_diameter.nose[0] = velum_target;
     }
+
+    /// Rate at which waves advance by one segment.
+    pub fn step_rate(&self) -> F {
+        self.sample_rate * self.steps_per_process as F
+    }
 }
 
+#[derive(Clone)]
 pub struct ShapeSource {
     pub length: usize,
     pub nose_length: usize,
@@ -336,25 +349,25 @@ impl Diameter {
     }
 
     pub fn compute_reflections(&mut self, reflections: &mut Reflections) {
-        let area: Vec<_> = self.mouth.iter().map(|d| d * d).collect();
+        let area = |i: usize| self.mouth[i] * self.mouth[i];
         for i in 0..self.mouth.len() - 1 {
-            reflections.mouth[i] = if area[i + 1] == 0.0 {
+            reflections.mouth[i] = if area(i + 1) == 0.0 {
                 0.999
             } else {
-                (area[i] - area[i + 1]) / (area[i] + area[i + 1])
+                (area(i) - area(i + 1)) / (area(i) + area(i + 1))
             };
         }
 
-        let nose_area: Vec<_> = self.nose.iter().map(|d| d * d).collect();
+        let nose_area = |i: usize| self.nose[i] * self.nose[i];
         for i in 0..self.nose.len() - 1 {
             reflections.nose[i] =
-                (nose_area[i] - nose_area[i + 1]) / (nose_area[i] + nose_area[i + 1]);
+                (nose_area(i) - nose_area(i + 1)) / (nose_area(i) + nose_area(i + 1));
         }
 
-        let sum = area[self.nose_start] + area[self.nose_start + 1] + nose_area[0];
-        reflections.junction_left = 2.0 * area[self.nose_start] / sum - 1.0;
-        reflections.junction_right = 2.0 * area[self.nose_start + 1] / sum - 1.0;
-        reflections.junction_nose = 2.0 * nose_area[0] / sum - 1.0;
+        let sum = area(self.nose_start) + area(self.nose_start + 1) + nose_area(0);
+        reflections.junction_left = 2.0 * area(self.nose_start) / sum - 1.0;
+        reflections.junction_right = 2.0 * area(self.nose_start + 1) / sum - 1.0;
+        reflections.junction_nose = 2.0 * nose_area(0) / sum - 1.0;
     }
 }
 
@@ -571,25 +584,34 @@ impl Transient {
 
 #[derive(Clone)]
 struct Turbulence {
+    /// The constriction it comes from, in `ShapeSource::other_constrictions`.
+    slot: usize,
     index: F,
-    diameter: F,
     strength: F,
     intensity: F,
     on: bool,
 }
 
 impl Turbulence {
-    fn new(index: F, diameter: F) -> Self {
-        let thinness = (8.0 * (0.7 - diameter)).clamp(0.0, 1.0);
-        let openness = (30.0 * (diameter - 0.3)).clamp(0.0, 1.0);
-        let strength = 0.66 * thinness * openness;
-        Self {
+    fn new(slot: usize, index: F, diameter: F) -> Self {
+        let mut turbulence = Self {
+            slot,
             index,
-            diameter,
-            strength,
+            strength: 0.0,
             intensity: 0.0,
             on: true,
-        }
+        };
+        turbulence.set(index, diameter);
+        turbulence
+    }
+
+    /// Moves it, keeping the intensity.
+    fn set(&mut self, index: F, diameter: F) {
+        let thinness = (8.0 * (0.7 - diameter)).clamp(0.0, 1.0);
+        let openness = (30.0 * (diameter - 0.3)).clamp(0.0, 1.0);
+        self.index = index;
+        self.strength = 0.66 * thinness * openness;
+        self.on = true;
     }
 
     fn update_intensity(&mut self, dtime: f64) {
@@ -1,4 +1,6 @@
+pub mod response;
 pub mod state;
 pub mod types;
 
+pub use response::Response;
 pub use state::State;
@@ -0,0 +1,218 @@
+use std::f64::consts::TAU;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl Complex {
+    pub fn new(re: f64, im: f64) -> Self {
+        Self { re, im }
+    }
+
+    pub fn from_polar(norm: f64, arg: f64) -> Self {
+        let (sin, cos) = arg.sin_cos();
+        Self::new(norm * cos, norm * sin)
+    }
+
+    pub fn norm(&self) -> f64 {
+        self.re.hypot(self.im)
+    }
+
+    pub fn arg(&self) -> f64 {
+        self.im.atan2(self.re)
+    }
+
+    pub fn sqrt(&self) -> Self {
+        Self::from_polar(self.norm().sqrt(), self.arg() / 2.0)
+    }
+}
+
+impl std::ops::Add for Complex {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.re + rhs.re, self.im + rhs.im)
+    }
+}
+
+impl std::ops::Sub for Complex {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.re - rhs.re, self.im - rhs.im)
+    }
+}
+
+impl std::ops::Mul for Complex {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self::new(
+            self.re * rhs.re - self.im * rhs.im,
+            self.re * rhs.im + self.im * rhs.re,
+        )
+    }
+}
+
+impl std::ops::Div for Complex {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        let d = rhs.re * rhs.re + rhs.im * rhs.im;
+        Self::new(
+            (self.re * rhs.re + self.im * rhs.im) / d,
+            (self.im * rhs.re - self.re * rhs.im) / d,
+        )
+    }
+}
+
+/// Response of biquad coefficients as returned by `FilterType::compute_params`.
+/// `period` is frequency / sample rate, as in `compute_params`.
+pub trait Response {
+    fn frequency_response(&self, period: f64) -> Complex;
+
+    /// In samples.
+    fn group_delay(&self, period: f64) -> f64;
+
+    /// Poles in the z-plane.
+    fn poles(&self) -> Vec<Complex>;
+
+    /// Zeros in the z-plane.
+    fn zeros(&self) -> Vec<Complex>;
+
+    #[inline]
+    fn magnitude(&self, period: f64) -> f64 {
+        self.frequency_response(period).norm()
+    }
+
+    #[inline]
+    fn magnitude_db(&self, period: f64) -> f64 {
+        20.0 * self.magnitude(period).log10()
+    }
+
+    /// In radians, wrapped to -π ~ π.
+    #[inline]
+    fn phase(&self, period: f64) -> f64 {
+        self.frequency_response(period).arg()
+    }
+
+    fn is_stable(&self) -> bool {
+        self.poles().iter().all(|p| p.norm() < 1.0)
+    }
+}
+
+impl Response for [f64; 6] {
+    fn frequency_response(&self, period: f64) -> Complex {
+        let [a0, a1, a2, b0, b1, b2] = *self;
+        polynomial(&[b0, b1, b2], period) / polynomial(&[a0, a1, a2], period)
+    }
+
+    fn group_delay(&self, period: f64) -> f64 {
+        let [a0, a1, a2, b0, b1, b2] = *self;
+        polynomial_delay(&[b0, b1, b2], period) - polynomial_delay(&[a0, a1, a2], period)
+    }
+
+    fn poles(&self) -> Vec<Complex> {
+        let [a0, a1, a2, ..] = *self;
+        quadratic_roots(a0, a1, a2)
+    }
+
+    fn zeros(&self) -> Vec<Complex> {
+        let [.., b0, b1, b2] = *self;
+        quadratic_roots(b0, b1, b2)
+    }
+}
+
+/// A cascade of biquads.
+impl Response for [[f64; 6]] {
+    fn frequency_response(&self, period: f64) -> Complex {
+        self.iter().fold(Complex::new(1.0, 0.0), |h, params| {
+            h * params.frequency_response(period)
+        })
+    }
+
+    fn group_delay(&self, period: f64) -> f64 {
+        self.iter().map(|params| params.group_delay(period)).sum()
+    }
+
+    fn poles(&self) -> Vec<Complex> {
+        self.iter().flat_map(|params| params.poles()).collect()
+    }
+
+    fn zeros(&self) -> Vec<Complex> {
+        self.iter().flat_map(|params| params.zeros()).collect()
+    }
+}
+
+/// Σ c[k] e^{-iωk}
+fn polynomial(c: &[f64], period: f64) -> Complex {
+    c.iter()
+        .enumerate()
+        .fold(Complex::default(), |acc, (k, c)| {
+            acc + Complex::from_polar(*c, -TAU * period * k as f64)
+        })
+}
+
+/// Group delay of the polynomial: Re(Σ k c[k] e^{-iωk} / Σ c[k] e^{-iωk})
+fn polynomial_delay(c: &[f64], period: f64) -> f64 {
+    let numerator = c
+        .iter()
+        .enumerate()
+        .fold(Complex::default(), |acc, (k, c)| {
+            acc + Complex::from_polar(*c * k as f64, -TAU * period * k as f64)
+        });
+    (numerator / polynomial(c, period)).re
+}
+
+/// Roots of c0 z² + c1 z + c2.
+fn quadratic_roots(c0: f64, c1: f64, c2: f64) -> Vec<Complex> {
+    if c0 == 0.0 {
+        if c1 == 0.0 {
+            return vec![];
+        }
+        return vec![Complex::new(-c2 / c1, 0.0)];
+    }
+    let d = Complex::new(c1 * c1 - 4.0 * c0 * c2, 0.0).sqrt();
+    let b = Complex::new(-c1, 0.0);
+    let a2 = Complex::new(2.0 * c0, 0.0);
+    vec![(b + d) / a2, (b - d) / a2]
+}
+
+#[test]
+fn test() {
+    use crate::types::*;
+
+    let lp = LowPass.compute_params(0.1, 1.0, std::f64::consts::FRAC_1_SQRT_2);
+    assert!((lp.magnitude(0.0) - 1.0).abs() < 1e-9);
+    assert!((lp.magnitude_db(0.1) + 3.0103).abs() < 1e-3);
+    assert!(lp.magnitude(0.5) < 1e-9);
+    assert!((lp.phase(0.1) + std::f64::consts::FRAC_PI_2).abs() < 1e-9);
+    assert!(lp.is_stable());
+
+    let ap = AllPass.compute_params(0.05, 1.0, 2.0);
+    for i in 1..50 {
+        let period = i as f64 / 100.0;
+        assert!((ap.magnitude(period) - 1.0).abs() < 1e-9);
+        assert!(ap.group_delay(period) > 0.0);
+    }
+
+    // Zeros of a notch lie on the unit circle at the centre frequency.
+    let notch = Notch.compute_params(0.2, 1.0, 1.0);
+    assert!(notch.magnitude(0.2) < 1e-9);
+    for z in notch.zeros() {
+        assert!((z.norm() - 1.0).abs() < 1e-9);
+        assert!((z.arg().abs() - TAU * 0.2).abs() < 1e-9);
+    }
+
+    // `gain` is the RBJ `A`, so the peak is `gain²`.
+    let peaking = Peaking.compute_params(0.1, 2.0, 1.0);
+    assert!((peaking.magnitude(0.1) - 4.0).abs() < 1e-9);
+    assert!((peaking.magnitude(0.0) - 1.0).abs() < 1e-9);
+
+    let cascade = [lp, lp];
+    assert!((cascade.magnitude_db(0.1) + 6.0206).abs() < 1e-3);
+    assert!((cascade.group_delay(0.05) - 2.0 * lp.group_delay(0.05)).abs() < 1e-9);
+    assert_eq!(cascade.poles().len(), 4);
+}
@@ -4,6 +4,7 @@ use corus::{
     contrib::buffer_playback::BufferPlayback,
     core::{var::Var, pan::Pan, sine::Sine},
 };
+use resampler::Quality;
 
 const SAMPLE_RATE: usize = 44100;
 
@@ -13,9 +14,9 @@ fn main() {
         .next()
         .unwrap_or("beats.wav".to_string());
     println!("load {:?} ...", &file);
-    let buf = write_to_file::read_wav_file(&file);
+    let (buf, sample_rate) = write_to_file::read_wav_file(&file);
 
-    let node = BufferPlayback::new(buf);
+    let node = BufferPlayback::with_sample_rate(buf, sample_rate, Quality::Medium);
     let node = Pan::new(node, Sine::new(Var::from(0.25)));
 
     let file = format!("{}-autopan.wav", file[..file.len() - 4].to_string());
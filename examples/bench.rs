@@ -1,55 +1,114 @@
-mod write_to_file;
+use std::time::Instant;
 
 use corus::{
-    core::{
-        add::Add, amp::Amp, var::Var, controllable::Controllable, mix::Mix, param::Param,
-        placeholder::Placeholder, share::Share,
-        ring_buffer_playback::RingBufferPlayback, ring_buffer_record::RingBufferRecord, sine::Sine,
-        Node,
-    },
+    contrib::render_graph::{FnJob, NodeJob, RenderGraph},
+    core::{add::Add, amp::Amp, mix::Mix, param::Param, sine::Sine, var::Var, Node},
     signal::C1f64,
+    time::Second,
+    ProcContext,
 };
 
-const SAMPLE_RATE: usize = 44100;
+const SAMPLE_RATE: u64 = 44100;
+const SECONDS: f64 = 4.0;
+const TRACKS: usize = 8;
+const VOICES_PER_TRACK: usize = 32;
+const BLOCK_SIZE: usize = 256;
 
-fn main() {
-    let mut nodes = Vec::new();
-    let modulator = Share::new(Amp::new(
-        Sine::new(Var::from(4.0)),
-        Var::from(20.0),
-    ));
-    for i in 0..100 {
-        let freq = Controllable::new(Param::new());
-        let mut freq_ctrl = freq.controller();
-        let f = 440.0 * (i + 1) as f64;
-        freq_ctrl.lock().set_value_at_time(0.0, f);
-        freq_ctrl.lock().linear_ramp_to_value_at_time(1.0, f * 2.0);
-        freq_ctrl.lock().exponential_ramp_to_value_at_time(2.0, f);
-        nodes.push(Box::new(Amp::new(
-            Sine::new(Add::new(freq, modulator.clone())),
-            Var::from(1.0 / (i + 1) as f64),
-        )) as Box<dyn Node<Output = C1f64>>);
+type Voice = Box<dyn Node<Output = C1f64> + Send>;
+
+fn voice(i: usize) -> Voice {
+    let mut freq = Param::new();
+    let f = 110.0 * (i % 24 + 1) as f64;
+    freq.set_value_at_time(0.0, f);
+    freq.linear_ramp_to_value_at_time(1.0, f * 2.0);
+    freq.exponential_ramp_to_value_at_time(2.0, f);
+    let vibrato = Amp::new(Sine::new(Var::from(4.0 + i as f64 * 0.01)), Var::from(5.0));
+    Box::new(Amp::new(
+        Sine::new(Add::new(freq, vibrato)),
+        Var::from(1.0 / (i + 1) as f64),
+    ))
+}
+
+fn bus(x: C1f64) -> C1f64 {
+    (x * 0.5).tanh()
+}
+
+/// Voices mixed into tracks with a saturating bus each, all in this thread.
+fn single() -> impl Node<Output = C1f64> {
+    let tracks = (0..TRACKS)
+        .map(|t| {
+            let voices = (0..VOICES_PER_TRACK)
+                .map(|v| voice(t * VOICES_PER_TRACK + v))
+                .collect();
+            Box::new(Bus(Mix::new(voices))) as Box<dyn Node<Output = C1f64>>
+        })
+        .collect();
+    Mix::new(tracks)
+}
+
+/// The same graph across `thread_num` threads besides this one.
+fn parallel(thread_num: usize) -> impl Node<Output = C1f64> {
+    let mut graph = RenderGraph::new(BLOCK_SIZE, thread_num);
+    for t in 0..TRACKS {
+        let voices: Vec<_> = (0..VOICES_PER_TRACK)
+            .map(|v| graph.add_job(NodeJob::new(voice(t * VOICES_PER_TRACK + v)), &[]))
+            .collect();
+        graph.add_job(FnJob(bus), &voices);
+    }
+    graph
+}
+
+struct Bus<A>(A);
+
+impl<A: Node<Output = C1f64>> Node for Bus<A> {
+    type Output = C1f64;
+
+    fn proc(&mut self, ctx: &ProcContext) -> Self::Output {
+        bus(self.0.proc(ctx))
+    }
+
+    fn lock(&mut self, ctx: &ProcContext) {
+        self.0.lock(ctx);
     }
 
-    let mix = Mix::new(nodes);
-
-    let mix = {
-        let mut p = Placeholder::new(None);
-        let mut ps = p.setter();
-        let buffer = Share::new(RingBufferRecord::new(p, SAMPLE_RATE));
-        unsafe {
-            ps.set(Box::new(Add::new(
-                mix,
-                Amp::new(
-                    RingBufferPlayback::new(Var::from(0.5), buffer.clone()),
-                    Var::from(0.5),
-                ),
-            )) as Box<dyn Node<Output = C1f64>>);
-        }
-        buffer
-    };
-
-    let node = Amp::new(mix, Var::from(0.1));
-
-    write_to_file::write_to_file("bench.wav", SAMPLE_RATE, 4.0, node, None, None);
+    fn unlock(&mut self) {
+        self.0.unlock();
+    }
+}
+
+fn run(name: &str, mut node: impl Node<Output = C1f64>) -> Vec<C1f64> {
+    let start = Instant::now();
+    let samples: Vec<_> = ProcContext::new(SAMPLE_RATE)
+        .lock(&mut node, Second(SECONDS))
+        .collect();
+    let elapsed = start.elapsed().as_secs_f64();
+    println!(
+        "{:<12} {:>8.1} ms  {:>6.1}x realtime",
+        name,
+        elapsed * 1000.0,
+        SECONDS / elapsed
+    );
+    samples
+}
+
+fn main() {
+    println!(
+        "{} tracks x {} voices, {} s at {} Hz",
+        TRACKS, VOICES_PER_TRACK, SECONDS, SAMPLE_RATE
+    );
+    let reference = run("single", single());
+    // Up to the number of cores unless given.
+    let threads = std::env::args()
+        .nth(1)
+        .and_then(|n| n.parse().ok())
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(4, |n| n.get()));
+    for threads in (1..=threads).filter(|n| n.is_power_of_two()) {
+        let samples = run(&format!("{} threads", threads), parallel(threads - 1));
+        let error = reference
+            .iter()
+            .zip(samples.iter())
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0, f64::max);
+        assert!(error < 1e-9, "output differs by {}", error);
+    }
 }
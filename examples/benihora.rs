@@ -1,5 +1,6 @@
 mod write_to_file;
 
+use benihora::phoneme::PhonemeTrack;
 use corus::{
     contrib::{
         amp_pan,
@@ -16,148 +17,38 @@ fn main() {
     let benihora = EventScheduleNode::new(EventControllable::new(benihora));
     let mut benihora_ctl = benihora.get_scheduler();
 
-    benihora_ctl.push_event(0.0, BenihoraEvent::SetStatus(true));
-
-    benihora_ctl.push_event(0.0, BenihoraEvent::MoveTongue(12.9, 2.43));
-    benihora_ctl.push_event(1.0, BenihoraEvent::MoveTongue(19.4, 3.43));
-    benihora_ctl.push_event(2.0, BenihoraEvent::MoveTongue(22.8, 2.05));
-    benihora_ctl.push_event(3.0, BenihoraEvent::MoveTongue(27.2, 2.2));
-    benihora_ctl.push_event(4.0, BenihoraEvent::MoveTongue(12.9, 2.43));
-    // benihora_ctl.push_event(5.0, BenihoraEvent::SetOtherConstrictions(vec![(36.0, 0.6)]));
-    benihora_ctl.push_event(4.5, BenihoraEvent::SetOtherConstrictions(vec![(36.0, 0.3)]));
-    benihora_ctl.push_event(4.5, BenihoraEvent::SetVelum(0.4));
-    benihora_ctl.push_event(5.0, BenihoraEvent::SetVelum(0.01));
-    benihora_ctl.push_event(5.1, BenihoraEvent::SetOtherConstrictions(vec![]));
-    benihora_ctl.push_event(5.2, BenihoraEvent::SetOtherConstrictions(vec![(36.0, 0.3)]));
-    benihora_ctl.push_event(5.3, BenihoraEvent::SetOtherConstrictions(vec![]));
-    benihora_ctl.push_event(5.4, BenihoraEvent::SetOtherConstrictions(vec![(36.0, 0.3)]));
-    benihora_ctl.push_event(5.5, BenihoraEvent::SetOtherConstrictions(vec![]));
-    benihora_ctl.push_event(5.6, BenihoraEvent::SetOtherConstrictions(vec![(36.0, 0.3)]));
-    benihora_ctl.push_event(5.7, BenihoraEvent::SetOtherConstrictions(vec![]));
-
-    // benihora_ctl.push_event(5.0, BenihoraEvent::MoveTongue(36.0, 0.3));
-    // benihora_ctl.push_event(5.1, BenihoraEvent::MoveTongue(12.9, 2.43));
-    // benihora_ctl.push_event(5.2, BenihoraEvent::MoveTongue(36.0, 0.3));
-    // benihora_ctl.push_event(5.3, BenihoraEvent::MoveTongue(12.9, 2.43));
-    // benihora_ctl.push_event(5.4, BenihoraEvent::MoveTongue(36.0, 0.3));
-    // benihora_ctl.push_event(5.5, BenihoraEvent::MoveTongue(12.9, 2.43));
-    // benihora_ctl.push_event(5.6, BenihoraEvent::MoveTongue(36.0, 0.3));
-    // benihora_ctl.push_event(5.7, BenihoraEvent::MoveTongue(12.9, 2.43));
-
-    // benihora_ctl.push_event(0.0, BenihoraEvent::SetFrequency(440.0));
-    benihora_ctl.push_event(
-        6.0,
-        BenihoraEvent::SetFrequency(notenum_to_frequency(48) as f64),
-    );
-    benihora_ctl.push_event(
-        6.5,
-        BenihoraEvent::SetFrequency(notenum_to_frequency(50) as f64),
-    );
-    benihora_ctl.push_event(
-        7.0,
-        BenihoraEvent::SetFrequency(notenum_to_frequency(52) as f64),
-    );
-    benihora_ctl.push_event(
-        7.5,
-        BenihoraEvent::SetFrequency(notenum_to_frequency(53) as f64),
-    );
-    benihora_ctl.push_event(
-        8.0,
-        BenihoraEvent::SetFrequency(notenum_to_frequency(55) as f64),
-    );
-    benihora_ctl.push_event(
-        8.5,
-        BenihoraEvent::SetFrequency(notenum_to_frequency(57) as f64),
-    );
-    benihora_ctl.push_event(
-        9.0,
-        BenihoraEvent::SetFrequency(notenum_to_frequency(59) as f64),
-    );
-    benihora_ctl.push_event(
-        9.5,
-        BenihoraEvent::SetFrequency(notenum_to_frequency(60) as f64),
-    );
-    benihora_ctl.push_event(
-        10.0,
-        BenihoraEvent::SetFrequency(notenum_to_frequency(48) as f64),
-    );
-
-    benihora_ctl.push_event(6.0, BenihoraEvent::MoveTongue(14.0, 2.09));
-    benihora_ctl.push_event(
-        5.95,
-        BenihoraEvent::SetOtherConstrictions(vec![(20.0, 0.2), (25.0, 0.2)]),
-    );
-    benihora_ctl.push_event(6.0, BenihoraEvent::SetOtherConstrictions(vec![]));
-
-    benihora_ctl.push_event(6.5, BenihoraEvent::MoveTongue(19.4, 3.43));
-    benihora_ctl.push_event(
-        6.45,
-        BenihoraEvent::SetOtherConstrictions(vec![(30.0, 0.2)]),
-    );
-    benihora_ctl.push_event(6.5, BenihoraEvent::SetOtherConstrictions(vec![]));
-
-    benihora_ctl.push_event(7.0, BenihoraEvent::MoveTongue(22.8, 2.05));
-    benihora_ctl.push_event(
-        6.95,
-        BenihoraEvent::SetOtherConstrictions(vec![(41.0, 0.7)]),
-    );
-    benihora_ctl.push_event(7.0, BenihoraEvent::SetOtherConstrictions(vec![]));
-    benihora_ctl.push_event(6.95, BenihoraEvent::SetVelum(0.4));
-    benihora_ctl.push_event(7.0, BenihoraEvent::SetVelum(0.01));
-
-    benihora_ctl.push_event(7.5, BenihoraEvent::MoveTongue(12.9, 2.43));
-    benihora_ctl.push_event(
-        7.45,
-        BenihoraEvent::SetOtherConstrictions(vec![(41.5, 0.6)]),
-    );
-    benihora_ctl.push_event(7.5, BenihoraEvent::SetOtherConstrictions(vec![]));
-
-    benihora_ctl.push_event(8.0, BenihoraEvent::MoveTongue(14.0, 2.09));
-    benihora_ctl.push_event(
-        7.98,
-        BenihoraEvent::SetOtherConstrictions(vec![(41.0, 0.6)]),
-    );
-    benihora_ctl.push_event(8.0, BenihoraEvent::SetOtherConstrictions(vec![]));
-
-    benihora_ctl.push_event(8.5, BenihoraEvent::MoveTongue(12.9, 2.43));
-    benihora_ctl.push_event(
-        8.45,
-        BenihoraEvent::SetOtherConstrictions(vec![(30.0, 0.2)]),
-    );
-    benihora_ctl.push_event(8.5, BenihoraEvent::SetOtherConstrictions(vec![]));
-
-    benihora_ctl.push_event(9.0, BenihoraEvent::MoveTongue(22.8, 2.05));
-    benihora_ctl.push_event(
-        8.98,
-        BenihoraEvent::SetOtherConstrictions(vec![(41.0, 0.6)]),
-    );
-    benihora_ctl.push_event(9.0, BenihoraEvent::SetOtherConstrictions(vec![]));
-
-    benihora_ctl.push_event(9.5, BenihoraEvent::MoveTongue(14.0, 2.09));
-    benihora_ctl.push_event(
-        9.45,
-        BenihoraEvent::SetOtherConstrictions(vec![(20.0, 0.2), (25.0, 0.2)]),
-    );
-    benihora_ctl.push_event(9.5, BenihoraEvent::SetOtherConstrictions(vec![]));
-
-    benihora_ctl.push_event(10.0, BenihoraEvent::SetTenseness(0.0));
-    benihora_ctl.push_event(10.5, BenihoraEvent::SetTenseness(0.25));
-    benihora_ctl.push_event(11.0, BenihoraEvent::SetTenseness(0.5));
-    benihora_ctl.push_event(11.5, BenihoraEvent::SetTenseness(0.75));
-    benihora_ctl.push_event(12.0, BenihoraEvent::SetTenseness(1.0));
-    benihora_ctl.push_event(12.5, BenihoraEvent::SetTenseness(0.6));
-
-    benihora_ctl.push_event(13.0, BenihoraEvent::SetStatus(false));
-    benihora_ctl.push_event(14.0, BenihoraEvent::SetStatus(true));
-
-    benihora_ctl.push_event(
-        15.0,
-        BenihoraEvent::SetOtherConstrictions(vec![(12.0, 0.6)]),
-    );
-    benihora_ctl.push_event(16.0, BenihoraEvent::SetOtherConstrictions(vec![]));
-    benihora_ctl.push_event(16.0, BenihoraEvent::SetVibrato(0.01, 4.0));
-    benihora_ctl.push_event(17.0, BenihoraEvent::SetVibrato(0.03, 4.0));
+    let track = PhonemeTrack::parse(
+        "_ a i u e o _ m a m i m u n e n o _ s a sh i s u z e z o _ k a t a p a g a d a b a _ h a f u v u",
+        0.7,
+    )
+    .unwrap();
+
+    let notes = [48, 50, 52, 53, 55, 57, 59, 60];
+    let note_length = track.duration() / notes.len() as f64;
+    for (i, notenum) in notes.iter().enumerate() {
+        benihora_ctl.push_event(
+            i as f64 * note_length,
+            BenihoraEvent::SetFrequency(notenum_to_frequency(*notenum) as f64),
+        );
+    }
+    benihora_ctl.push_event(0.0, BenihoraEvent::SetVibrato(0.01, 4.0));
+
+    let step = 0.01;
+    for i in 0..=((track.duration() + 0.5) / step) as usize {
+        let time = i as f64 * step;
+        benihora_ctl.push_event(
+            time,
+            BenihoraEvent::SetArticulation(track.articulation(time)),
+        );
+    }
 
     let node = amp_pan(benihora, Var::from(1.0), Var::from(0.0));
-    write_to_file::write_to_file("benihora.wav", SAMPLE_RATE, 20.0, node, None, None);
+    write_to_file::write_to_file(
+        "benihora.wav",
+        SAMPLE_RATE,
+        track.duration() + 1.0,
+        node,
+        None,
+        None,
+    );
 }
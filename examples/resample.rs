@@ -1,18 +1,21 @@
 mod write_to_file;
 
 use corus::{
-    contrib::resample::{Resample, ResampleType},
+    contrib::resample::{Resample, ResampleType, SincResample},
     core::{accumulator::Accumulator, map::Map, param::Param},
     signal::C1f64,
 };
+use resampler::Quality;
 
 fn main() {
-    let mut freq = Param::with_value(440.0);
-    freq.exponential_ramp_to_value_at_time(2.0, 880.0);
-    let node = Map::new(Accumulator::new(freq, C1f64::from(1.0)), |v: f64| {
-        (v * 2.0 * std::f64::consts::PI).sin()
-    });
-    let node = Resample::new(node, 0.0, 4000, ResampleType::NearestNeighbor);
+    let source = || {
+        let mut freq = Param::with_value(440.0);
+        freq.exponential_ramp_to_value_at_time(2.0, 880.0);
+        Map::new(Accumulator::new(freq, C1f64::from(1.0)), |v: f64| {
+            (v * 2.0 * std::f64::consts::PI).sin()
+        })
+    };
+    let node = Resample::new(source(), 0.0, 4000, ResampleType::NearestNeighbor);
     write_to_file::write_to_file(
         "resample.wav",
         44100,
@@ -21,4 +24,7 @@ fn main() {
         Some(0xcd4cfed689495a2f),
         Some(0xa65115ea898355ad),
     );
+
+    let node = SincResample::new(source(), 4000, Quality::Medium);
+    write_to_file::write_to_file("resample-sinc.wav", 44100, 3.0, node, None, None);
 }
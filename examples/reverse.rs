@@ -10,7 +10,7 @@ fn main() {
         .next()
         .unwrap_or("beats.wav".to_string());
     println!("load {:?} ...", &file);
-    let buf = write_to_file::read_wav_file(&file);
+    let (buf, _) = write_to_file::read_wav_file(&file);
 
     let acc = Accumulator::new(Var::new(-1.0), 100.0);
     let node = Map::new(acc, move |f| {
@@ -5,6 +5,7 @@ use corus::{
     core::{mul::Mul, pan::Pan, sine::Sine, var::Var},
     time::Second,
 };
+use resampler::Quality;
 
 const SAMPLE_RATE: usize = 44100;
 
@@ -14,10 +15,10 @@ fn main() {
         .next()
         .unwrap_or("beats.wav".to_string());
     println!("load {:?} ...", &file);
-    let buf = write_to_file::read_wav_file(&file);
-    let buf_len = buf.len() as f64 / SAMPLE_RATE as f64;
+    let (buf, sample_rate) = write_to_file::read_wav_file(&file);
+    let buf_len = buf.len() as f64 / sample_rate as f64;
 
-    let buf = BufferPlayback::new(buf);
+    let buf = BufferPlayback::with_sample_rate(buf, sample_rate, Quality::Medium);
     let node = Sine::new(Var::new(440.0));
     let node = Pan::new(node, Var::new(0.0));
     let node = Mul::new(node, Rms::new(buf, Second(0.3)));
@@ -85,9 +85,11 @@ pub fn write_to_file_with_event_queue<N>(
 }
 
 #[allow(dead_code)]
-pub fn read_wav_file(file: &str) -> Vec<C2f64> {
+/// Returns the samples and their sample rate.
+pub fn read_wav_file(file: &str) -> (Vec<C2f64>, u64) {
     let mut wav = hound::WavReader::open(&file).unwrap();
     println!("spec: {:?}", wav.spec());
+    let sample_rate = wav.spec().sample_rate as u64;
     let mut samples = wav.samples::<i16>();
     let mut buf = Vec::new();
     while let Some(l) = samples.next() {
@@ -97,7 +99,7 @@ pub fn read_wav_file(file: &str) -> Vec<C2f64> {
             r.unwrap() as f64 / std::i16::MAX as f64,
         ]));
     }
-    buf
+    (buf, sample_rate)
 }
 
 /// Usage: cargo run --release --example foo | pacat
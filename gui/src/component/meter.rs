@@ -0,0 +1,147 @@
+use std::{any::Any, cell::RefCell, collections::VecDeque, marker::PhantomData, rc::Rc};
+
+use corus::{
+    contrib::rms::Rms,
+    time::{Sample, Second},
+    Node, ProcContext,
+};
+
+use crate::{context::Context, interface::*, tap::TapReader};
+
+use super::Component;
+
+/// Bottom of the scale, in dB.
+const MIN_DB: f64 = -60.0;
+/// Fall of the peak hold, in dB per second.
+const PEAK_FALL: f64 = 20.0;
+
+/// A vertical peak and RMS meter. The RMS is taken over 300 ms.
+pub struct Meter<U: Ui> {
+    pub rect: Rect,
+    reader: TapReader,
+    sample_rate: f64,
+    samples: Vec<f32>,
+    input: Rc<RefCell<VecDeque<f64>>>,
+    rms: Rms<Input, Second>,
+    ctx: ProcContext,
+    peak: f64,
+    rms_level: f64,
+    _t: PhantomData<dyn Fn() -> U>,
+}
+
+impl<U: Ui> Meter<U> {
+    pub fn new(rect: Rect, reader: TapReader, sample_rate: f64) -> Self {
+        let input = Rc::new(RefCell::new(VecDeque::new()));
+        Self {
+            rect,
+            reader,
+            sample_rate,
+            samples: vec![],
+            rms: Rms::new(Input(input.clone()), Second(0.3)),
+            input,
+            ctx: ProcContext::new(sample_rate as u64),
+            peak: 0.0,
+            rms_level: 0.0,
+            _t: Default::default(),
+        }
+    }
+
+    /// Amplitude, falling slowly after peaks.
+    pub fn peak(&self) -> f64 {
+        self.peak
+    }
+
+    pub fn rms(&self) -> f64 {
+        self.rms_level
+    }
+}
+
+impl<U: Ui> Component<U> for Meter<U> {
+    fn update(&mut self) {
+        self.samples.clear();
+        self.reader.read(&mut self.samples);
+        if self.samples.is_empty() {
+            return;
+        }
+
+        let fall = 10.0f64.powf(-PEAK_FALL / 20.0 * self.samples.len() as f64 / self.sample_rate);
+        let peak = self
+            .samples
+            .iter()
+            .fold(0.0f64, |a, x| a.max(x.abs() as f64));
+        self.peak = peak.max(self.peak * fall);
+
+        self.input
+            .borrow_mut()
+            .extend(self.samples.iter().map(|x| *x as f64));
+        if let Some(rms) = self
+            .ctx
+            .lock(&mut self.rms, Sample(self.samples.len() as u64))
+            .last()
+        {
+            self.rms_level = rms;
+        }
+    }
+
+    fn message_receive(&mut self, _ctx: &mut Context<U>, _message: &Box<dyn Any>) -> bool {
+        false
+    }
+
+    fn draw(&mut self, ctx: &mut Context<U>) {
+        let Rect(x, y, w, h) = self.rect;
+        let to_y = |amp: f64| {
+            let db = 20.0 * amp.max(1e-10).log10();
+            y + ((db / MIN_DB).clamp(0.0, 1.0) * (h - 1) as f64) as i32
+        };
+        let right = x + w as i32 - 1;
+
+        ctx.canvas.set_draw_color(RGB(0, 160, 0));
+        for row in to_y(self.rms_level)..y + h as i32 {
+            ctx.canvas
+                .draw_line(Point::new(x, row), Point::new(right, row))
+                .unwrap();
+        }
+        ctx.canvas.set_draw_color(RGB(224, 0, 0));
+        let peak = to_y(self.peak);
+        ctx.canvas
+            .draw_line(Point::new(x, peak), Point::new(right, peak))
+            .unwrap();
+        ctx.canvas.set_draw_color(RGB(0, 0, 0));
+        ctx.canvas.draw_rect(self.rect);
+    }
+}
+
+/// Feeds the tapped samples to `Rms`.
+struct Input(Rc<RefCell<VecDeque<f64>>>);
+
+impl Node for Input {
+    type Output = f64;
+
+    fn proc(&mut self, _ctx: &ProcContext) -> f64 {
+        self.0.borrow_mut().pop_front().unwrap_or(0.0)
+    }
+
+    fn lock(&mut self, _ctx: &ProcContext) {}
+
+    fn unlock(&mut self) {}
+}
+
+#[test]
+fn test() {
+    let (mut tap, reader) = crate::tap::tap(48000);
+    let mut meter = Meter::<crate::headless::Ui>::new(Rect::new(0, 0, 10, 100), reader, 48000.0);
+
+    let sine: Vec<f32> = (0..24000)
+        .map(|i| (std::f64::consts::TAU * 1000.0 * i as f64 / 48000.0).sin() as f32 * 0.5)
+        .collect();
+    tap.write(&sine);
+    meter.update();
+    assert!((meter.peak() - 0.5).abs() < 1e-3);
+    assert!((meter.rms() - 0.5 / 2.0f64.sqrt()).abs() < 1e-3);
+
+    // One second of silence: the peak falls by 20 dB and the RMS to 0.
+    tap.write(&vec![0.0; 48000]);
+    meter.update();
+    assert!((meter.peak() - 0.05).abs() < 1e-3);
+    assert!(meter.rms() < 1e-6);
+}
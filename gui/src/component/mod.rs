@@ -3,6 +3,9 @@ use std::any::Any;
 use crate::{context::Context, interface::*};
 
 pub mod component_container;
+pub mod meter;
+pub mod scope;
+pub mod spectrum;
 pub mod text_box;
 pub mod text_box_container;
 pub mod text_input_state;
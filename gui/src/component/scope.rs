@@ -0,0 +1,95 @@
+use std::{any::Any, marker::PhantomData};
+
+use crate::{context::Context, interface::*, tap::TapReader};
+
+use super::Component;
+
+/// An oscilloscope. Draws one sample per pixel.
+pub struct Scope<U: Ui> {
+    pub rect: Rect,
+    /// The trace starts where the signal rises through this level.
+    /// Without such a point the latest samples are drawn.
+    pub trigger_level: f32,
+    reader: TapReader,
+    samples: Vec<f32>,
+    _t: PhantomData<dyn Fn() -> U>,
+}
+
+impl<U: Ui> Scope<U> {
+    pub fn new(rect: Rect, reader: TapReader) -> Self {
+        Self {
+            rect,
+            trigger_level: 0.0,
+            reader,
+            samples: vec![],
+            _t: Default::default(),
+        }
+    }
+
+    /// The samples to draw.
+    pub fn trace(&self) -> &[f32] {
+        let width = self.rect.2 as usize;
+        if self.samples.len() < width {
+            return &self.samples;
+        }
+        let start = (1..=self.samples.len() - width)
+            .rev()
+            .find(|&i| {
+                self.samples[i - 1] < self.trigger_level && self.trigger_level <= self.samples[i]
+            })
+            .unwrap_or(self.samples.len() - width);
+        &self.samples[start..start + width]
+    }
+}
+
+impl<U: Ui> Component<U> for Scope<U> {
+    fn update(&mut self) {
+        self.reader.read(&mut self.samples);
+        let keep = self.rect.2 as usize * 2;
+        if self.samples.len() > keep {
+            self.samples.drain(..self.samples.len() - keep);
+        }
+    }
+
+    fn message_receive(&mut self, _ctx: &mut Context<U>, _message: &Box<dyn Any>) -> bool {
+        false
+    }
+
+    fn draw(&mut self, ctx: &mut Context<U>) {
+        let Rect(x, y, w, h) = self.rect;
+        let center = y + h as i32 / 2;
+        ctx.canvas.set_draw_color(RGB(192, 192, 192));
+        ctx.canvas
+            .draw_line(Point::new(x, center), Point::new(x + w as i32 - 1, center))
+            .unwrap();
+        ctx.canvas.set_draw_color(RGB(0, 128, 0));
+        let to_y = |v: f32| center - (v.clamp(-1.0, 1.0) * (h / 2) as f32) as i32;
+        let trace = self.trace();
+        for (i, v) in trace.windows(2).enumerate() {
+            let i = i as i32;
+            ctx.canvas
+                .draw_line(
+                    Point::new(x + i, to_y(v[0])),
+                    Point::new(x + i + 1, to_y(v[1])),
+                )
+                .unwrap();
+        }
+        ctx.canvas.set_draw_color(RGB(0, 0, 0));
+        ctx.canvas.draw_rect(self.rect);
+    }
+}
+
+#[test]
+fn test() {
+    let (mut tap, reader) = crate::tap::tap(1024);
+    let mut scope = Scope::<crate::headless::Ui>::new(Rect::new(0, 0, 100, 50), reader);
+
+    // A saw with a period of 30 samples, rising through 0 at multiples of 30 plus 15.
+    let saw: Vec<f32> = (0..300).map(|i| (i % 30) as f32 / 15.0 - 1.0).collect();
+    tap.write(&saw);
+    scope.update();
+    let trace = scope.trace();
+    assert_eq!(trace.len(), 100);
+    assert_eq!(trace[0], 0.0);
+    assert!(trace[1] > 0.0);
+}
@@ -0,0 +1,133 @@
+use std::{any::Any, marker::PhantomData, sync::Arc};
+
+use rustfft::{num_complex::Complex32, Fft, FftPlanner};
+
+use crate::{context::Context, interface::*, tap::TapReader};
+
+use super::Component;
+
+const FFT_SIZE: usize = 2048;
+/// Lowest frequency drawn, in Hz.
+const MIN_FREQUENCY: f64 = 20.0;
+/// Lowest level drawn, in dB.
+const MIN_DB: f32 = -90.0;
+
+/// An FFT spectrum analyser with a logarithmic frequency axis.
+pub struct Spectrum<U: Ui> {
+    pub rect: Rect,
+    reader: TapReader,
+    sample_rate: f64,
+    samples: Vec<f32>,
+    fft: Arc<dyn Fft<f32>>,
+    window: Vec<f32>,
+    _t: PhantomData<dyn Fn() -> U>,
+}
+
+impl<U: Ui> Spectrum<U> {
+    pub fn new(rect: Rect, reader: TapReader, sample_rate: f64) -> Self {
+        let window = (0..FFT_SIZE)
+            .map(|i| 0.5 - 0.5 * (std::f32::consts::TAU * i as f32 / FFT_SIZE as f32).cos())
+            .collect();
+        Self {
+            rect,
+            reader,
+            sample_rate,
+            samples: vec![],
+            fft: FftPlanner::new().plan_fft_forward(FFT_SIZE),
+            window,
+            _t: Default::default(),
+        }
+    }
+
+    /// Levels of the bins up to the Nyquist frequency in dB relative to a full scale sine,
+    /// or `None` until enough samples have arrived.
+    pub fn levels(&self) -> Option<Vec<f32>> {
+        if self.samples.len() < FFT_SIZE {
+            return None;
+        }
+        let mut buffer: Vec<Complex32> = self.samples[self.samples.len() - FFT_SIZE..]
+            .iter()
+            .zip(self.window.iter())
+            .map(|(x, w)| Complex32::new(x * w, 0.0))
+            .collect();
+        self.fft.process(&mut buffer);
+        let scale = 2.0 / self.window.iter().sum::<f32>();
+        Some(
+            buffer[..FFT_SIZE / 2]
+                .iter()
+                .map(|c| 20.0 * (c.norm() * scale).max(1e-10).log10())
+                .collect(),
+        )
+    }
+
+    pub fn bin_frequency(&self, bin: usize) -> f64 {
+        bin as f64 * self.sample_rate / FFT_SIZE as f64
+    }
+}
+
+impl<U: Ui> Component<U> for Spectrum<U> {
+    fn update(&mut self) {
+        self.reader.read(&mut self.samples);
+        if self.samples.len() > FFT_SIZE {
+            self.samples.drain(..self.samples.len() - FFT_SIZE);
+        }
+    }
+
+    fn message_receive(&mut self, _ctx: &mut Context<U>, _message: &Box<dyn Any>) -> bool {
+        false
+    }
+
+    fn draw(&mut self, ctx: &mut Context<U>) {
+        let Rect(x, y, w, h) = self.rect;
+        if let Some(levels) = self.levels() {
+            // Each column shows the loudest bin within its frequency range.
+            let max_frequency = self.sample_rate / 2.0;
+            let bin_at = |column: f64| {
+                let frequency =
+                    MIN_FREQUENCY * (max_frequency / MIN_FREQUENCY).powf(column / w as f64);
+                ((frequency / self.sample_rate * FFT_SIZE as f64) as usize).min(levels.len() - 1)
+            };
+            let to_y = |db: f32| y + ((db / MIN_DB).clamp(0.0, 1.0) * (h - 1) as f32) as i32;
+            let points: Vec<Point> = (0..w)
+                .map(|column| {
+                    let (start, end) = (bin_at(column as f64), bin_at(column as f64 + 1.0));
+                    let db = levels[start..=end.max(start)]
+                        .iter()
+                        .cloned()
+                        .fold(MIN_DB, f32::max);
+                    Point::new(x + column as i32, to_y(db))
+                })
+                .collect();
+            ctx.canvas.set_draw_color(RGB(0, 0, 192));
+            for p in points.windows(2) {
+                ctx.canvas.draw_line(p[0], p[1]).unwrap();
+            }
+        }
+        ctx.canvas.set_draw_color(RGB(0, 0, 0));
+        ctx.canvas.draw_rect(self.rect);
+    }
+}
+
+#[test]
+fn test() {
+    let (mut tap, reader) = crate::tap::tap(4096);
+    let mut spectrum =
+        Spectrum::<crate::headless::Ui>::new(Rect::new(0, 0, 100, 50), reader, 48000.0);
+    assert!(spectrum.levels().is_none());
+
+    let sine: Vec<f32> = (0..FFT_SIZE * 2)
+        .map(|i| (std::f64::consts::TAU * 1000.0 * i as f64 / 48000.0).sin() as f32 * 0.5)
+        .collect();
+    tap.write(&sine);
+    spectrum.update();
+    let levels = spectrum.levels().unwrap();
+    let (peak, db) =
+        levels.iter().enumerate().fold(
+            (0, MIN_DB),
+            |a, (i, db)| if *db > a.1 { (i, *db) } else { a },
+        );
+    assert!((spectrum.bin_frequency(peak) - 1000.0).abs() < 48000.0 / FFT_SIZE as f64);
+    // -6 dB, less the scalloping loss of the Hann window.
+    assert!(-8.0 < db && db < -5.5, "{}", db);
+    assert!(levels[peak * 3] < -60.0);
+}
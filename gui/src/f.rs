@@ -6,7 +6,7 @@ use std::{
 };
 
 use corus::{
-    core::{var::Var, controllable::Controllable, mul::Mul, share::Share, sine::Sine},
+    core::{controllable::Controllable, var::Var},
     notenum_to_frequency,
     signal::Mono,
     time::Sample,
@@ -15,11 +15,15 @@ use corus::{
 
 use crate::{
     component::{
+        component_container::ComponentContainer, meter::Meter, scope::Scope, spectrum::Spectrum,
         text_box::TextBox, text_box_container::TextBoxContainer, text_input_state::TextInputState,
         Component,
     },
     context::Context,
+    input::{KeyboardInput, NoteEvent},
     interface::{VideoSubsystem, *},
+    patch::{BoxedNode, Patch},
+    tap::{tap, Tap},
 };
 
 pub fn f<U: Ui>() {
@@ -30,16 +34,22 @@ pub fn f<U: Ui>() {
     let canvas = window.into_canvas().unwrap();
 
     let audio_ctx = Arc::new(Mutex::new(ProcContext::new(44100 as u64)));
-    let controllable = Controllable::new(Sine::new(Var::from(440.0)));
+    let patch = Patch::new();
+    let controllable = Controllable::new(Box::new(Var::from(0.0)) as BoxedNode);
     let mut controller = controllable.controller();
-    let controllable = Share::new(controllable);
+    let mut controllable = Some(controllable);
+    let (tap, tap_reader) = tap(1 << 15);
+    let mut tap = Some(tap);
+    let mut audio_sample_rate = 44100;
 
     let mut device = audio_subsys
         .open_playback(&mut |sample_rate| {
             *audio_ctx.lock().unwrap() = ProcContext::new(sample_rate as u64);
+            audio_sample_rate = sample_rate;
             Audio::new(
                 audio_ctx.clone(),
-                Box::new(Mul::new(controllable.clone(), Var::from(0.05))),
+                Box::new(controllable.take().unwrap()),
+                tap.take().unwrap(),
             )
         })
         .unwrap();
@@ -50,10 +60,41 @@ pub fn f<U: Ui>() {
     let mut ctx = Context::<U>::new(canvas, font, text_inputing_state, video_subsys);
 
     let mut my_component = TextBoxContainer::new(vec![
-        TextBox::new(0, "hello".to_string(), Rect::new(100, 100, 100, 20)),
-        TextBox::new(1, "world".to_string(), Rect::new(100, 130, 100, 20)),
-        TextBox::new(2, "!!!!".to_string(), Rect::new(100, 160, 100, 20)),
+        TextBox::new(0, "key".to_string(), Rect::new(100, 100, 100, 20)),
+        TextBox::new(1, "sine".to_string(), Rect::new(100, 140, 100, 20)),
+        TextBox::new(2, "* 0.1".to_string(), Rect::new(100, 180, 100, 20)),
+        TextBox::new(3, "out".to_string(), Rect::new(100, 220, 100, 20)),
     ]);
+    for i in 1..4 {
+        my_component.text_boxes[i].inputs.push(i - 1);
+    }
+    let sample_rate = audio_sample_rate as f64;
+    let mut monitors = ComponentContainer::<U>::new(vec![
+        Box::new(Scope::new(Rect::new(300, 100, 256, 100), tap_reader.clone())),
+        Box::new(Spectrum::new(
+            Rect::new(300, 220, 256, 100),
+            tap_reader.clone(),
+            sample_rate,
+        )),
+        Box::new(Meter::new(Rect::new(576, 100, 16, 220), tap_reader, sample_rate)),
+    ]);
+
+    // The patch is rebuilt whenever a text or a wire changes.
+    let mut built_patch = None;
+
+    let mut keyboard = KeyboardInput::new();
+    // Set `MIDI_PORT` to choose among the ports; the first one is used by default.
+    #[cfg(feature = "midi")]
+    let mut midi =
+        crate::input::midi::MidiInput::connect(&std::env::var("MIDI_PORT").unwrap_or_default())
+            .map_err(|e| eprintln!("{}", e))
+            .ok();
+    let play = |note: NoteEvent| {
+        if let NoteEvent::NoteOn { notenum, .. } = note {
+            patch.set_key(notenum_to_frequency(notenum));
+        }
+    };
+    let mut patch_error: Option<String> = None;
 
     'running: loop {
         let audio_time = audio_ctx.lock().unwrap().current_time;
@@ -61,6 +102,11 @@ pub fn f<U: Ui>() {
         ctx.canvas.clear();
 
         my_component.draw(&mut ctx);
+        monitors.update();
+        monitors.draw(&mut ctx);
+        if let Some(error) = &patch_error {
+            ctx.draw_text(error, 10, 450);
+        }
         ctx.text_inputing_state
             .clone()
             .lock()
@@ -68,6 +114,11 @@ pub fn f<U: Ui>() {
             .draw(&mut ctx);
         ctx.canvas.present();
 
+        #[cfg(feature = "midi")]
+        for note in midi.iter_mut().flat_map(|midi| midi.events()) {
+            play(note);
+        }
+
         'event_receive: for ev in ui.events() {
             {
                 if ctx
@@ -80,6 +131,11 @@ pub fn f<U: Ui>() {
                     continue 'event_receive;
                 }
             }
+            if !ctx.text_inputing_state.lock().unwrap().inputing {
+                if let Some(note) = keyboard.event(&ev) {
+                    play(note);
+                }
+            }
             match &ev {
                 Event::Quit { .. }
                 | Event::KeyDown {
@@ -88,34 +144,6 @@ pub fn f<U: Ui>() {
                 } => {
                     break 'running;
                 }
-                Event::KeyDown {
-                    keycode: Some(keycode),
-                    ..
-                } => {
-                    let mut set = |nn: u8| {
-                        // osc_freq_ctrl
-                        //     .lock()
-                        //     .set_value_at_time(audio_time, notenum_to_frequency(nn));
-                        *controller.lock() = Sine::new(Var::from(notenum_to_frequency(nn)));
-                    };
-                    match keycode {
-                        Keycode::Z => set(64),
-                        Keycode::S => set(65),
-                        Keycode::X => set(66),
-                        Keycode::D => set(67),
-                        Keycode::C => set(68),
-                        Keycode::V => set(69),
-                        Keycode::G => set(70),
-                        Keycode::B => set(71),
-                        Keycode::H => set(72),
-                        Keycode::N => set(73),
-                        Keycode::J => set(74),
-                        Keycode::M => set(75),
-                        Keycode::Comma => set(76),
-                        Keycode::Return => {}
-                        _ => (),
-                    }
-                }
                 Event::MouseButtonDown { .. } => {}
                 Event::MouseMotion { x, y, .. } => {
                     // mod_freq_rate_ctrl
@@ -137,6 +165,22 @@ pub fn f<U: Ui>() {
         for message in messages {
             my_component.message_receive(&mut ctx, &message);
         }
+
+        let patch_source: Vec<_> = my_component
+            .text_boxes
+            .iter()
+            .map(|tb| (tb.id, tb.str.clone(), tb.inputs.clone()))
+            .collect();
+        if built_patch.as_ref() != Some(&patch_source) {
+            match patch.build(&my_component.text_boxes) {
+                Ok(node) => {
+                    *controller.lock() = node;
+                    patch_error = None;
+                }
+                Err(e) => patch_error = Some(e),
+            }
+            built_patch = Some(patch_source);
+        }
         thread::sleep(Duration::new(0, 1_000_000_000u32 / 60));
     }
 }
@@ -144,11 +188,16 @@ pub fn f<U: Ui>() {
 pub struct Audio {
     node: Box<dyn Node<Output = f64> + Send + Sync>,
     pub ctx: Arc<Mutex<ProcContext>>,
+    tap: Tap,
 }
 
 impl Audio {
-    pub fn new(ctx: Arc<Mutex<ProcContext>>, node: Box<dyn Node<Output = f64> + Send + Sync>) -> Self {
-        Self { node, ctx }
+    pub fn new(
+        ctx: Arc<Mutex<ProcContext>>,
+        node: Box<dyn Node<Output = f64> + Send + Sync>,
+        tap: Tap,
+    ) -> Self {
+        Self { node, ctx, tap }
     }
 }
 
@@ -159,5 +208,7 @@ impl AudioCallback for Audio {
         for x in out.iter_mut() {
             *x = s.next().unwrap().get_m() as f32;
         }
+        drop(s);
+        self.tap.write(out);
     }
 }
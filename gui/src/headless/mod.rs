@@ -0,0 +1,440 @@
+//! A backend without a display or an audio device, for tests.
+//! Events are scripted with `Ui::push_events`, frames are rendered into memory and audio is pulled
+//! with `AudioSubsystem::pull`.
+
+use std::{
+    collections::VecDeque,
+    path::Path,
+    sync::{Arc, Mutex, Weak},
+};
+
+use crate::interface;
+
+pub const SAMPLE_RATE: i32 = 44100;
+
+pub struct Root;
+
+impl interface::Root for Root {
+    type Ui = Ui;
+
+    type VideoSubsystem = <Self::Ui as interface::Ui>::VideoSubsystem;
+
+    type AudioSubsystem = <Self::Ui as interface::Ui>::AudioSubsystem;
+
+    type Window = Window;
+
+    type Canvas = Canvas;
+
+    type Font = <Self::Canvas as interface::Canvas>::Font;
+}
+
+pub struct Ui {
+    script: VecDeque<Vec<interface::Event>>,
+}
+
+impl Ui {
+    /// Queues `events` as one batch. Each call of `events` returns the next batch.
+    pub fn push_events(&mut self, events: Vec<interface::Event>) {
+        self.script.push_back(events);
+    }
+}
+
+impl interface::Ui for Ui {
+    type VideoSubsystem = VideoSubsystem;
+
+    type AudioSubsystem = AudioSubsystem;
+
+    fn init() -> Result<Self, String> {
+        Ok(Ui {
+            script: VecDeque::new(),
+        })
+    }
+
+    fn video(&mut self) -> Result<Self::VideoSubsystem, String> {
+        Ok(VideoSubsystem {
+            text_input_rect: None,
+        })
+    }
+
+    fn audio(&mut self) -> Result<Self::AudioSubsystem, String> {
+        Ok(AudioSubsystem {
+            sample_rate: SAMPLE_RATE,
+            callbacks: vec![],
+        })
+    }
+
+    /// The file is not read; glyphs are drawn as boxes.
+    fn load_font(&mut self, _path: impl AsRef<Path>, point_size: u16) -> Result<<<<Self::VideoSubsystem as interface::VideoSubsystem>::Window as interface::Window>::Canvas as interface::Canvas>::Font, String>{
+        Ok(Font { point_size })
+    }
+
+    fn events(&mut self) -> Vec<interface::Event> {
+        self.script.pop_front().unwrap_or_default()
+    }
+}
+
+pub struct VideoSubsystem {
+    /// Set by `text_input_start`.
+    pub text_input_rect: Option<interface::Rect>,
+}
+
+impl interface::VideoSubsystem for VideoSubsystem {
+    type Window = Window;
+
+    fn new_window(
+        &mut self,
+        _title: &str,
+        width: u32,
+        height: u32,
+    ) -> Result<Self::Window, String> {
+        Ok(Window { width, height })
+    }
+
+    fn text_input_start(&mut self, rect: interface::Rect) {
+        self.text_input_rect = Some(rect);
+    }
+}
+
+pub struct AudioSubsystem {
+    sample_rate: i32,
+    /// Callbacks of the devices not yet dropped.
+    callbacks: Vec<Weak<Mutex<dyn interface::AudioCallback>>>,
+}
+
+impl AudioSubsystem {
+    /// Runs the callbacks of the open devices for `len` samples and mixes their output.
+    pub fn pull(&mut self, len: usize) -> Vec<f32> {
+        self.callbacks
+            .retain(|callback| callback.strong_count() > 0);
+        let mut out = vec![0.0; len];
+        let mut buffer = vec![0.0; len];
+        for callback in self.callbacks.iter().filter_map(|c| c.upgrade()) {
+            buffer.iter_mut().for_each(|x| *x = 0.0);
+            callback.lock().unwrap().callback(&mut buffer);
+            for (o, x) in out.iter_mut().zip(buffer.iter()) {
+                *o += x;
+            }
+        }
+        out
+    }
+}
+
+impl interface::AudioSubsystem for AudioSubsystem {
+    fn open_playback<A: interface::AudioCallback>(
+        &mut self,
+        get_callback: &mut dyn FnMut(i32) -> A,
+    ) -> Result<Box<dyn interface::AudioDevice<A>>, String> {
+        let callback = Arc::new(Mutex::new(get_callback(self.sample_rate)));
+        self.callbacks
+            .push(Arc::downgrade(&callback) as Weak<Mutex<A>>);
+        Ok(Box::new(AudioDevice { callback }))
+    }
+}
+
+/// Playback stops when dropped.
+pub struct AudioDevice<A: interface::AudioCallback> {
+    callback: Arc<Mutex<A>>,
+}
+
+impl<A: interface::AudioCallback> interface::AudioDevice<A> for AudioDevice<A> {}
+
+pub struct Window {
+    width: u32,
+    height: u32,
+}
+
+impl interface::Window for Window {
+    type Canvas = Canvas;
+
+    fn into_canvas(self) -> Result<Self::Canvas, String> {
+        Ok(Canvas::new(self.width, self.height))
+    }
+}
+
+/// RGBA, 4 bytes per pixel, row by row.
+pub struct Canvas {
+    width: u32,
+    height: u32,
+    color: interface::RGB,
+    back: Vec<u8>,
+    front: Vec<u8>,
+    frame_count: usize,
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32) -> Self {
+        let size = (width * height * 4) as usize;
+        Canvas {
+            width,
+            height,
+            color: interface::RGB(0, 0, 0),
+            back: vec![0; size],
+            front: vec![0; size],
+            frame_count: 0,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The last presented frame.
+    pub fn frame(&self) -> &[u8] {
+        &self.front
+    }
+
+    /// Number of calls of `present`.
+    pub fn frame_count(&self) -> usize {
+        self.frame_count
+    }
+
+    /// A pixel of the last presented frame.
+    pub fn pixel(&self, x: u32, y: u32) -> interface::RGB {
+        let i = ((y * self.width + x) * 4) as usize;
+        interface::RGB(self.front[i], self.front[i + 1], self.front[i + 2])
+    }
+
+    /// Pixels outside the canvas are clipped.
+    fn put(&mut self, x: i32, y: i32) {
+        if x < 0 || y < 0 || self.width as i32 <= x || self.height as i32 <= y {
+            return;
+        }
+        let i = ((y as u32 * self.width + x as u32) * 4) as usize;
+        self.back[i..i + 4].copy_from_slice(&[self.color.0, self.color.1, self.color.2, 255]);
+    }
+}
+
+impl interface::Canvas for Canvas {
+    type Font = Font;
+
+    /// Bresenham's line, both ends included.
+    fn draw_line(&mut self, start: interface::Point, end: interface::Point) -> Result<(), String> {
+        let (dx, dy) = ((end.0 - start.0).abs(), -(end.1 - start.1).abs());
+        let (sx, sy) = ((end.0 - start.0).signum(), (end.1 - start.1).signum());
+        let (mut x, mut y) = (start.0, start.1);
+        let mut error = dx + dy;
+        loop {
+            self.put(x, y);
+            if x == end.0 && y == end.1 {
+                return Ok(());
+            }
+            if 2 * error >= dy {
+                error += dy;
+                x += sx;
+            }
+            if 2 * error <= dx {
+                error += dx;
+                y += sy;
+            }
+        }
+    }
+
+    fn set_draw_color(&mut self, rgb: interface::RGB) {
+        self.color = rgb;
+    }
+
+    /// Outline only, like SDL.
+    fn draw_rect(&mut self, rect: interface::Rect) {
+        if rect.2 == 0 || rect.3 == 0 {
+            return;
+        }
+        let (left, top) = (rect.0, rect.1);
+        let (right, bottom) = (rect.0 + rect.2 as i32 - 1, rect.1 + rect.3 as i32 - 1);
+        for x in left..=right {
+            self.put(x, top);
+            self.put(x, bottom);
+        }
+        for y in top..=bottom {
+            self.put(left, y);
+            self.put(right, y);
+        }
+    }
+
+    /// Each non-blank character is drawn as a black box filling its cell.
+    fn draw_text(&mut self, font: &mut Font, text: &str, x: i32, y: i32) -> (u32, u32) {
+        if text.is_empty() {
+            return (0, 0);
+        }
+        let (w, h) = font.glyph_size();
+        let color = self.color;
+        self.color = interface::RGB(0, 0, 0);
+        for (i, c) in text.chars().enumerate() {
+            if !c.is_whitespace() {
+                let x = x + (i as u32 * w) as i32;
+                interface::Canvas::draw_rect(
+                    self,
+                    interface::Rect::new(x + 1, y + 1, w - 2, h - 2),
+                );
+            }
+        }
+        self.color = color;
+        (w * text.chars().count() as u32, h)
+    }
+
+    /// Fills with the draw color, like SDL.
+    fn clear(&mut self) {
+        let pixel = [self.color.0, self.color.1, self.color.2, 255];
+        for chunk in self.back.chunks_mut(4) {
+            chunk.copy_from_slice(&pixel);
+        }
+    }
+
+    fn present(&mut self) {
+        self.front.copy_from_slice(&self.back);
+        self.frame_count += 1;
+    }
+}
+
+/// Fixed pitch; every glyph is half as wide as it is high.
+pub struct Font {
+    point_size: u16,
+}
+
+impl Font {
+    fn glyph_size(&self) -> (u32, u32) {
+        let h = (self.point_size as u32).max(4);
+        (h / 2, h)
+    }
+}
+
+impl interface::Font for Font {}
+
+#[test]
+fn test() {
+    use std::any::Any;
+
+    use crate::{
+        component::{
+            text_box::TextBox, text_box_container::TextBoxContainer,
+            text_input_state::TextInputState, Component,
+        },
+        context::Context,
+        interface::{
+            AudioCallback, AudioSubsystem as _, Canvas as _, Event, MouseButton, Rect, Ui as _,
+            VideoSubsystem as _, Window as _, RGB,
+        },
+    };
+
+    let mut ui = Ui::init().unwrap();
+    let mut video_subsys = ui.video().unwrap();
+    let canvas = video_subsys
+        .new_window("test", 320, 240)
+        .unwrap()
+        .into_canvas()
+        .unwrap();
+    let font = ui.load_font("./clacon.ttf", 20).unwrap();
+    let mut ctx = Context::<Ui>::new(
+        canvas,
+        font,
+        TextInputState::new(false, "".to_string()),
+        video_subsys,
+    );
+    let mut container = TextBoxContainer::new(vec![TextBox::new(
+        0,
+        "hello".to_string(),
+        Rect::new(100, 100, 100, 20),
+    )]);
+
+    let mouse_button = |down: bool, clicks: u8, x: i32, y: i32| {
+        if down {
+            Event::MouseButtonDown {
+                timestamp: 0,
+                window_id: 0,
+                which: 0,
+                mouse_btn: MouseButton::Left,
+                clicks,
+                x,
+                y,
+            }
+        } else {
+            Event::MouseButtonUp {
+                timestamp: 0,
+                window_id: 0,
+                which: 0,
+                mouse_btn: MouseButton::Left,
+                clicks,
+                x,
+                y,
+            }
+        }
+    };
+    // Drag the box by (10, 20), then double-click it.
+    ui.push_events(vec![
+        mouse_button(true, 1, 110, 105),
+        Event::MouseMotion {
+            timestamp: 0,
+            window_id: 0,
+            which: 0,
+            x: 120,
+            y: 125,
+            xrel: 10,
+            yrel: 20,
+        },
+        mouse_button(false, 1, 120, 125),
+    ]);
+    ui.push_events(vec![
+        mouse_button(true, 2, 120, 125),
+        mouse_button(false, 2, 120, 125),
+    ]);
+
+    let frame = |ui: &mut Ui, ctx: &mut Context<Ui>, container: &mut TextBoxContainer<Ui>| {
+        for event in ui.events() {
+            container.message_receive(ctx, &(Box::new(event) as Box<dyn Any>));
+        }
+        ctx.canvas.set_draw_color(RGB(255, 255, 255));
+        ctx.canvas.clear();
+        container.draw(ctx);
+        ctx.canvas.present();
+    };
+
+    frame(&mut ui, &mut ctx, &mut container);
+    assert_eq!(container.text_boxes[0].rect, Rect::new(110, 120, 100, 20));
+    assert_eq!(ctx.canvas.frame_count(), 1);
+    assert_eq!(ctx.canvas.pixel(110, 120), RGB(0, 0, 0));
+    assert_eq!(ctx.canvas.pixel(209, 139), RGB(0, 0, 0));
+    assert_eq!(ctx.canvas.pixel(100, 100), RGB(255, 255, 255));
+    assert_eq!(ctx.canvas.pixel(150, 130), RGB(255, 255, 255));
+    // Glyphs of "hello".
+    assert_eq!(ctx.canvas.pixel(111, 121), RGB(0, 0, 0));
+    let first = ctx.canvas.frame().to_vec();
+
+    frame(&mut ui, &mut ctx, &mut container);
+    assert_eq!(
+        ctx.video_subsys.text_input_rect,
+        Some(Rect::new(110, 120, 100, 20))
+    );
+    assert_eq!(container.text_boxes[0].str, "");
+    assert_ne!(ctx.canvas.frame(), &first[..]);
+
+    // No more scripted events: the frame is stable.
+    let second = ctx.canvas.frame().to_vec();
+    frame(&mut ui, &mut ctx, &mut container);
+    assert_eq!(ctx.canvas.frame(), &second[..]);
+
+    struct Ramp(f32);
+    impl AudioCallback for Ramp {
+        fn callback(&mut self, out: &mut [f32]) {
+            for x in out.iter_mut() {
+                *x = self.0;
+                self.0 += 1.0;
+            }
+        }
+    }
+    let mut audio_subsys = ui.audio().unwrap();
+    let mut sample_rate = 0;
+    let device = audio_subsys
+        .open_playback(&mut |sr| {
+            sample_rate = sr;
+            Ramp(0.0)
+        })
+        .unwrap();
+    assert_eq!(sample_rate, SAMPLE_RATE);
+    assert_eq!(audio_subsys.pull(3), vec![0.0, 1.0, 2.0]);
+    assert_eq!(audio_subsys.pull(2), vec![3.0, 4.0]);
+    drop(device);
+    assert_eq!(audio_subsys.pull(2), vec![0.0, 0.0]);
+}
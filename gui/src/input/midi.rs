@@ -0,0 +1,84 @@
+//! MIDI device input through ALSA.
+
+use std::sync::mpsc::{channel, Receiver};
+
+use midir::MidiInputConnection;
+
+use super::NoteEvent;
+
+pub struct MidiInput {
+    _connection: MidiInputConnection<()>,
+    receiver: Receiver<NoteEvent>,
+}
+
+impl MidiInput {
+    /// Connects to the first port whose name contains `port_name`.
+    pub fn connect(port_name: &str) -> Result<Self, String> {
+        let input = midir::MidiInput::new("corus gui").map_err(|e| e.to_string())?;
+        let port = input
+            .ports()
+            .into_iter()
+            .find(|port| {
+                input
+                    .port_name(port)
+                    .map_or(false, |name| name.contains(port_name))
+            })
+            .ok_or_else(|| format!("no MIDI port named {}", port_name))?;
+        let (sender, receiver) = channel();
+        let connection = input
+            .connect(
+                &port,
+                "corus gui input",
+                move |_, message, _| {
+                    if let Some(event) = NoteEvent::from_midi(message) {
+                        sender.send(event).ok();
+                    }
+                },
+                (),
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            _connection: connection,
+            receiver,
+        })
+    }
+
+    /// Events received since the last call.
+    pub fn events(&mut self) -> Vec<NoteEvent> {
+        self.receiver.try_iter().collect()
+    }
+}
+
+#[test]
+fn test() {
+    use midir::os::unix::VirtualOutput;
+
+    // A virtual port loops the messages back.
+    let mut output = midir::MidiOutput::new("corus gui test")
+        .unwrap()
+        .create_virtual("corus gui loopback")
+        .unwrap();
+    let mut input = MidiInput::connect("corus gui loopback").unwrap();
+    output.send(&[0x90, 60, 127]).unwrap();
+    output.send(&[0xb0, 1, 64]).unwrap();
+    output.send(&[0x80, 60, 0]).unwrap();
+
+    let mut events = vec![];
+    for _ in 0..100 {
+        events.extend(input.events());
+        if events.len() >= 2 {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    assert_eq!(
+        events,
+        vec![
+            NoteEvent::NoteOn {
+                notenum: 60,
+                velocity: 1.0
+            },
+            NoteEvent::NoteOff { notenum: 60 }
+        ]
+    );
+}
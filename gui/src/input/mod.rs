@@ -0,0 +1,237 @@
+//! Note input from the computer keyboard and, with the `midi` feature, from MIDI devices.
+
+#[cfg(feature = "midi")]
+pub mod midi;
+
+use corus::{
+    contrib::generic_poly_synth::{NoteHandler, PolySynth},
+    core::Node,
+    signal::C1f64,
+};
+
+use crate::interface::{Event, Keycode};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NoteEvent {
+    /// `velocity` is in 0..=1.
+    NoteOn {
+        notenum: u8,
+        velocity: f64,
+    },
+    NoteOff {
+        notenum: u8,
+    },
+}
+
+impl NoteEvent {
+    /// Voices are told apart by note number and take `(notenum, velocity)`.
+    pub fn apply<A>(&self, synth: &mut PolySynth<(u8, f64), (), A, Option<u8>>, time: f64)
+    where
+        A: Node<Output = C1f64> + NoteHandler<(u8, f64), ()>,
+    {
+        match *self {
+            NoteEvent::NoteOn { notenum, velocity } => {
+                synth.note_on(time, Some(notenum), (notenum, velocity))
+            }
+            NoteEvent::NoteOff { notenum } => synth.note_off(time, Some(notenum), ()),
+        }
+    }
+
+    /// Note on and note off messages of any channel. A note on with velocity 0 is a note off.
+    pub fn from_midi(message: &[u8]) -> Option<Self> {
+        match *message {
+            [status, notenum, velocity] if status & 0xf0 == 0x90 && velocity != 0 => {
+                Some(NoteEvent::NoteOn {
+                    notenum,
+                    velocity: velocity as f64 / 127.0,
+                })
+            }
+            [status, notenum, _] if status & 0xf0 == 0x80 || status & 0xf0 == 0x90 => {
+                Some(NoteEvent::NoteOff { notenum })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Plays notes with the computer keyboard. Keys down and up become note ons and note offs.
+pub struct KeyboardInput {
+    /// Keys and their notes in semitones above `base_notenum`.
+    pub layout: Vec<(Keycode, i32)>,
+    pub base_notenum: u8,
+    /// Shift in octaves.
+    pub octave: i32,
+    pub octave_down: Keycode,
+    pub octave_up: Keycode,
+    /// Keys that select the velocity of the following notes.
+    pub velocity_keys: Vec<(Keycode, f64)>,
+    pub velocity: f64,
+    /// Keys held and the notes they started, so that they end even if the octave changed.
+    held: Vec<(Keycode, u8)>,
+}
+
+impl KeyboardInput {
+    /// The bottom row laid out like a piano keyboard from `Z`, the arrow keys shifting octaves
+    /// and the digits selecting velocities.
+    pub fn new() -> Self {
+        use Keycode::*;
+        Self {
+            layout: [
+                Z, S, X, D, C, V, G, B, H, N, J, M, Comma, L, Period, Semicolon, Slash,
+            ]
+            .iter()
+            .cloned()
+            .zip(0..)
+            .collect(),
+            base_notenum: 64,
+            octave: 0,
+            octave_down: Down,
+            octave_up: Up,
+            velocity_keys: [Num1, Num2, Num3, Num4, Num5, Num6, Num7, Num8, Num9]
+                .iter()
+                .cloned()
+                .zip(1..)
+                .map(|(key, i)| (key, i as f64 / 9.0))
+                .collect(),
+            velocity: 1.0,
+            held: vec![],
+        }
+    }
+
+    pub fn event(&mut self, event: &Event) -> Option<NoteEvent> {
+        match event {
+            Event::KeyDown {
+                keycode: Some(keycode),
+                repeat: false,
+                ..
+            } => {
+                if *keycode == self.octave_down {
+                    self.octave -= 1;
+                } else if *keycode == self.octave_up {
+                    self.octave += 1;
+                } else if let Some((_, velocity)) =
+                    self.velocity_keys.iter().find(|(k, _)| k == keycode)
+                {
+                    self.velocity = *velocity;
+                } else if let Some((_, offset)) = self.layout.iter().find(|(k, _)| k == keycode) {
+                    if self.held.iter().any(|(k, _)| k == keycode) {
+                        return None;
+                    }
+                    let notenum = self.base_notenum as i32 + self.octave * 12 + offset;
+                    if !(0..128).contains(&notenum) {
+                        return None;
+                    }
+                    self.held.push((keycode.clone(), notenum as u8));
+                    return Some(NoteEvent::NoteOn {
+                        notenum: notenum as u8,
+                        velocity: self.velocity,
+                    });
+                }
+                None
+            }
+            Event::KeyUp {
+                keycode: Some(keycode),
+                ..
+            } => {
+                let i = self.held.iter().position(|(k, _)| k == keycode)?;
+                let (_, notenum) = self.held.remove(i);
+                Some(NoteEvent::NoteOff { notenum })
+            }
+            _ => None,
+        }
+    }
+}
+
+#[test]
+fn test() {
+    let key = |down: bool, keycode: Keycode, repeat: bool| {
+        let keycode = Some(keycode);
+        if down {
+            Event::KeyDown {
+                timestamp: 0,
+                window_id: 0,
+                keycode,
+                repeat,
+            }
+        } else {
+            Event::KeyUp {
+                timestamp: 0,
+                window_id: 0,
+                keycode,
+                repeat,
+            }
+        }
+    };
+
+    let mut input = KeyboardInput::new();
+    assert_eq!(
+        input.event(&key(true, Keycode::C, false)),
+        Some(NoteEvent::NoteOn {
+            notenum: 68,
+            velocity: 1.0
+        })
+    );
+    assert_eq!(input.event(&key(true, Keycode::C, true)), None);
+    assert_eq!(input.event(&key(true, Keycode::Num3, false)), None);
+    assert_eq!(input.event(&key(true, Keycode::Up, false)), None);
+    assert_eq!(
+        input.event(&key(true, Keycode::Z, false)),
+        Some(NoteEvent::NoteOn {
+            notenum: 76,
+            velocity: 3.0 / 9.0
+        })
+    );
+    // Released after the octave shift, `C` still ends its own note.
+    assert_eq!(
+        input.event(&key(false, Keycode::C, false)),
+        Some(NoteEvent::NoteOff { notenum: 68 })
+    );
+    assert_eq!(input.event(&key(false, Keycode::C, false)), None);
+    assert_eq!(input.event(&key(false, Keycode::A, false)), None);
+
+    assert_eq!(
+        NoteEvent::from_midi(&[0x91, 60, 127]),
+        Some(NoteEvent::NoteOn {
+            notenum: 60,
+            velocity: 1.0
+        })
+    );
+    assert_eq!(
+        NoteEvent::from_midi(&[0x90, 60, 0]),
+        Some(NoteEvent::NoteOff { notenum: 60 })
+    );
+    assert_eq!(
+        NoteEvent::from_midi(&[0x80, 60, 64]),
+        Some(NoteEvent::NoteOff { notenum: 60 })
+    );
+    assert_eq!(NoteEvent::from_midi(&[0xb0, 1, 64]), None);
+
+    // Drives a `generic_poly_synth::PolySynth`.
+    use corus::{contrib::generic_poly_synth::Voice, core::var::Var, time::Sample, ProcContext};
+    use std::sync::{Arc, Mutex};
+    let played = Arc::new(Mutex::new(vec![]));
+    let mut synth = PolySynth::new(
+        || {
+            let (on, off) = (played.clone(), played.clone());
+            Voice::new(
+                Var::new(0.0),
+                Box::new(move |_, (notenum, _)| on.lock().unwrap().push(notenum as i32)),
+                Box::new(move |_, ()| off.lock().unwrap().push(-1)),
+            )
+        },
+        2,
+    );
+    for event in [
+        NoteEvent::from_midi(&[0x90, 60, 100]),
+        NoteEvent::from_midi(&[0x90, 64, 100]),
+        NoteEvent::from_midi(&[0x80, 60, 0]),
+    ]
+    .iter()
+    .flatten()
+    {
+        event.apply(&mut synth, 0.0);
+    }
+    assert_eq!(*played.lock().unwrap(), vec![60, 64, -1]);
+    let mut ctx = ProcContext::new(44100);
+    assert_eq!(ctx.lock(&mut synth, Sample(1)).next(), Some(0.0));
+}
@@ -6,7 +6,11 @@ mod fm;
 mod rand_fm;
 mod interface;
 mod framework;
+mod headless;
+mod input;
 mod model;
+mod patch;
+mod tap;
 
 fn main() {
     f::f::<framework::Ui>();
@@ -0,0 +1,311 @@
+//! The language of the text boxes. Each box holds an operator followed by numeric arguments and
+//! its input is the sum of the boxes wired into it:
+//!
+//! - `440`: a constant.
+//! - `sine [frequency]`, `saw [frequency]`, `square [frequency] [duty]`: the input is added to the frequency.
+//! - `lpf [frequency] [q]`, `hpf [frequency] [q]`, `bpf [frequency] [q]`: filter the input.
+//! - `* x`, `+ x`: scale or offset the input.
+//! - `key`: frequency of the last key played.
+//! - `out`: the input goes to the speakers.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use corus::{
+    core::{
+        accumulator::Accumulator,
+        add::Add,
+        biquad_filter::{
+            types::{BandPass, HighPass, LowPass},
+            BiquadFilter, BiquadFilterParams,
+        },
+        map::Map,
+        mul::Mul,
+        share::Share,
+        sine::Sine,
+        var::Var,
+    },
+    Node, ProcContext,
+};
+
+use crate::{component::text_box::TextBox, interface::Ui};
+
+pub type BoxedNode = Box<dyn Node<Output = f64> + Send + Sync>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Op {
+    Number(f64),
+    Sine(f64),
+    Saw(f64),
+    Square(f64, f64),
+    LowPass(f64, f64),
+    HighPass(f64, f64),
+    BandPass(f64, f64),
+    Mul(f64),
+    Add(f64),
+    Key,
+    Out,
+}
+
+impl Op {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut tokens = text.split_whitespace();
+        let name = tokens.next().ok_or_else(|| "empty".to_string())?;
+        if let Ok(x) = name.parse() {
+            args::<0>(tokens, [])?;
+            return Ok(Op::Number(x));
+        }
+        Ok(match name {
+            "sine" => {
+                let [f] = args(tokens, [0.0])?;
+                Op::Sine(f)
+            }
+            "saw" => {
+                let [f] = args(tokens, [0.0])?;
+                Op::Saw(f)
+            }
+            "square" => {
+                let [f, duty] = args(tokens, [0.0, 0.5])?;
+                Op::Square(f, duty)
+            }
+            "lpf" => {
+                let [f, q] = args(tokens, [1000.0, 0.7])?;
+                Op::LowPass(f, q)
+            }
+            "hpf" => {
+                let [f, q] = args(tokens, [1000.0, 0.7])?;
+                Op::HighPass(f, q)
+            }
+            "bpf" => {
+                let [f, q] = args(tokens, [1000.0, 0.7])?;
+                Op::BandPass(f, q)
+            }
+            "*" => {
+                let [x] = args(tokens, [1.0])?;
+                Op::Mul(x)
+            }
+            "+" => {
+                let [x] = args(tokens, [0.0])?;
+                Op::Add(x)
+            }
+            "key" => {
+                args::<0>(tokens, [])?;
+                Op::Key
+            }
+            "out" => {
+                args::<0>(tokens, [])?;
+                Op::Out
+            }
+            _ => return Err(format!("unknown operator `{}`", name)),
+        })
+    }
+
+    fn build(&self, input: BoxedNode, key: &Arc<AtomicU64>) -> BoxedNode {
+        match *self {
+            Op::Number(x) => Box::new(Var::new(x)),
+            Op::Sine(f) => Box::new(Sine::new(Add::new(input, Var::new(f)))),
+            Op::Saw(f) => Box::new(Map::new(
+                Accumulator::new(Add::new(input, Var::new(f)), 1.0),
+                |x: f64| x * 2.0 - 1.0,
+            )),
+            Op::Square(f, duty) => Box::new(Map::new(
+                Accumulator::new(Add::new(input, Var::new(f)), 1.0),
+                move |x: f64| if x < duty { -1.0 } else { 1.0 },
+            )),
+            Op::LowPass(f, q) => Box::new(BiquadFilter::new(
+                input,
+                BiquadFilterParams::new(LowPass, Var::new(f), Var::new(0.0), Var::new(q)),
+            )),
+            Op::HighPass(f, q) => Box::new(BiquadFilter::new(
+                input,
+                BiquadFilterParams::new(HighPass, Var::new(f), Var::new(0.0), Var::new(q)),
+            )),
+            Op::BandPass(f, q) => Box::new(BiquadFilter::new(
+                input,
+                BiquadFilterParams::new(BandPass, Var::new(f), Var::new(0.0), Var::new(q)),
+            )),
+            Op::Mul(x) => Box::new(Mul::new(input, Var::new(x))),
+            Op::Add(x) => Box::new(Add::new(input, Var::new(x))),
+            Op::Key => Box::new(Atomic(key.clone())),
+            Op::Out => input,
+        }
+    }
+}
+
+/// Fills the arguments left out with `defaults`.
+fn args<'a, const N: usize>(
+    tokens: impl Iterator<Item = &'a str>,
+    defaults: [f64; N],
+) -> Result<[f64; N], String> {
+    let mut args = defaults;
+    for (i, token) in tokens.enumerate() {
+        if i == N {
+            return Err(format!("takes at most {} arguments", N));
+        }
+        args[i] = token
+            .parse()
+            .map_err(|_| format!("`{}` is not a number", token))?;
+    }
+    Ok(args)
+}
+
+pub struct Patch {
+    key: Arc<AtomicU64>,
+}
+
+impl Patch {
+    pub fn new() -> Self {
+        Patch {
+            key: Arc::new(AtomicU64::new(440.0f64.to_bits())),
+        }
+    }
+
+    /// Read by `key` boxes of the graphs built already, too.
+    pub fn set_key(&self, frequency: f64) {
+        self.key.store(frequency.to_bits(), Ordering::Relaxed);
+    }
+
+    /// The sum of the `out` boxes. A box wired into several boxes is processed once per sample.
+    pub fn build<U: Ui>(&self, text_boxes: &[TextBox<U>]) -> Result<BoxedNode, String> {
+        let ops = text_boxes
+            .iter()
+            .map(|tb| {
+                Op::parse(&tb.str)
+                    .map(|op| (tb.id, op))
+                    .map_err(|e| format!("{}: {}", tb.str, e))
+            })
+            .collect::<Result<HashMap<_, _>, _>>()?;
+        let mut builder = Builder {
+            key: &self.key,
+            text_boxes,
+            ops: &ops,
+            built: HashMap::new(),
+            visiting: vec![],
+        };
+        let mut outs = vec![];
+        for tb in text_boxes {
+            if ops[&tb.id] == Op::Out {
+                outs.push(builder.build(tb.id)?);
+            }
+        }
+        Ok(sum(outs))
+    }
+}
+
+struct Builder<'a, U: Ui> {
+    key: &'a Arc<AtomicU64>,
+    text_boxes: &'a [TextBox<U>],
+    ops: &'a HashMap<usize, Op>,
+    built: HashMap<usize, Share<BoxedNode>>,
+    /// For detecting cycles.
+    visiting: Vec<usize>,
+}
+
+impl<'a, U: Ui> Builder<'a, U> {
+    fn build(&mut self, id: usize) -> Result<BoxedNode, String> {
+        if let Some(node) = self.built.get(&id) {
+            return Ok(Box::new(node.clone()));
+        }
+        if self.visiting.contains(&id) {
+            return Err("wires form a cycle".to_string());
+        }
+        let tb = self
+            .text_boxes
+            .iter()
+            .find(|tb| tb.id == id)
+            .ok_or_else(|| format!("no box {}", id))?;
+
+        self.visiting.push(id);
+        let inputs = tb
+            .inputs
+            .iter()
+            .map(|i| self.build(*i))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.visiting.pop();
+
+        let node = Share::new(self.ops[&id].build(sum(inputs), self.key));
+        self.built.insert(id, node.clone());
+        Ok(Box::new(node))
+    }
+}
+
+fn sum(nodes: Vec<BoxedNode>) -> BoxedNode {
+    nodes
+        .into_iter()
+        .reduce(|a, b| Box::new(Add::new(a, b)))
+        .unwrap_or_else(|| Box::new(Var::new(0.0)))
+}
+
+struct Atomic(Arc<AtomicU64>);
+
+impl Node for Atomic {
+    type Output = f64;
+
+    fn proc(&mut self, _ctx: &ProcContext) -> f64 {
+        f64::from_bits(self.0.load(Ordering::Relaxed))
+    }
+
+    fn lock(&mut self, _ctx: &ProcContext) {}
+
+    fn unlock(&mut self) {}
+}
+
+#[test]
+fn test() {
+    use crate::{headless, interface::Rect};
+    use corus::time::Sample;
+
+    assert_eq!(Op::parse("sine 440"), Ok(Op::Sine(440.0)));
+    assert_eq!(Op::parse(" lpf  800 "), Ok(Op::LowPass(800.0, 0.7)));
+    assert_eq!(Op::parse("* 0.2"), Ok(Op::Mul(0.2)));
+    assert_eq!(Op::parse("-1.5"), Ok(Op::Number(-1.5)));
+    assert!(Op::parse("").is_err());
+    assert!(Op::parse("sine a").is_err());
+    assert!(Op::parse("* 1 2").is_err());
+    assert!(Op::parse("cosine").is_err());
+
+    let text_box = |id: usize, str: &str, inputs: Vec<usize>| {
+        let mut tb = TextBox::<headless::Ui>::new(id, str.to_string(), Rect::new(0, 0, 1, 1));
+        tb.inputs = inputs;
+        tb
+    };
+    let render = |text_boxes: &[TextBox<headless::Ui>]| -> Result<Vec<f64>, String> {
+        let mut node = Patch::new().build(text_boxes)?;
+        let mut ctx = ProcContext::new(44100);
+        let samples = ctx.lock(&mut node, Sample(100)).collect();
+        Ok(samples)
+    };
+
+    // The sine is shared by both `*` boxes and still advances once per sample.
+    let samples = render(&[
+        text_box(0, "key", vec![]),
+        text_box(1, "sine", vec![0]),
+        text_box(2, "* 0.5", vec![1]),
+        text_box(3, "* 0.25", vec![1]),
+        text_box(4, "out", vec![2, 3]),
+    ])
+    .unwrap();
+    for (i, x) in samples.iter().enumerate() {
+        let expected = 0.75 * (std::f64::consts::TAU * 440.0 * i as f64 / 44100.0).sin();
+        assert!((x - expected).abs() < 1e-9);
+    }
+
+    // No `out`: silence.
+    assert_eq!(
+        render(&[text_box(0, "sine 440", vec![])]).unwrap(),
+        vec![0.0; 100]
+    );
+
+    assert!(render(&[
+        text_box(0, "+ 1", vec![1]),
+        text_box(1, "+ 1", vec![0]),
+        text_box(2, "out", vec![0]),
+    ])
+    .is_err());
+    assert!(render(&[text_box(0, "out", vec![]), text_box(1, "lpf x", vec![])]).is_err());
+}
@@ -0,0 +1,119 @@
+//! Copies audio from the audio callback to the UI thread without locking.
+//! The audio side writes into a ring buffer and never waits; readers that fall behind by more
+//! than the capacity skip the samples overwritten.
+
+use std::sync::{
+    atomic::{fence, AtomicU32, AtomicUsize, Ordering},
+    Arc,
+};
+
+struct Shared {
+    buffer: Box<[AtomicU32]>,
+    /// Number of samples written so far.
+    written: AtomicUsize,
+    /// Raised before the samples are written, like a seqlock.
+    reserved: AtomicUsize,
+}
+
+/// The writing end, owned by the audio callback.
+pub struct Tap {
+    shared: Arc<Shared>,
+}
+
+/// A reading end. Clones read independently.
+#[derive(Clone)]
+pub struct TapReader {
+    shared: Arc<Shared>,
+    read: usize,
+}
+
+pub fn tap(capacity: usize) -> (Tap, TapReader) {
+    assert!(capacity > 0);
+    let shared = Arc::new(Shared {
+        buffer: (0..capacity).map(|_| AtomicU32::new(0)).collect(),
+        written: AtomicUsize::new(0),
+        reserved: AtomicUsize::new(0),
+    });
+    (
+        Tap {
+            shared: shared.clone(),
+        },
+        TapReader { shared, read: 0 },
+    )
+}
+
+impl Tap {
+    pub fn write(&mut self, samples: &[f32]) {
+        let buffer = &self.shared.buffer;
+        let written = self.shared.written.load(Ordering::Relaxed);
+        self.shared
+            .reserved
+            .store(written + samples.len(), Ordering::Relaxed);
+        fence(Ordering::Release);
+        for (i, x) in samples.iter().enumerate() {
+            buffer[(written + i) % buffer.len()].store(x.to_bits(), Ordering::Relaxed);
+        }
+        self.shared
+            .written
+            .store(written + samples.len(), Ordering::Release);
+    }
+}
+
+impl TapReader {
+    /// Appends the samples written since the last call to `out`.
+    pub fn read(&mut self, out: &mut Vec<f32>) {
+        let buffer = &self.shared.buffer;
+        let written = self.shared.written.load(Ordering::Acquire);
+        let start = self.read.max(written.saturating_sub(buffer.len()));
+        let len = out.len();
+        out.extend(
+            (start..written)
+                .map(|i| f32::from_bits(buffer[i % buffer.len()].load(Ordering::Relaxed))),
+        );
+
+        // Drop the samples the writer may have overwritten while they were copied.
+        fence(Ordering::Acquire);
+        let reserved = self.shared.reserved.load(Ordering::Relaxed);
+        let valid_start = reserved.saturating_sub(buffer.len());
+        if start < valid_start {
+            out.drain(len..len + (valid_start - start).min(written - start));
+        }
+        self.read = written;
+    }
+}
+
+#[test]
+fn test() {
+    let (mut tap, mut reader) = tap(4);
+    let mut reader2 = reader.clone();
+    let mut out = vec![];
+
+    tap.write(&[1.0, 2.0]);
+    reader.read(&mut out);
+    assert_eq!(out, vec![1.0, 2.0]);
+    reader.read(&mut out);
+    assert_eq!(out, vec![1.0, 2.0]);
+
+    // Only the last 4 samples are kept.
+    tap.write(&[3.0, 4.0, 5.0, 6.0, 7.0]);
+    reader.read(&mut out);
+    assert_eq!(out, vec![1.0, 2.0, 4.0, 5.0, 6.0, 7.0]);
+
+    let mut out2 = vec![];
+    reader2.read(&mut out2);
+    assert_eq!(out2, vec![4.0, 5.0, 6.0, 7.0]);
+
+    let thread = std::thread::spawn(move || {
+        for i in 0..10000 {
+            tap.write(&[i as f32]);
+        }
+    });
+    let mut out = vec![];
+    while !thread.is_finished() {
+        reader.read(&mut out);
+    }
+    reader.read(&mut out);
+    // Samples may be skipped but never reordered or torn.
+    assert!(out.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(*out.last().unwrap(), 9999.0);
+}
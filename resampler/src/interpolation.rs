@@ -0,0 +1,219 @@
+use std::sync::OnceLock;
+
+use crate::{Kernel, Quality, Sample};
+
+const SINC_QUALITY: Quality = Quality::Low;
+
+/// Ways to read between samples, e.g. for fractional delays.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Interpolation {
+    /// The sample at the position rounded down.
+    NearestNeighbor,
+    #[default]
+    Linear,
+    /// 4-point cubic Hermite (Catmull-Rom).
+    Hermite,
+    /// 3rd order Lagrange, on 4 points.
+    Lagrange3,
+    /// 5th order Lagrange, on 6 points.
+    Lagrange5,
+    /// 1st order allpass. Flat magnitude, but it has state: use an `Interpolator` per read
+    /// position and read it once per sample.
+    Thiran,
+    /// Kaiser-windowed sinc, as in `Resampler` but without lowering the cutoff.
+    Sinc,
+}
+
+impl Interpolation {
+    /// Smallest position readable without samples at negative indices.
+    /// For delay lines, where index 0 is the newest sample, it is the latency of the method.
+    pub fn min_delay(self) -> f64 {
+        match self {
+            Interpolation::NearestNeighbor | Interpolation::Linear => 0.0,
+            Interpolation::Hermite | Interpolation::Lagrange3 => 1.0,
+            Interpolation::Lagrange5 => 2.0,
+            Interpolation::Thiran => 0.5,
+            Interpolation::Sinc => SINC_QUALITY.zero_crossings() as f64,
+        }
+    }
+
+    /// Value at `x` in `buffer`, 0 outside. `Thiran` starts from silence at each call.
+    pub fn tap<T: Sample>(self, buffer: &[T], x: f64) -> T {
+        Interpolator::new(self).get(
+            |i| {
+                usize::try_from(i)
+                    .ok()
+                    .and_then(|i| buffer.get(i).copied())
+                    .unwrap_or_default()
+            },
+            x,
+        )
+    }
+}
+
+/// Reads a signal at fractional positions. `getter(i)` gives the sample at index `i`.
+#[derive(Debug, Clone)]
+pub struct Interpolator<T: Sample> {
+    pub interpolation: Interpolation,
+    thiran: T,
+}
+
+impl<T: Sample> Interpolator<T> {
+    pub fn new(interpolation: Interpolation) -> Self {
+        Self {
+            interpolation,
+            thiran: T::default(),
+        }
+    }
+
+    #[inline]
+    pub fn get(&mut self, getter: impl Fn(isize) -> T, x: f64) -> T {
+        let n = x.floor();
+        let t = x - n;
+        let n = n as isize;
+        match self.interpolation {
+            Interpolation::NearestNeighbor => getter(n),
+            Interpolation::Linear => {
+                if t == 0.0 {
+                    return getter(n);
+                }
+                weighted(&getter, n, &[1.0 - t, t])
+            }
+            Interpolation::Hermite => {
+                let (t2, t3) = (t * t, t * t * t);
+                weighted(
+                    &getter,
+                    n - 1,
+                    &[
+                        (-t3 + 2.0 * t2 - t) * 0.5,
+                        (3.0 * t3 - 5.0 * t2 + 2.0) * 0.5,
+                        (-3.0 * t3 + 4.0 * t2 + t) * 0.5,
+                        (t3 - t2) * 0.5,
+                    ],
+                )
+            }
+            Interpolation::Lagrange3 => weighted(&getter, n - 1, &lagrange::<4>(t + 1.0)),
+            Interpolation::Lagrange5 => weighted(&getter, n - 2, &lagrange::<6>(t + 2.0)),
+            Interpolation::Thiran => {
+                // The fractional part is kept in [0.5, 1.5), where the allpass is accurate.
+                let n = (x - 0.5).floor();
+                let d = x - n;
+                let a = (1.0 - d) / (1.0 + d);
+                let n = n as isize;
+                let y = getter(n).mul_add(a, getter(n + 1));
+                self.thiran = self.thiran.mul_add(-a, y);
+                self.thiran
+            }
+            Interpolation::Sinc => {
+                static KERNEL: OnceLock<Kernel> = OnceLock::new();
+                KERNEL
+                    .get_or_init(|| Kernel::new(SINC_QUALITY))
+                    .interpolate(getter, x, 1.0)
+            }
+        }
+    }
+}
+
+#[inline]
+fn weighted<T: Sample>(getter: &impl Fn(isize) -> T, start: isize, weights: &[f64]) -> T {
+    weights
+        .iter()
+        .zip(start..)
+        .fold(T::default(), |acc, (w, i)| getter(i).mul_add(*w, acc))
+}
+
+/// Weights of the points 0..N at `x`.
+#[inline]
+fn lagrange<const N: usize>(x: f64) -> [f64; N] {
+    let mut weights = [1.0; N];
+    for (k, w) in weights.iter_mut().enumerate() {
+        for j in 0..N {
+            if j != k {
+                *w *= (x - j as f64) / (k as f64 - j as f64);
+            }
+        }
+    }
+    weights
+}
+
+#[test]
+fn test() {
+    use std::f64::consts::TAU;
+
+    let all = [
+        Interpolation::NearestNeighbor,
+        Interpolation::Linear,
+        Interpolation::Hermite,
+        Interpolation::Lagrange3,
+        Interpolation::Lagrange5,
+        Interpolation::Thiran,
+        Interpolation::Sinc,
+    ];
+
+    // Integer positions are exact, except for the allpass that settles first.
+    let buffer: Vec<f64> = (0..64).map(|i| (i as f64 * 0.37).sin()).collect();
+    for interpolation in all {
+        if interpolation != Interpolation::Thiran {
+            assert!((interpolation.tap(&buffer, 30.0) - buffer[30]).abs() < 1e-9);
+        }
+    }
+    assert_eq!(
+        Interpolation::Lagrange3.tap(&[1.0, 2.0, 3.0, 4.0], 1.5),
+        2.5
+    );
+    assert_eq!(Interpolation::NearestNeighbor.tap(&[1.0, 2.0], 0.9), 1.0);
+
+    // Reads a sine of `frequency` (relative to the sample rate) delayed by `delay(n)` samples.
+    let render = |interpolation: Interpolation, frequency: f64, delay: &dyn Fn(usize) -> f64| {
+        let mut interpolator = Interpolator::new(interpolation);
+        let signal = |i: isize| (i as f64 * frequency * TAU).sin();
+        let mut error = 0.0f64;
+        let mut gain = [0.0, 0.0];
+        for n in 0..20000 {
+            let d = delay(n);
+            let y = interpolator.get(|i| signal(n as isize - i), d);
+            if 1000 <= n {
+                let phase = (n as f64 - d) * frequency * TAU;
+                error = error.max((y - phase.sin()).abs());
+                gain[0] += y * phase.sin();
+                gain[1] += y * phase.cos();
+            }
+        }
+        let gain = (gain[0].powi(2) + gain[1].powi(2)).sqrt() * 2.0 / 19000.0;
+        (gain, error)
+    };
+
+    // Frequency response at a half-sample delay, the worst case for most methods.
+    let response = |interpolation, frequency| render(interpolation, frequency, &|_| 20.5);
+    let linear = response(Interpolation::Linear, 0.25);
+    assert!((linear.0 - (TAU / 8.0).cos()).abs() < 1e-3);
+    let hermite = response(Interpolation::Hermite, 0.25);
+    let lagrange3 = response(Interpolation::Lagrange3, 0.25);
+    let lagrange5 = response(Interpolation::Lagrange5, 0.25);
+    // At half a sample, Catmull-Rom and Lagrange weights coincide.
+    assert!((hermite.0 - lagrange3.0).abs() < 1e-9);
+    assert!(linear.0 < hermite.0 && lagrange3.0 < lagrange5.0 && lagrange5.0 < 1.0);
+    assert!((response(Interpolation::Thiran, 0.25).0 - 1.0).abs() < 1e-3);
+    let sinc = response(Interpolation::Sinc, 0.25);
+    assert!((sinc.0 - 1.0).abs() < 1e-2 && sinc.1 < 2e-2);
+    for interpolation in all.into_iter().skip(1) {
+        let (gain, error) = response(interpolation, 0.01);
+        assert!(
+            (gain - 1.0).abs() < 1e-3 && error < 1e-2,
+            "{:?}",
+            interpolation
+        );
+    }
+
+    // A delay swept like a chorus: the error grows with the artefacts.
+    let modulated = |interpolation| {
+        render(interpolation, 0.05, &|n| {
+            25.0 + 5.0 * (n as f64 * 0.001).sin()
+        })
+        .1
+    };
+    let errors = all.map(modulated);
+    assert!(0.1 < errors[0]);
+    assert!(errors[2] < errors[1] && errors[4] < errors[3] && errors[3] < errors[1]);
+    assert!(errors[5] < errors[1] && errors[6] < errors[2]);
+}
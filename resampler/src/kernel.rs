@@ -0,0 +1,87 @@
+use crate::{Quality, Sample};
+
+/// Tabulated Kaiser-windowed sinc.
+#[derive(Debug, Clone)]
+pub struct Kernel {
+    zero_crossings: usize,
+    phases: usize,
+    table: Vec<f64>,
+}
+
+impl Kernel {
+    pub fn new(quality: Quality) -> Self {
+        let zero_crossings = quality.zero_crossings();
+        let phases = quality.phases();
+        let beta = quality.beta();
+        let len = zero_crossings * phases;
+        let table = (0..=len + 1)
+            .map(|i| {
+                let x = i as f64 / phases as f64;
+                if len < i {
+                    return 0.0;
+                }
+                let r = x / zero_crossings as f64;
+                sinc(x) * bessel_i0(beta * (1.0 - r * r).max(0.0).sqrt()) / bessel_i0(beta)
+            })
+            .collect();
+        Self {
+            zero_crossings,
+            phases,
+            table,
+        }
+    }
+
+    pub fn zero_crossings(&self) -> usize {
+        self.zero_crossings
+    }
+
+    /// Kernel value at `x` zero crossings from the centre.
+    #[inline]
+    pub fn get(&self, x: f64) -> f64 {
+        let x = x.abs() * self.phases as f64;
+        let i = x as usize;
+        if self.table.len() - 1 <= i {
+            return 0.0;
+        }
+        let t = x - i as f64;
+        self.table[i] + (self.table[i + 1] - self.table[i]) * t
+    }
+
+    /// Band-limited value at fractional `position`.
+    /// `cutoff` is relative to the input Nyquist frequency.
+    pub fn interpolate<T: Sample>(
+        &self,
+        getter: impl Fn(isize) -> T,
+        position: f64,
+        cutoff: f64,
+    ) -> T {
+        let width = self.zero_crossings as f64 / cutoff;
+        let start = (position - width).ceil() as isize;
+        let end = (position + width).floor() as isize;
+        (start..=end).fold(T::default(), |acc, j| {
+            let weight = cutoff * self.get((position - j as f64) * cutoff);
+            getter(j).mul_add(weight, acc)
+        })
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x == 0.0 {
+        1.0
+    } else {
+        let x = x * std::f64::consts::PI;
+        x.sin() / x
+    }
+}
+
+fn bessel_i0(x: f64) -> f64 {
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    let mut k = 1.0;
+    while term > sum * 1e-12 {
+        term *= (x / (2.0 * k)).powi(2);
+        sum += term;
+        k += 1.0;
+    }
+    sum
+}
@@ -0,0 +1,114 @@
+mod interpolation;
+mod kernel;
+mod resampler;
+
+pub use interpolation::{Interpolation, Interpolator};
+pub use kernel::Kernel;
+pub use resampler::Resampler;
+
+/// A value that can be resampled.
+pub trait Sample: Copy + Default {
+    /// Returns `acc + self * weight`.
+    fn mul_add(self, weight: f64, acc: Self) -> Self;
+}
+
+impl Sample for f64 {
+    #[inline]
+    fn mul_add(self, weight: f64, acc: Self) -> Self {
+        acc + self * weight
+    }
+}
+
+impl Sample for f32 {
+    #[inline]
+    fn mul_add(self, weight: f64, acc: Self) -> Self {
+        acc + self * weight as f32
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quality {
+    Low,
+    Medium,
+    High,
+}
+
+impl Quality {
+    /// Zero crossings of the sinc on each side.
+    pub fn zero_crossings(&self) -> usize {
+        match self {
+            Quality::Low => 8,
+            Quality::Medium => 16,
+            Quality::High => 32,
+        }
+    }
+
+    /// Kernel table entries per zero crossing.
+    pub fn phases(&self) -> usize {
+        match self {
+            Quality::Low => 128,
+            Quality::Medium => 256,
+            Quality::High => 1024,
+        }
+    }
+
+    /// Kaiser window beta.
+    pub fn beta(&self) -> f64 {
+        match self {
+            Quality::Low => 6.0,
+            Quality::Medium => 8.6,
+            Quality::High => 12.0,
+        }
+    }
+
+    /// Cutoff relative to the lower Nyquist frequency.
+    pub fn rolloff(&self) -> f64 {
+        match self {
+            Quality::Low => 0.9,
+            Quality::Medium => 0.94,
+            Quality::High => 0.97,
+        }
+    }
+}
+
+/// Converts a whole buffer, without delay.
+pub fn resample_buffer<T: Sample>(
+    input: &[T],
+    input_sample_rate: f64,
+    output_sample_rate: f64,
+    quality: Quality,
+) -> Vec<T> {
+    let kernel = Kernel::new(quality);
+    let in_per_out = input_sample_rate / output_sample_rate;
+    let cutoff = quality.rolloff() * in_per_out.recip().min(1.0);
+    let len = (input.len() as f64 / in_per_out).round() as usize;
+    (0..len)
+        .map(|i| {
+            kernel.interpolate(
+                |j| {
+                    usize::try_from(j)
+                        .ok()
+                        .and_then(|j| input.get(j).copied())
+                        .unwrap_or_default()
+                },
+                i as f64 * in_per_out,
+                cutoff,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_resample_buffer() {
+    let sine = |sample_rate: f64| {
+        (0..sample_rate as usize)
+            .map(|i| (i as f64 / sample_rate * 1000.0 * std::f64::consts::TAU).sin())
+            .collect::<Vec<_>>()
+    };
+    let output = resample_buffer(&sine(44100.0), 44100.0, 48000.0, Quality::Medium);
+    let expected = sine(48000.0);
+    assert_eq!(output.len(), expected.len());
+    for i in 100..output.len() - 100 {
+        assert!((output[i] - expected[i]).abs() < 1e-3);
+    }
+}
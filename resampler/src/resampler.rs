@@ -0,0 +1,143 @@
+use crate::{Kernel, Quality, Sample};
+
+/// Streaming band-limited resampler.
+///
+/// The kernel width is fixed by the rates given to `new`, so `set_rates` never allocates
+/// and the delay stays constant. Lowering the output rate further than that widens the
+/// passband beyond the kernel and lets some aliasing through.
+#[derive(Debug, Clone)]
+pub struct Resampler<T: Sample> {
+    kernel: Kernel,
+    quality: Quality,
+    min_cutoff: f64,
+    in_per_out: f64,
+    cutoff: f64,
+    history: Vec<T>,
+    pos: usize,
+    delay: usize,
+    time: f64,
+}
+
+impl<T: Sample> Resampler<T> {
+    pub fn new(input_sample_rate: f64, output_sample_rate: f64, quality: Quality) -> Self {
+        let kernel = Kernel::new(quality);
+        let in_per_out = input_sample_rate / output_sample_rate;
+        let min_cutoff = quality.rolloff() * in_per_out.recip().min(1.0);
+        let delay = (kernel.zero_crossings() as f64 / min_cutoff).ceil() as usize;
+        Self {
+            kernel,
+            quality,
+            min_cutoff,
+            in_per_out,
+            cutoff: min_cutoff,
+            history: vec![T::default(); delay * 2],
+            pos: 0,
+            delay,
+            time: 1.0 - in_per_out,
+        }
+    }
+
+    /// Changes the ratio on the fly, e.g. for pitch modulation.
+    pub fn set_rates(&mut self, input_sample_rate: f64, output_sample_rate: f64) {
+        self.in_per_out = input_sample_rate / output_sample_rate;
+        self.cutoff =
+            (self.quality.rolloff() * self.in_per_out.recip().min(1.0)).max(self.min_cutoff);
+    }
+
+    pub fn in_per_out(&self) -> f64 {
+        self.in_per_out
+    }
+
+    /// Delay in input samples. Constant, including while both rates are equal.
+    pub fn delay(&self) -> f64 {
+        self.delay as f64
+    }
+
+    pub fn process(&mut self, mut x: impl FnMut() -> T) -> T {
+        self.time += self.in_per_out;
+        while 1.0 <= self.time {
+            self.time -= 1.0;
+            self.pos = (self.pos + 1) % self.history.len();
+            self.history[self.pos] = x();
+        }
+        // Index 0 is the oldest input. The output is `delay - time` samples before the newest.
+        let len = self.history.len();
+        if self.is_aligned() {
+            return self.history[(self.pos + len - self.delay) % len];
+        }
+
+        let position = (len - 1) as f64 - self.delay as f64 + self.time;
+        self.kernel.interpolate(
+            |j| {
+                if (0..len as isize).contains(&j) {
+                    self.history[(self.pos + 1 + j as usize) % len]
+                } else {
+                    T::default()
+                }
+            },
+            position,
+            self.cutoff,
+        )
+    }
+
+    /// Equal rates with the output on an input sample, which is passed through as is.
+    fn is_aligned(&self) -> bool {
+        self.in_per_out == 1.0 && self.time == 0.0
+    }
+}
+
+#[test]
+fn test() {
+    let rates = [(48000.0, 44100.0), (44100.0, 48000.0), (96000.0, 48000.0)];
+    for (input_sample_rate, output_sample_rate) in rates {
+        let mut resampler = Resampler::new(input_sample_rate, output_sample_rate, Quality::Medium);
+        let frequency = 1000.0;
+        let mut i = 0;
+        let delay = resampler.delay() / input_sample_rate;
+        for n in 0..4800 {
+            let y = resampler.process(|| {
+                let t = i as f64 / input_sample_rate;
+                i += 1;
+                (t * frequency * std::f64::consts::TAU).sin()
+            });
+            if 200 < n {
+                let t = n as f64 / output_sample_rate - delay;
+                let expected = (t * frequency * std::f64::consts::TAU).sin();
+                assert!((y - expected).abs() < 1e-3, "{} {}", y, expected);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_equal_rates() {
+    let mut resampler = Resampler::new(44100.0, 48000.0, Quality::Medium);
+    let delay = resampler.delay();
+    let mut i = 0;
+    let mut prev = 0.0;
+    for n in 0..4800 {
+        if n == 2400 {
+            resampler.set_rates(48000.0, 48000.0);
+        }
+        let y = resampler.process(|| {
+            i += 1;
+            (i as f64 * 0.01).sin()
+        });
+        assert_eq!(resampler.delay(), delay);
+        if 200 < n {
+            assert!((y - prev).abs() < 0.02, "{} {}", y, prev);
+        }
+        prev = y;
+    }
+
+    let mut resampler = Resampler::new(48000.0, 48000.0, Quality::Medium);
+    let delay = resampler.delay() as usize;
+    let mut i = 0;
+    for n in 0..100usize {
+        let y = resampler.process(|| {
+            i += 1;
+            i as f64
+        });
+        assert_eq!(y, (n + 1).saturating_sub(delay) as f64);
+    }
+}
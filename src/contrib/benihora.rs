@@ -1,4 +1,9 @@
-use crate::{core::Node, signal::C1f64, ProcContext};
+use benihora::{
+    glottis::{External, GlottalSource},
+    phoneme::Articulation,
+};
+
+use crate::{core::Node, signal::C1f64, EventListener, ProcContext};
 
 type F = f64;
 
@@ -12,6 +17,10 @@ impl Benihora {
             benihora: benihora::BenihoraManaged::new(sound_speed, sample_rate, 0),
         }
     }
+
+    pub fn set_glottal_source(&mut self, source: Box<dyn GlottalSource>) {
+        self.benihora.benihora.glottis.source = source;
+    }
 }
 
 impl Node for Benihora {
@@ -35,9 +44,10 @@ pub enum BenihoraEvent {
     SetTenseness(F),
     SetStatus(bool),
     SetVibrato(F, F),
+    SetArticulation(Articulation),
 }
 
-impl crate::EventListener<BenihoraEvent> for Benihora {
+impl EventListener<BenihoraEvent> for Benihora {
     #[inline]
     fn apply_event(&mut self, _: f64, event: &BenihoraEvent) {
         match event {
@@ -70,6 +80,53 @@ impl crate::EventListener<BenihoraEvent> for Benihora {
                 self.benihora.frequency.vibrato_amount = *amount;
                 self.benihora.frequency.vibrato_frequency = *frequency;
             }
+            BenihoraEvent::SetArticulation(articulation) => {
+                articulation.apply(&mut self.benihora);
+            }
         }
     }
 }
+
+/// Benihora whose tract is excited by `excitation` instead of the glottis.
+pub struct BenihoraTalkBox<A: Node<Output = C1f64>> {
+    benihora: Benihora,
+    excitation: A,
+}
+
+impl<A: Node<Output = C1f64>> BenihoraTalkBox<A> {
+    pub fn new(excitation: A, sound_speed: f64, sample_rate: f64) -> Self {
+        let mut benihora = Benihora::new(sound_speed, sample_rate);
+        let inner_sample_rate = benihora.benihora.benihora.inner_sample_rate();
+        benihora.set_glottal_source(Box::new(External::new(sample_rate, inner_sample_rate)));
+        Self {
+            benihora,
+            excitation,
+        }
+    }
+}
+
+impl<A: Node<Output = C1f64>> Node for BenihoraTalkBox<A> {
+    type Output = C1f64;
+
+    #[inline]
+    fn proc(&mut self, ctx: &ProcContext) -> C1f64 {
+        let x = self.excitation.proc(ctx);
+        self.benihora.benihora.benihora.glottis.source.set_input(x);
+        self.benihora.proc(ctx)
+    }
+
+    fn lock(&mut self, ctx: &ProcContext) {
+        self.excitation.lock(ctx);
+    }
+
+    fn unlock(&mut self) {
+        self.excitation.unlock();
+    }
+}
+
+impl<A: Node<Output = C1f64> + 'static> EventListener<BenihoraEvent> for BenihoraTalkBox<A> {
+    #[inline]
+    fn apply_event(&mut self, time: f64, event: &BenihoraEvent) {
+        self.benihora.apply_event(time, event);
+    }
+}
@@ -1,44 +1,109 @@
 use std::borrow::Borrow;
 
+use resampler::{Quality, Resampler, Sample};
+
 use crate::{core::Node, proc_context::ProcContext};
 
 pub struct BufferPlayback<T, B>
 where
-    T: 'static + Clone + Default,
+    T: 'static + Sample,
     B: Borrow<Vec<T>>,
 {
     buffer: B,
-    _t: std::marker::PhantomData<T>,
+    sample_rate: Option<u64>,
+    quality: Quality,
+    resampler: Option<(u64, Resampler<T>)>,
+    position: usize,
 }
 
 impl<T, B> BufferPlayback<T, B>
 where
-    T: 'static + Clone + Default,
+    T: 'static + Sample,
     B: Borrow<Vec<T>>,
 {
+    /// Plays `buffer` at the output sample rate.
     pub fn new(buffer: B) -> Self {
         BufferPlayback {
             buffer,
-            _t: Default::default(),
+            sample_rate: None,
+            quality: Quality::Medium,
+            resampler: None,
+            position: 0,
+        }
+    }
+
+    /// Plays `buffer` recorded at `sample_rate`, band-limited resampled to the output sample rate.
+    pub fn with_sample_rate(buffer: B, sample_rate: u64, quality: Quality) -> Self {
+        BufferPlayback {
+            sample_rate: Some(sample_rate),
+            quality,
+            ..Self::new(buffer)
+        }
+    }
+
+    /// Delay in seconds.
+    pub fn delay(&self) -> f64 {
+        match (self.sample_rate, &self.resampler) {
+            (Some(sample_rate), Some((_, resampler))) => resampler.delay() / sample_rate as f64,
+            _ => 0.0,
         }
     }
 }
 
 impl<T, B> Node for BufferPlayback<T, B>
 where
-    T: 'static + Clone + Default,
+    T: 'static + Sample,
     B: Borrow<Vec<T>>,
 {
     type Output = T;
 
     #[inline]
     fn proc(&mut self, ctx: &ProcContext) -> Self::Output {
-        let s = (ctx.current_time * ctx.sample_rate as f64) as usize;
         let buf = self.buffer.borrow();
-        buf[s % buf.len()].clone()
+        let sample_rate = match self.sample_rate {
+            Some(sample_rate) if sample_rate != ctx.sample_rate => sample_rate,
+            _ => {
+                let s = (ctx.current_time * ctx.sample_rate as f64) as usize;
+                return buf[s % buf.len()];
+            }
+        };
+        if self.resampler.as_ref().map(|(sr, _)| *sr) != Some(ctx.sample_rate) {
+            self.resampler = Some((
+                ctx.sample_rate,
+                Resampler::new(sample_rate as f64, ctx.sample_rate as f64, self.quality),
+            ));
+            self.position = (ctx.current_time * sample_rate as f64) as usize;
+        }
+        let (_, resampler) = self.resampler.as_mut().unwrap();
+        let position = &mut self.position;
+        resampler.process(|| {
+            let x = buf[*position % buf.len()];
+            *position += 1;
+            x
+        })
     }
 
     fn lock(&mut self, _ctx: &ProcContext) {}
 
     fn unlock(&mut self) {}
 }
+
+#[test]
+fn test() {
+    let frequency = 1000.0;
+    let buffer: Vec<f64> = (0..22050)
+        .map(|i| (i as f64 / 22050.0 * frequency * std::f64::consts::TAU).sin())
+        .collect();
+    let mut node = BufferPlayback::with_sample_rate(buffer, 22050, Quality::Medium);
+    let delay = Resampler::<f64>::new(22050.0, 44100.0, Quality::Medium).delay() / 22050.0;
+    let mut ctx = ProcContext::new(44100);
+    let mut lock = ctx.lock(&mut node, crate::time::Second(0.1));
+    for n in 0..4410 {
+        let y = lock.sample();
+        if 200 < n {
+            let t = n as f64 / 44100.0 - delay;
+            let expected = (t * frequency * std::f64::consts::TAU).sin();
+            assert!((y - expected).abs() < 1e-3, "{} {}", y, expected);
+        }
+    }
+}
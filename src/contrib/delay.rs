@@ -1,11 +1,12 @@
-use crate::{ring_buffer::RingBuffer, signal::Signal};
+use crate::{
+    interpolation::{round_nearest_neighbor, Interpolator},
+    ring_buffer::RingBuffer,
+    signal::Signal,
+};
 
 use super::{Node, ProcContext};
 
-pub enum Interpolation {
-    NearestNeighbor,
-    Linear,
-}
+pub use crate::interpolation::Interpolation;
 
 pub struct Delay<A, B>
 where
@@ -17,7 +18,7 @@ where
     node: A,
     delay: B,
     buffer: RingBuffer<A::Output>,
-    interpolation: Interpolation,
+    interpolator: Interpolator<A::Output>,
 }
 
 impl<A, B> Delay<A, B>
@@ -32,7 +33,7 @@ where
             node,
             delay,
             buffer: RingBuffer::new(size),
-            interpolation,
+            interpolator: Interpolator::new(interpolation),
         }
     }
 }
@@ -50,17 +51,11 @@ where
     fn proc(&mut self, ctx: &ProcContext) -> Self::Output {
         let v = self.node.proc(ctx);
         let delay = self.delay.proc(ctx);
-        self.buffer.push(v.clone());
-        match self.interpolation {
-            Interpolation::NearestNeighbor => self.buffer.get(delay.round() as usize),
-            Interpolation::Linear => {
-                let delay_i = delay.floor() as usize;
-                self.buffer.get(delay_i).lerp(
-                    &self.buffer.get(delay_i + 1),
-                    <A::Output as Signal>::Float::from(delay.fract()),
-                )
-            }
-        }
+        self.buffer.push(v);
+        let interpolation = self.interpolator.interpolation;
+        let delay = round_nearest_neighbor(interpolation, delay).max(interpolation.min_delay());
+        let buffer = &self.buffer;
+        self.interpolator.get(|i| buffer.get_or_default(i), delay)
     }
 
     fn lock(&mut self, ctx: &ProcContext) {
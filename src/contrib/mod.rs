@@ -23,6 +23,7 @@ mod perlin_noise;
 pub mod poly_synth;
 pub mod rand;
 pub mod rand_fm_synth;
+pub mod render_graph;
 pub mod resample;
 pub mod rms;
 pub mod schroeder;
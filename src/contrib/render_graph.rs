@@ -0,0 +1,441 @@
+use std::{
+    cell::UnsafeCell,
+    ops::Add,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+use crate::{time::Sample, EventQueue, Node, ProcContext};
+
+/// Polls before an idle worker parks.
+const SPIN_COUNT: usize = 1 << 12;
+
+/// A unit of work rendered a block at a time on any thread.
+pub trait Job<T>: Send {
+    fn lock(&mut self, ctx: &ProcContext);
+    fn unlock(&mut self);
+    /// `input` is the sum of the outputs of the dependencies, as long as `output`.
+    fn render(&mut self, input: &[T], output: &mut [T]);
+}
+
+/// Renders a node with its own context. Adds the input, so it also serves as a bus.
+pub struct NodeJob<A: Node> {
+    node: A,
+    context: Option<ProcContext>,
+}
+
+impl<A: Node> NodeJob<A> {
+    pub fn new(node: A) -> Self {
+        Self {
+            node,
+            context: None,
+        }
+    }
+}
+
+impl<A> Job<A::Output> for NodeJob<A>
+where
+    A: Node + Send,
+    A::Output: Clone + Add<Output = A::Output>,
+{
+    fn lock(&mut self, ctx: &ProcContext) {
+        let mut context = ctx.clone();
+        context.event_queue = match self.context.take() {
+            Some(old_context) => old_context.event_queue,
+            None => EventQueue::new(),
+        };
+        self.context = Some(context);
+    }
+
+    fn unlock(&mut self) {}
+
+    fn render(&mut self, input: &[A::Output], output: &mut [A::Output]) {
+        let context = self.context.as_mut().unwrap();
+        let samples = context.lock(&mut self.node, Sample(output.len() as u64));
+        for ((y, x), s) in output.iter_mut().zip(input).zip(samples) {
+            *y = x.clone() + s;
+        }
+    }
+}
+
+/// Maps the input sample by sample, such as an effect on a bus.
+pub struct FnJob<F>(pub F);
+
+impl<T: Clone, F: FnMut(T) -> T + Send> Job<T> for FnJob<F> {
+    fn lock(&mut self, _ctx: &ProcContext) {}
+
+    fn unlock(&mut self) {}
+
+    fn render(&mut self, input: &[T], output: &mut [T]) {
+        for (y, x) in output.iter_mut().zip(input) {
+            *y = (self.0)(x.clone());
+        }
+    }
+}
+
+/// Renders a graph of jobs across a fixed pool of threads, a block at a time.
+///
+/// A job starts once its dependencies have finished the block. Ready jobs go to the queue of the
+/// thread that readied them and idle threads steal from the others. The thread calling `proc`
+/// takes part, and the scheduling neither locks nor allocates while rendering. The output is the
+/// sum of the jobs nobody depends on.
+pub struct RenderGraph<T> {
+    block_size: usize,
+    thread_num: usize,
+    /// Until the first `lock`, when the threads start.
+    jobs: Vec<(Box<dyn Job<T>>, Vec<usize>)>,
+    shared: Option<Arc<Shared<T>>>,
+    threads: Vec<thread::JoinHandle<()>>,
+    sinks: Vec<usize>,
+    output: Vec<T>,
+    output_i: usize,
+    output_len: usize,
+    rest: usize,
+}
+
+impl<T> RenderGraph<T>
+where
+    T: Clone + Add<Output = T> + Default + Send + 'static,
+{
+    /// `thread_num` threads help the one calling `proc`.
+    pub fn new(block_size: usize, thread_num: usize) -> Self {
+        assert!(block_size > 0);
+        Self {
+            block_size,
+            thread_num,
+            jobs: vec![],
+            shared: None,
+            threads: vec![],
+            sinks: vec![],
+            output: vec![Default::default(); block_size],
+            output_i: 0,
+            output_len: 0,
+            rest: 0,
+        }
+    }
+
+    /// `dependencies` are ids returned earlier, so the graph has no cycles.
+    pub fn add_job(&mut self, job: impl Job<T> + 'static, dependencies: &[usize]) -> usize {
+        assert!(self.shared.is_none(), "the graph has started");
+        let id = self.jobs.len();
+        assert!(dependencies.iter().all(|d| *d < id));
+        self.jobs.push((Box::new(job), dependencies.to_vec()));
+        id
+    }
+
+    fn start(&mut self) {
+        let mut dependents = vec![vec![]; self.jobs.len()];
+        for (i, (_, dependencies)) in self.jobs.iter().enumerate() {
+            for d in dependencies {
+                dependents[*d].push(i);
+            }
+        }
+        self.sinks = (0..self.jobs.len())
+            .filter(|i| dependents[*i].is_empty())
+            .collect();
+        let len = self.jobs.len();
+        let slots = std::mem::take(&mut self.jobs)
+            .into_iter()
+            .zip(dependents)
+            .map(|((job, dependencies), dependents)| Slot {
+                inner: UnsafeCell::new(SlotInner {
+                    job,
+                    input: vec![Default::default(); self.block_size],
+                    output: vec![Default::default(); self.block_size],
+                }),
+                pending: AtomicUsize::new(0),
+                dependencies,
+                dependents,
+            })
+            .collect();
+        let shared = Arc::new(Shared {
+            slots,
+            queues: (0..=self.thread_num).map(|_| Queue::new(len)).collect(),
+            remaining: AtomicUsize::new(0),
+            len: AtomicUsize::new(0),
+            epoch: AtomicUsize::new(0),
+            end: AtomicBool::new(false),
+        });
+        self.threads = (1..=self.thread_num)
+            .map(|me| {
+                let shared = shared.clone();
+                thread::spawn(move || {
+                    let mut epoch = 0;
+                    loop {
+                        let mut spins = 0;
+                        loop {
+                            if shared.end.load(Ordering::Acquire) {
+                                return;
+                            }
+                            let e = shared.epoch.load(Ordering::Acquire);
+                            if e != epoch {
+                                epoch = e;
+                                break;
+                            }
+                            if spins < SPIN_COUNT {
+                                spins += 1;
+                                std::hint::spin_loop();
+                            } else {
+                                thread::park();
+                            }
+                        }
+                        shared.work(me);
+                    }
+                })
+            })
+            .collect();
+        self.shared = Some(shared);
+    }
+
+    fn render_block(&mut self) {
+        let shared = self.shared.as_ref().unwrap();
+        let len = self.rest.min(self.block_size);
+        shared.len.store(len, Ordering::Relaxed);
+        for slot in shared.slots.iter() {
+            slot.pending
+                .store(slot.dependencies.len(), Ordering::Relaxed);
+        }
+        shared
+            .remaining
+            .store(shared.slots.len(), Ordering::Release);
+        for (i, slot) in shared.slots.iter().enumerate() {
+            if slot.dependencies.is_empty() {
+                shared.queues[0].push(i);
+            }
+        }
+        shared.epoch.fetch_add(1, Ordering::Release);
+        for thread in self.threads.iter() {
+            thread.thread().unpark();
+        }
+        shared.work(0);
+
+        self.output[..len].fill(Default::default());
+        for i in self.sinks.iter() {
+            // The block is done, so no other thread touches the slots.
+            let sink = unsafe { &*shared.slots[*i].inner.get() };
+            for (y, x) in self.output.iter_mut().zip(&sink.output[..len]) {
+                *y = y.clone() + x.clone();
+            }
+        }
+        self.output_i = 0;
+        self.output_len = len;
+        self.rest -= len;
+    }
+
+    /// Jobs can be touched only between blocks.
+    fn for_each_job(&mut self, mut f: impl FnMut(&mut dyn Job<T>)) {
+        match &self.shared {
+            Some(shared) => {
+                for slot in shared.slots.iter() {
+                    f(unsafe { &mut *slot.inner.get() }.job.as_mut());
+                }
+            }
+            None => {
+                for (job, _) in self.jobs.iter_mut() {
+                    f(job.as_mut());
+                }
+            }
+        }
+    }
+}
+
+impl<T> Node for RenderGraph<T>
+where
+    T: Clone + Add<Output = T> + Default + Send + 'static,
+{
+    type Output = T;
+
+    #[inline]
+    fn proc(&mut self, _ctx: &ProcContext) -> Self::Output {
+        if self.output_i == self.output_len {
+            self.render_block();
+        }
+        let x = self.output[self.output_i].clone();
+        self.output_i += 1;
+        x
+    }
+
+    fn lock(&mut self, ctx: &ProcContext) {
+        if self.shared.is_none() {
+            self.start();
+        }
+        self.for_each_job(|job| job.lock(ctx));
+        self.rest = ctx.rest_proc_samples as usize;
+        self.output_i = 0;
+        self.output_len = 0;
+    }
+
+    fn unlock(&mut self) {
+        self.for_each_job(|job| job.unlock());
+    }
+}
+
+impl<T> Drop for RenderGraph<T> {
+    fn drop(&mut self) {
+        if let Some(shared) = &self.shared {
+            shared.end.store(true, Ordering::Release);
+        }
+        for thread in self.threads.drain(..) {
+            thread.thread().unpark();
+            let _ = thread.join();
+        }
+    }
+}
+
+struct SlotInner<T> {
+    job: Box<dyn Job<T>>,
+    input: Vec<T>,
+    output: Vec<T>,
+}
+
+struct Slot<T> {
+    /// Owned by the thread that popped the job until it finishes the block.
+    inner: UnsafeCell<SlotInner<T>>,
+    /// Dependencies yet to finish the block.
+    pending: AtomicUsize,
+    dependencies: Vec<usize>,
+    dependents: Vec<usize>,
+}
+
+struct Shared<T> {
+    slots: Box<[Slot<T>]>,
+    /// Index 0 is the thread calling `proc`.
+    queues: Box<[Queue]>,
+    /// Jobs yet to finish the block.
+    remaining: AtomicUsize,
+    /// Samples in the block.
+    len: AtomicUsize,
+    /// Counts the blocks to wake the workers.
+    epoch: AtomicUsize,
+    end: AtomicBool,
+}
+
+unsafe impl<T: Send> Sync for Shared<T> {}
+unsafe impl<T: Send> Send for Shared<T> {}
+
+impl<T: Clone + Add<Output = T> + Default> Shared<T> {
+    /// Runs and steals jobs until the block is done.
+    fn work(&self, me: usize) {
+        let n = self.queues.len();
+        while self.remaining.load(Ordering::Acquire) != 0 {
+            match (0..n).find_map(|k| self.queues[(me + k) % n].pop()) {
+                Some(i) => self.run(i, me),
+                None => std::hint::spin_loop(),
+            }
+        }
+    }
+
+    fn run(&self, i: usize, me: usize) {
+        let len = self.len.load(Ordering::Relaxed);
+        let slot = &self.slots[i];
+        let inner = unsafe { &mut *slot.inner.get() };
+        let input = &mut inner.input[..len];
+        input.fill(Default::default());
+        for d in slot.dependencies.iter() {
+            // Finished, so only read from now on.
+            let dependency = unsafe { &*self.slots[*d].inner.get() };
+            for (y, x) in input.iter_mut().zip(&dependency.output) {
+                *y = y.clone() + x.clone();
+            }
+        }
+        inner.job.render(input, &mut inner.output[..len]);
+
+        for d in slot.dependents.iter() {
+            if self.slots[*d].pending.fetch_sub(1, Ordering::AcqRel) == 1 {
+                self.queues[me].push(*d);
+            }
+        }
+        self.remaining.fetch_sub(1, Ordering::Release);
+    }
+}
+
+/// Bounded lock-free queue of job ids: its thread pushes, any thread pops.
+/// Each job is queued at most once a block, so it never holds more than the jobs.
+struct Queue {
+    buffer: Box<[AtomicUsize]>,
+    head: AtomicUsize,
+    tail: AtomicUsize,
+}
+
+impl Queue {
+    fn new(capacity: usize) -> Self {
+        Self {
+            buffer: (0..capacity.max(1)).map(|_| AtomicUsize::new(0)).collect(),
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+        }
+    }
+
+    /// By the owning thread only.
+    fn push(&self, x: usize) {
+        let tail = self.tail.load(Ordering::Relaxed);
+        self.buffer[tail % self.buffer.len()].store(x, Ordering::Relaxed);
+        self.tail.store(tail + 1, Ordering::Release);
+    }
+
+    fn pop(&self) -> Option<usize> {
+        loop {
+            let head = self.head.load(Ordering::Acquire);
+            if self.tail.load(Ordering::Acquire) <= head {
+                return None;
+            }
+            let x = self.buffer[head % self.buffer.len()].load(Ordering::Relaxed);
+            if self
+                .head
+                .compare_exchange_weak(head, head + 1, Ordering::AcqRel, Ordering::Relaxed)
+                .is_ok()
+            {
+                return Some(x);
+            }
+        }
+    }
+}
+
+#[test]
+fn test() {
+    use crate::core::{sine::Sine, var::Var};
+
+    // Four voices into two buses into a master, against the same in one thread.
+    let build = |graph: &mut RenderGraph<f64>| {
+        let voices: Vec<_> = (0..4)
+            .map(|i| {
+                graph.add_job(
+                    NodeJob::new(Sine::new(Var::from(110.0 * (i + 1) as f64))),
+                    &[],
+                )
+            })
+            .collect();
+        let low = graph.add_job(FnJob(|x: f64| x * 0.5), &voices[..2]);
+        let high = graph.add_job(FnJob(|x: f64| x * 0.25), &voices[2..]);
+        graph.add_job(FnJob(|x: f64| x.tanh()), &[low, high]);
+    };
+    let mut single = RenderGraph::new(64, 0);
+    build(&mut single);
+    let mut parallel = RenderGraph::new(64, 3);
+    build(&mut parallel);
+
+    let a: Vec<f64> = ProcContext::new(44100)
+        .lock(&mut single, Sample(1000))
+        .collect();
+    let mut ctx = ProcContext::new(44100);
+    let b: Vec<f64> = ctx.lock(&mut parallel, Sample(1000)).collect();
+    assert_eq!(a, b);
+    // Blocks carry on across locks.
+    let c: Vec<f64> = ctx.lock(&mut parallel, Sample(1000)).collect();
+    assert_ne!(b, c);
+
+    let expected: Vec<f64> = (0..1000)
+        .map(|t| {
+            let sine = |i: usize| {
+                (t as f64 / 44100.0 * 110.0 * (i + 1) as f64 * std::f64::consts::TAU).sin()
+            };
+            ((sine(0) + sine(1)) * 0.5 + (sine(2) + sine(3)) * 0.25).tanh()
+        })
+        .collect();
+    for (a, b) in a.iter().zip(expected.iter()) {
+        assert!((a - b).abs() < 1e-9);
+    }
+}
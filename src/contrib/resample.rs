@@ -1,3 +1,5 @@
+use resampler::{Quality, Resampler, Sample};
+
 use crate::{signal::Signal, EventQueue, Node, ProcContext};
 
 pub struct Resample<A>
@@ -79,3 +81,85 @@ where
         self.node.unlock();
     }
 }
+
+/// Band-limited counterpart of `Resample`.
+pub struct SincResample<A>
+where
+    A: Node,
+    A::Output: Sample,
+{
+    node: A,
+    sample_rate: u64,
+    quality: Quality,
+    resampler: Option<(u64, Resampler<A::Output>)>,
+    current_sample: u64,
+}
+
+impl<A> SincResample<A>
+where
+    A: Node,
+    A::Output: Sample,
+{
+    pub fn new(node: A, sample_rate: u64, quality: Quality) -> Self {
+        SincResample {
+            node,
+            sample_rate,
+            quality,
+            resampler: None,
+            current_sample: 0,
+        }
+    }
+
+    /// Delay in seconds.
+    pub fn delay(&self) -> f64 {
+        self.resampler
+            .as_ref()
+            .map(|(_, r)| r.delay() / self.sample_rate as f64)
+            .unwrap_or_default()
+    }
+}
+
+impl<A> Node for SincResample<A>
+where
+    A: Node,
+    A::Output: Sample,
+{
+    type Output = A::Output;
+
+    #[inline]
+    fn proc(&mut self, ctx: &ProcContext) -> Self::Output {
+        if self.resampler.as_ref().map(|(sr, _)| *sr) != Some(ctx.sample_rate) {
+            self.resampler = Some((
+                ctx.sample_rate,
+                Resampler::new(
+                    self.sample_rate as f64,
+                    ctx.sample_rate as f64,
+                    self.quality,
+                ),
+            ));
+        }
+        let (_, resampler) = self.resampler.as_mut().unwrap();
+        let node = &mut self.node;
+        let current_sample = &mut self.current_sample;
+        let sample_rate = self.sample_rate;
+        resampler.process(|| {
+            let x = node.proc(&ProcContext {
+                sample_rate,
+                current_time: *current_sample as f64 / sample_rate as f64,
+                current_sample: *current_sample,
+                rest_proc_samples: ctx.rest_proc_samples * sample_rate / ctx.sample_rate,
+                event_queue: EventQueue::new(),
+            });
+            *current_sample += 1;
+            x
+        })
+    }
+
+    fn lock(&mut self, ctx: &ProcContext) {
+        self.node.lock(ctx);
+    }
+
+    fn unlock(&mut self) {
+        self.node.unlock();
+    }
+}
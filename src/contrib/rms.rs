@@ -44,7 +44,8 @@ where
         let squared_v = self.node.proc(ctx).map(|x| x.powi(2));
         self.acc = self.acc.clone() + squared_v.clone() + -self.buffer.get(window_size);
         self.buffer.push(squared_v);
-        (self.acc.clone() / window_size as f64).map(|x| x.sqrt())
+        // The running sum can drift slightly below zero after silence.
+        (self.acc.clone() / window_size as f64).map(|x| x.max(0.0).sqrt())
     }
 
     fn lock(&mut self, ctx: &ProcContext) {
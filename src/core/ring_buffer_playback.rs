@@ -1,38 +1,46 @@
 use std::borrow::Borrow;
 
-use crate::ring_buffer::RingBuffer;
+use crate::{
+    interpolation::{round_nearest_neighbor, Interpolation, Interpolator},
+    ring_buffer::RingBuffer,
+};
 
 use super::{Node, ProcContext};
 
 pub struct RingBufferPlayback<T, A, B>
 where
-    T: 'static + Clone + Default,
+    T: 'static + resampler::Sample,
     A: Node<Output = f64>,
     B: Borrow<RingBuffer<T>>,
 {
     node: A,
     buffer: B,
-    _t: std::marker::PhantomData<T>,
+    interpolator: Interpolator<T>,
 }
 
 impl<T, A, B> RingBufferPlayback<T, A, B>
 where
-    T: 'static + Clone + Default,
+    T: 'static + resampler::Sample,
     A: Node<Output = f64>,
     B: Borrow<RingBuffer<T>>,
 {
+    /// Rounds the delay to the nearest sample.
     pub fn new(node: A, buffer: B) -> Self {
+        Self::with_interpolation(node, buffer, Interpolation::NearestNeighbor)
+    }
+
+    pub fn with_interpolation(node: A, buffer: B, interpolation: Interpolation) -> Self {
         RingBufferPlayback {
             node,
             buffer,
-            _t: Default::default(),
+            interpolator: Interpolator::new(interpolation),
         }
     }
 }
 
 impl<T, A, B> Node for RingBufferPlayback<T, A, B>
 where
-    T: 'static + Clone + Default,
+    T: 'static + resampler::Sample,
     A: Node<Output = f64>,
     B: Borrow<RingBuffer<T>>,
 {
@@ -41,8 +49,11 @@ where
     #[inline]
     fn proc(&mut self, ctx: &ProcContext) -> T {
         let t = self.node.proc(ctx);
-        let i = (t * ctx.sample_rate as f64).round() as usize;
-        self.buffer.borrow().get(i)
+        let interpolation = self.interpolator.interpolation;
+        let delay = round_nearest_neighbor(interpolation, t * ctx.sample_rate as f64)
+            .max(interpolation.min_delay());
+        let buffer = self.buffer.borrow();
+        self.interpolator.get(|i| buffer.get_or_default(i), delay)
     }
 
     fn lock(&mut self, ctx: &ProcContext) {
@@ -53,3 +64,21 @@ where
         self.node.unlock();
     }
 }
+
+#[test]
+fn test() {
+    let mut buffer = RingBuffer::new(8);
+    for i in 0..8 {
+        buffer.push(i as f64);
+    }
+    let ctx = ProcContext::new(10);
+    // 0.26 s is 2.6 samples back, rounded to 3.
+    let mut playback = RingBufferPlayback::new(super::var::Var::new(0.26), &buffer);
+    assert_eq!(playback.proc(&ctx), 4.0);
+    let mut playback = RingBufferPlayback::with_interpolation(
+        super::var::Var::new(0.26),
+        &buffer,
+        Interpolation::Linear,
+    );
+    assert!((playback.proc(&ctx) - 4.4).abs() < 1e-9);
+}
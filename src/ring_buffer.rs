@@ -34,6 +34,16 @@ impl<T: Clone + Default> RingBuffer<T> {
         self.buffer[i].clone()
     }
 
+    /// Default outside the buffer, for reading between samples.
+    #[inline]
+    pub fn get_or_default(&self, index: isize) -> T {
+        if 0 <= index && (index as usize) < self.buffer.len() {
+            self.get(index as usize)
+        } else {
+            T::default()
+        }
+    }
+
     #[inline]
     pub fn fast_resize(&mut self, size: usize) {
         self.buffer.resize(size, Default::default());
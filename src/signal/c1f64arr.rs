@@ -90,6 +90,17 @@ impl<const N: usize> Default for C1f64Arr<N> {
     }
 }
 
+impl<const N: usize> resampler::Sample for C1f64Arr<N> {
+    #[inline]
+    fn mul_add(self, weight: f64, acc: Self) -> Self {
+        let mut arr = acc.0;
+        for i in 0..N {
+            arr[i] += self.0[i] * weight;
+        }
+        Self(arr)
+    }
+}
+
 impl<const N: usize> Signal for C1f64Arr<N> {
     type Float = f64;
 
@@ -21,6 +21,7 @@ pub trait Signal:
     + Div<f64, Output = Self>
     + Neg<Output = Self>
     + Default
+    + resampler::Sample
 {
     type Float: 'static + From<f64> + Copy;
 
@@ -95,6 +96,13 @@ impl From<[f64; 2]> for C2f64 {
     }
 }
 
+impl resampler::Sample for C2f64 {
+    #[inline]
+    fn mul_add(self, weight: f64, acc: Self) -> Self {
+        Self([acc.0[0] + self.0[0] * weight, acc.0[1] + self.0[1] * weight])
+    }
+}
+
 impl Signal for C1f64 {
     type Float = f64;
 
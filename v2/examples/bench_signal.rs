@@ -0,0 +1,131 @@
+use std::time::Instant;
+
+use corus_v2::{
+    nodes::{
+        effects::{
+            compressor::{self, Compressor},
+            dynamics::Detection,
+            parametric_eq::{Band, BandType, ParametricEq, Placement},
+        },
+        unison::{Unison, UnisonLanes},
+    },
+    signal::{float_array::FloatArray, Stereo},
+    ProcessContext,
+};
+
+const SAMPLE_RATE: f64 = 44100.0;
+const SECONDS: f64 = 10.0;
+const VOICES: usize = 8;
+
+fn saw<F: num_traits::Float>(x: F) -> F {
+    x + x - F::one()
+}
+
+fn compressor_param<F: num_traits::Float + num_traits::FromPrimitive>() -> compressor::Param<F> {
+    let c = |x| F::from_f64(x).unwrap();
+    compressor::Param {
+        threshold: c(-20.0),
+        ratio: c(4.0),
+        knee: c(6.0),
+        attack: c(0.005),
+        release: c(0.1),
+        makeup: c(0.0),
+        lookahead: c(0.0),
+        sidechain_low_cut: c(0.0),
+        detection: Detection::Rms,
+    }
+}
+
+fn eq_bands<F: num_traits::Float + num_traits::FromPrimitive>() -> Vec<Band<F>> {
+    let c = |x| F::from_f64(x).unwrap();
+    [
+        (BandType::LowShelf, 100.0),
+        (BandType::Bell, 1000.0),
+        (BandType::HighShelf, 8000.0),
+    ]
+    .into_iter()
+    .map(|(r#type, frequency)| Band {
+        enabled: true,
+        r#type,
+        placement: Placement::Stereo,
+        frequency: c(frequency),
+        gain: c(3.0),
+        q: c(0.7),
+    })
+    .collect()
+}
+
+fn run<F: Into<f64>>(name: &str, mut process: impl FnMut(&ProcessContext) -> (F, F)) {
+    let mut ctx = ProcessContext::new(SAMPLE_RATE);
+    let mut acc = 0.0;
+    let start = Instant::now();
+    for _ in 0..(SAMPLE_RATE * SECONDS) as usize {
+        let (l, r) = process(&ctx);
+        acc += l.into() + r.into();
+        ctx.next();
+    }
+    let elapsed = start.elapsed().as_secs_f64();
+    println!(
+        "{:<16} {:>8.1} ms  {:>7.1}x realtime",
+        name,
+        elapsed * 1000.0,
+        SECONDS / elapsed
+    );
+    std::hint::black_box(acc);
+}
+
+fn main() {
+    println!(
+        "{}-voice unison saw, {} s at {} Hz",
+        VOICES, SECONDS, SAMPLE_RATE
+    );
+
+    let mut unison = Unison::<f64>::new(VOICES);
+    run("f64", |ctx| {
+        let x = unison.process(ctx, 440.0, 0.02, 0.8, saw);
+        (x.get_l(), x.get_r())
+    });
+
+    let mut unison = Unison::<f32>::new(VOICES);
+    run("f32", |ctx| {
+        let x = unison.process(ctx, 440.0, 0.02, 0.8, saw);
+        (x.get_l(), x.get_r())
+    });
+
+    let mut unison = UnisonLanes::<FloatArray<VOICES, f32>>::new(VOICES);
+    run("f32 lanes", |ctx| {
+        let x = unison.process(ctx, 440.0, 0.02, 0.8, |x| x.map(saw));
+        (x.get_l(), x.get_r())
+    });
+
+    #[cfg(feature = "simd")]
+    {
+        use corus_v2::signal::simd::SimdArray;
+
+        let mut unison = UnisonLanes::<SimdArray<VOICES, f32>>::new(VOICES);
+        run("f32 simd", |ctx| {
+            let x = unison.process(ctx, 440.0, 0.02, 0.8, |x| x + x - 1.0);
+            (x.get_l(), x.get_r())
+        });
+    }
+
+    println!("3-band EQ into a compressor on the unison");
+
+    let mut unison = Unison::<f64>::new(VOICES);
+    let (mut eq, mut comp) = (ParametricEq::new(3), Compressor::<2, f64>::new(1));
+    let (bands, param) = (eq_bands(), compressor_param());
+    run("f64", |ctx| {
+        let x = unison.process(ctx, 440.0, 0.02, 0.8, saw);
+        let x = comp.process(&param, ctx, eq.process(ctx, &bands, x));
+        (x.get_l(), x.get_r())
+    });
+
+    let mut unison = Unison::<f32>::new(VOICES);
+    let (mut eq, mut comp) = (ParametricEq::new(3), Compressor::<2, f32>::new(1));
+    let (bands, param) = (eq_bands(), compressor_param());
+    run("f32", |ctx| {
+        let x = unison.process(ctx, 440.0, 0.02, 0.8, saw);
+        let x = comp.process(&param, ctx, eq.process(ctx, &bands, x));
+        (x.get_l(), x.get_r())
+    });
+}
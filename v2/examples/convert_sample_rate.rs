@@ -0,0 +1,52 @@
+use resampler::{resample_buffer, Quality};
+
+/// Usage: convert_sample_rate <input.wav> <output.wav> <sample rate>
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let [_, input, output, sample_rate] = args.as_slice() else {
+        panic!("usage: convert_sample_rate <input.wav> <output.wav> <sample rate>");
+    };
+    let sample_rate: u32 = sample_rate.parse().unwrap();
+
+    let mut reader = hound::WavReader::open(input).unwrap();
+    let spec = reader.spec();
+    let channels = spec.channels as usize;
+    let samples: Vec<f64> = match spec.sample_format {
+        hound::SampleFormat::Int => {
+            let scale = (1i64 << (spec.bits_per_sample - 1)) as f64;
+            reader
+                .samples::<i32>()
+                .map(|x| x.unwrap() as f64 / scale)
+                .collect()
+        }
+        hound::SampleFormat::Float => reader.samples::<f32>().map(|x| x.unwrap() as f64).collect(),
+    };
+
+    let converted: Vec<Vec<f64>> = (0..channels)
+        .map(|c| {
+            let channel: Vec<f64> = samples.iter().skip(c).step_by(channels).copied().collect();
+            resample_buffer(
+                &channel,
+                spec.sample_rate as f64,
+                sample_rate as f64,
+                Quality::High,
+            )
+        })
+        .collect();
+
+    let spec = hound::WavSpec {
+        channels: spec.channels,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(output, spec).unwrap();
+    for i in 0..converted[0].len() {
+        for channel in &converted {
+            writer
+                .write_sample((channel[i].clamp(-1.0, 1.0) * std::i16::MAX as f64) as i16)
+                .unwrap();
+        }
+    }
+    writer.finalize().unwrap();
+}
@@ -0,0 +1,108 @@
+use benihora::{lerp, tract::ShapeSource, BenihoraManaged};
+
+use crate::{nodes::poly_synth::NoteHandler, ProcessContext, Producer};
+
+/// A benihora voice. Notes take `(frequency, velocity)`.
+pub struct BenihoraVoice {
+    pub benihora: BenihoraManaged,
+}
+
+impl BenihoraVoice {
+    /// `seed` must stay below 2^16.
+    pub fn new(sound_speed: f64, sample_rate: f64, seed: u32) -> Self {
+        let mut benihora = BenihoraManaged::new(sound_speed, sample_rate, seed);
+        benihora.benihora.tract.reserve_constrictions(8);
+        Self { benihora }
+    }
+
+    /// Tongue position for a point of the vowel plane: `x` moves the tongue from back (0) to
+    /// front (1) and `y` from open (0) to close (1).
+    pub fn vowel_to_tongue(source: &ShapeSource, x: f64, y: f64) -> (f64, f64) {
+        let index = lerp(
+            source.blade_start as f64 + 2.0,
+            source.tip_start as f64 - 3.0,
+            x.clamp(0.0, 1.0),
+        );
+        let diameter = lerp(3.5, 2.05, y.clamp(0.0, 1.0));
+        source.tongue_clamp(index, diameter)
+    }
+
+    pub fn set_vowel(&mut self, x: f64, y: f64) {
+        let tract = &mut self.benihora.benihora.tract;
+        let tongue = Self::vowel_to_tongue(&tract.source, x, y);
+        if tract.source.tongue != tongue {
+            tract.source.tongue = tongue;
+            tract.update_diameter();
+        }
+    }
+
+    /// `(index, diameter)` pairs. Doesn't allocate for up to 8 constrictions.
+    pub fn set_constrictions(&mut self, constrictions: &[(f64, f64)]) {
+        let tract = &mut self.benihora.benihora.tract;
+        if tract.source.other_constrictions != constrictions {
+            tract.source.other_constrictions.clear();
+            tract
+                .source
+                .other_constrictions
+                .extend_from_slice(constrictions);
+            tract.update_diameter();
+        }
+    }
+
+    /// 0.01 (closed) to 0.4 (open).
+    pub fn set_velum(&mut self, velum: f64) {
+        self.benihora.benihora.tract.set_velum_target(velum);
+    }
+
+    pub fn set_tenseness(&mut self, tenseness: f64) {
+        self.benihora.set_tenseness(tenseness);
+    }
+
+    pub fn set_frequency(&mut self, frequency: f64) {
+        self.benihora.frequency.set(frequency);
+    }
+}
+
+impl Producer for BenihoraVoice {
+    type Output = f64;
+
+    fn process(&mut self, ctx: &ProcessContext) -> Self::Output {
+        self.benihora.process(ctx.current_time())
+    }
+}
+
+impl NoteHandler<(f64, f64), ()> for BenihoraVoice {
+    /// Harder notes are louder and reach it faster.
+    fn note_on(&mut self, _time: f64, (frequency, velocity): (f64, f64)) {
+        let velocity = velocity.clamp(0.0, 1.0);
+        self.benihora.frequency.set(frequency);
+        self.benihora.intensity.target = lerp(0.25, 1.0, velocity);
+        self.benihora.intensity.up_velocity = lerp(1.0, 10.0, velocity);
+        self.benihora.sound = true;
+    }
+
+    fn note_off(&mut self, _time: f64, _: ()) {
+        self.benihora.sound = false;
+    }
+}
+
+#[test]
+fn test() {
+    // A softer note settles quieter.
+    let power = |velocity| {
+        let mut voice = BenihoraVoice::new(1.0, 48000.0, 1);
+        let mut ctx = ProcessContext::new(48000.0);
+        voice.note_on(0.0, (220.0, velocity));
+        let mut power = 0.0;
+        for i in 0..48000 {
+            let x = voice.process(&ctx);
+            if 24000 <= i {
+                power += x * x;
+            }
+            ctx.next();
+        }
+        power
+    };
+    let (soft, hard) = (power(0.2), power(1.0));
+    assert!(soft * 2.0 < hard, "{} {}", soft, hard);
+}
@@ -1,5 +1,7 @@
 use std::ops::Range;
 
+use num_traits::FromPrimitive;
+
 use crate::{interpolate_get, signal::Signal};
 
 pub struct IntegratedBuffer<S: Signal> {
@@ -34,15 +36,15 @@ impl<S: Signal> IntegratedBuffer<S> {
 
     pub fn get_by_normalized_f64_with_linear_interpolation(&self, range: Range<f64>) -> S
     where
-        S: Signal<Float = f64>,
+        S::Float: FromPrimitive,
     {
         let len = self.buffer.len();
         let getter = |i| {
             self.buffer[i % len]
                 + *self.buffer.last().unwrap() * S::float_from_f64((i / len) as f64)
         };
-        (interpolate_get(range.end * len as f64, getter)
-            - interpolate_get(range.start * len as f64, getter))
+        (interpolate_get(S::float_from_f64(range.end * len as f64), getter)
+            - interpolate_get(S::float_from_f64(range.start * len as f64), getter))
             / S::float_from_f64((range.end - range.start) * len as f64)
     }
 }
@@ -1,3 +1,5 @@
+#[cfg(feature = "benihora")]
+pub mod benihora;
 pub mod integrated_buffer;
 pub mod ondemand;
 pub mod pid_controller;
@@ -11,6 +11,13 @@ impl<T> EventQueue<T> {
         }
     }
 
+    /// Pushing stays allocation-free as long as the queue holds at most `capacity` events.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            queue: VecDeque::with_capacity(capacity),
+        }
+    }
+
     pub fn push(&mut self, time: f64, event: T) {
         if let Some(index) = self.queue.iter().position(|(t, _)| *t > time) {
             self.queue.insert(index, (time, event));
@@ -1,3 +1,5 @@
+#![cfg_attr(feature = "simd", feature(portable_simd))]
+
 pub mod contrib;
 pub mod event_queue;
 pub mod nodes;
@@ -8,6 +10,7 @@ pub mod unsafe_wrapper;
 use std::collections::VecDeque;
 
 use num_traits::{FromPrimitive, ToPrimitive};
+pub use resampler::{Interpolation, Interpolator};
 use signal::Signal;
 
 #[derive(Debug, Clone)]
@@ -91,13 +94,7 @@ pub fn interpolate_get<T: Signal>(x: T::Float, getter: impl Fn(usize) -> T) -> T
 where
     T::Float: FromPrimitive + ToPrimitive,
 {
-    let x = x.to_f64().unwrap();
-    let x0 = x.floor() as usize;
-    let x1 = x0 + 1;
-    let y0 = getter(x0);
-    let y1 = getter(x1);
-    let t = x - x0 as f64;
-    y0 + (y1 - y0) * T::Float::from_f64(t).unwrap()
+    Interpolator::new(Interpolation::Linear).get(|i| getter(i as usize), x.to_f64().unwrap())
 }
 
 #[test]
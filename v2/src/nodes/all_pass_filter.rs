@@ -1,10 +1,11 @@
-use crate::{interpolate_get, ring_buffer::RingBuffer, signal::Signal, ProcessContext};
+use crate::{ring_buffer::RingBuffer, signal::Signal, Interpolation, Interpolator, ProcessContext};
 
 use num_traits::*;
 
 /// FIR all-pass filter.
 pub struct AllPassFilter<S: Signal> {
     buffer: RingBuffer<S>,
+    interpolator: Interpolator<S>,
 }
 
 impl<S: Signal> AllPassFilter<S>
@@ -14,9 +15,14 @@ where
     pub fn new(len: usize) -> Self {
         Self {
             buffer: RingBuffer::new(len),
+            interpolator: Interpolator::new(Interpolation::Linear),
         }
     }
 
+    pub fn set_interpolation(&mut self, interpolation: Interpolation) {
+        self.interpolator.interpolation = interpolation;
+    }
+
     pub fn process(
         &mut self,
         ctx: &ProcessContext,
@@ -25,10 +31,14 @@ where
         feedback: S::Float,
     ) -> S {
         let feedback = S::from(feedback);
-        let i = delay * S::Float::from_f64(ctx.sample_rate()).unwrap();
-        let d = interpolate_get(i, |i| self.buffer.get(i));
+        let d = self.buffer.read(&mut self.interpolator, ctx, delay);
         let a = x + d * feedback;
         self.buffer.push(a);
         d - a * feedback
     }
+
+    /// The line `delay` seconds back, e.g. for output taps of reverbs.
+    pub fn tap(&self, ctx: &ProcessContext, delay: f64) -> S {
+        self.buffer.tap(ctx, delay)
+    }
 }
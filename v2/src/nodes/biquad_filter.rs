@@ -1,4 +1,4 @@
-use biquad::{Biquad, Coefficients, DirectForm1, ToHertz, Type, Q_BUTTERWORTH_F64};
+use biquad::{Biquad, Coefficients, DirectForm1, ToHertz, Type};
 
 use crate::{
     signal::{Mono, Signal, Stereo},
@@ -7,48 +7,62 @@ use crate::{
 
 pub type FilterType = Type<f64>;
 
-pub struct BiquadFilter<const N: usize, S: Signal<Float = f64>> {
+pub struct BiquadFilter<const N: usize, S: Signal> {
     biquad: [DirectForm1<S::Float>; N],
 }
 
-impl<const N: usize, S: Signal<Float = f64>> BiquadFilter<N, S> {
+impl<const N: usize, S: Signal> BiquadFilter<N, S>
+where
+    S::Float: ToHertz<S::Float>,
+{
     pub fn new() -> Self {
         assert_eq!(N, S::CHANNEL);
 
-        let coeffs = Coefficients::<f64>::from_params(
+        let coeffs = Coefficients::<S::Float>::from_params(
             Type::LowPass,
-            48000.0.hz(),
-            1.0.khz(),
-            Q_BUTTERWORTH_F64,
+            S::float_from_f64(48000.0).hz(),
+            S::float_from_f64(1.0).khz(),
+            S::float_from_f64(std::f64::consts::FRAC_1_SQRT_2),
         )
         .unwrap();
         Self {
-            biquad: [DirectForm1::<f64>::new(coeffs); N],
+            biquad: [DirectForm1::<S::Float>::new(coeffs); N],
         }
     }
 
     pub fn update_coefficients(
         &mut self,
         ctx: &ProcessContext,
-        r#type: Type<f64>,
-        freq: f64,
-        q: f64,
+        r#type: Type<S::Float>,
+        freq: S::Float,
+        q: S::Float,
     ) {
-        let coeffs =
-            Coefficients::<f64>::from_params(r#type, ctx.sample_rate().hz(), freq.hz(), q).unwrap();
+        let coeffs = Coefficients::<S::Float>::from_params(
+            r#type,
+            S::float_from_f64(ctx.sample_rate()).hz(),
+            freq.hz(),
+            q,
+        )
+        .unwrap();
         for biquad in self.biquad.iter_mut() {
             biquad.update_coefficients(coeffs.clone());
         }
     }
 }
 
-impl<S: Signal<Float = f64> + Mono> BiquadFilter<1, S> {
+impl<S: Signal + Mono> BiquadFilter<1, S>
+where
+    S::Float: ToHertz<S::Float>,
+{
     pub fn process(&mut self, x: S) -> S {
         S::from(self.biquad[0].run(x.get_m()))
     }
 }
 
-impl<S: Signal<Float = f64> + Stereo> BiquadFilter<2, S> {
+impl<S: Signal + Stereo> BiquadFilter<2, S>
+where
+    S::Float: ToHertz<S::Float>,
+{
     pub fn process(&mut self, x: S) -> S {
         let l = self.biquad[0].run(x.get_l());
         let r = self.biquad[1].run(x.get_r());
@@ -1,9 +1,10 @@
-use crate::{interpolate_get, ring_buffer::RingBuffer, signal::Signal, ProcessContext};
+use crate::{ring_buffer::RingBuffer, signal::Signal, Interpolation, Interpolator, ProcessContext};
 
 use num_traits::*;
 
 pub struct CombFilter<S: Signal> {
     buffer: RingBuffer<S>,
+    interpolator: Interpolator<S>,
 }
 
 impl<S: Signal> CombFilter<S>
@@ -13,9 +14,14 @@ where
     pub fn new(len: usize) -> Self {
         Self {
             buffer: RingBuffer::new(len),
+            interpolator: Interpolator::new(Interpolation::Linear),
         }
     }
 
+    pub fn set_interpolation(&mut self, interpolation: Interpolation) {
+        self.interpolator.interpolation = interpolation;
+    }
+
     pub fn process(
         &mut self,
         ctx: &ProcessContext,
@@ -23,8 +29,7 @@ where
         delay: S::Float,
         feedback: S::Float,
     ) -> S {
-        let i = delay * S::Float::from_f64(ctx.sample_rate()).unwrap();
-        let y = interpolate_get(i, |i| self.buffer.get(i));
+        let y = self.buffer.read(&mut self.interpolator, ctx, delay);
         self.buffer.push(x + y * S::from(feedback));
         y
     }
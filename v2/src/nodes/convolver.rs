@@ -0,0 +1,246 @@
+use std::sync::Arc;
+
+use num_traits::{Float, Zero};
+use rustfft::{num_complex::Complex, Fft, FftNum, FftPlanner};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Partitioning {
+    /// Every partition is one block long.
+    Uniform,
+    /// Partitions grow 4 times at each stage up to `max_block`, so long IRs take fewer
+    /// multiplications. The larger stages spread their work over the block after their input, so
+    /// no single sample computes a whole stage.
+    NonUniform { max_block: usize },
+}
+
+/// Partitioned overlap-save FFT convolution in `F`. Delays the output by `block` samples.
+pub struct Convolver<F: FftNum> {
+    stages: Vec<Stage<F>>,
+    latency: usize,
+}
+
+impl<F: FftNum + Float> Convolver<F> {
+    pub fn new(ir: &[f64], block: usize, partitioning: Partitioning) -> Self {
+        assert!(block > 0);
+        let mut planner = FftPlanner::new();
+        let mut stages = vec![];
+        match partitioning {
+            Partitioning::Uniform => stages.push(Stage::new(ir, block, false, &mut planner)),
+            Partitioning::NonUniform { max_block } => {
+                // A spread stage of block size `b` delays by `2 * b`, so it covers the IR from
+                // `2 * b - block` to keep the total latency at `block`.
+                let mut size = block;
+                let mut start = 0;
+                loop {
+                    let next = size * 4;
+                    let next_start = next * 2 - block;
+                    let last = max_block < next || ir.len() <= next_start;
+                    let end = if last { ir.len() } else { next_start };
+                    let spread = size != block;
+                    stages.push(Stage::new(
+                        &ir[start.min(end)..end],
+                        size,
+                        spread,
+                        &mut planner,
+                    ));
+                    if last {
+                        break;
+                    }
+                    start = next_start;
+                    size = next;
+                }
+            }
+        }
+        Self {
+            stages,
+            latency: block,
+        }
+    }
+
+    pub fn latency(&self) -> usize {
+        self.latency
+    }
+
+    #[inline]
+    pub fn process(&mut self, x: F) -> F {
+        self.stages
+            .iter_mut()
+            .fold(F::zero(), |acc, stage| acc + stage.process(x))
+    }
+}
+
+struct Stage<F: FftNum> {
+    block: usize,
+    fft: Arc<dyn Fft<F>>,
+    ifft: Arc<dyn Fft<F>>,
+    /// Spectra of the IR partitions.
+    partitions: Vec<Vec<Complex<F>>>,
+    /// Spectra of the latest input blocks, the newest at `head`.
+    history: Vec<Vec<Complex<F>>>,
+    head: usize,
+    /// The previous and the current input blocks.
+    input: Vec<F>,
+    output: Vec<F>,
+    pos: usize,
+    accumulator: Vec<Complex<F>>,
+    scratch: Vec<Complex<F>>,
+    /// Whether the work on a block is spread over the next one, which delays by another block.
+    spread: bool,
+    /// Steps done of the work on the latest input block.
+    step: usize,
+}
+
+impl<F: FftNum + Float> Stage<F> {
+    fn new(ir: &[f64], block: usize, spread: bool, planner: &mut FftPlanner<F>) -> Self {
+        let fft = planner.plan_fft_forward(block * 2);
+        let ifft = planner.plan_fft_inverse(block * 2);
+        let mut scratch = vec![
+            Complex::zero();
+            fft.get_inplace_scratch_len()
+                .max(ifft.get_inplace_scratch_len())
+        ];
+        let partitions: Vec<_> = ir
+            .chunks(block)
+            .map(|chunk| {
+                let mut spectrum = vec![Complex::zero(); block * 2];
+                for (s, x) in spectrum.iter_mut().zip(chunk) {
+                    s.re = F::from_f64(*x).unwrap();
+                }
+                fft.process_with_scratch(&mut spectrum, &mut scratch);
+                spectrum
+            })
+            .collect();
+        Self {
+            block,
+            history: vec![vec![Complex::zero(); block * 2]; partitions.len()],
+            partitions,
+            fft,
+            ifft,
+            head: 0,
+            input: vec![F::zero(); block * 2],
+            output: vec![F::zero(); block],
+            pos: 0,
+            accumulator: vec![Complex::zero(); block * 2],
+            scratch,
+            spread,
+            step: usize::MAX,
+        }
+    }
+
+    #[inline]
+    fn process(&mut self, x: F) -> F {
+        if self.partitions.is_empty() {
+            return F::zero();
+        }
+        self.input[self.block + self.pos] = x;
+        let y = self.output[self.pos];
+        self.pos += 1;
+        if self.spread {
+            // The last step, which overwrites `output`, runs after its last sample is read.
+            self.work(self.steps() * self.pos / self.block);
+        }
+        if self.pos == self.block {
+            self.pos = 0;
+            self.push_input();
+            if !self.spread {
+                self.work(self.steps());
+            }
+        }
+        y
+    }
+
+    /// The forward FFT, a multiply-accumulate for each partition, and the inverse FFT.
+    fn steps(&self) -> usize {
+        self.partitions.len() + 2
+    }
+
+    fn push_input(&mut self) {
+        let len = self.partitions.len();
+        self.head = (self.head + len - 1) % len;
+        for (s, x) in self.history[self.head].iter_mut().zip(&self.input) {
+            *s = Complex::new(*x, F::zero());
+        }
+        self.input.copy_within(self.block.., 0);
+        self.step = 0;
+    }
+
+    /// Runs the steps of the work on the latest input block up to `until`.
+    fn work(&mut self, until: usize) {
+        let len = self.partitions.len();
+        while self.step < until {
+            match self.step {
+                0 => {
+                    let spectrum = &mut self.history[self.head];
+                    self.fft.process_with_scratch(spectrum, &mut self.scratch);
+                    self.accumulator.fill(Complex::zero());
+                }
+                step if step <= len => {
+                    let i = step - 1;
+                    let spectrum = &self.history[(self.head + i) % len];
+                    let partition = &self.partitions[i];
+                    for ((a, x), h) in self.accumulator.iter_mut().zip(spectrum).zip(partition) {
+                        *a = *a + x * h;
+                    }
+                }
+                _ => {
+                    self.ifft
+                        .process_with_scratch(&mut self.accumulator, &mut self.scratch);
+                    // The second half is free of the circular wrap.
+                    let scale = F::from_usize(self.block * 2).unwrap().recip();
+                    for (y, a) in self.output.iter_mut().zip(&self.accumulator[self.block..]) {
+                        *y = a.re * scale;
+                    }
+                }
+            }
+            self.step += 1;
+        }
+    }
+}
+
+#[test]
+fn test() {
+    let mut rand = crate::contrib::rand::Rand::new(1);
+    let ir: Vec<f64> = (0..3000).map(|_| rand.next_f64() - 0.5).collect();
+    let input: Vec<f64> = (0..8000).map(|_| rand.next_f64() - 0.5).collect();
+    let expected: Vec<f64> = (0..input.len())
+        .map(|n| {
+            ir.iter()
+                .enumerate()
+                .take(n + 1)
+                .map(|(j, h)| h * input[n - j])
+                .sum()
+        })
+        .collect();
+
+    for partitioning in [
+        Partitioning::Uniform,
+        Partitioning::NonUniform { max_block: 512 },
+        Partitioning::NonUniform { max_block: 8192 },
+    ] {
+        let mut convolver = Convolver::<f64>::new(&ir, 32, partitioning);
+        let latency = convolver.latency();
+        let output: Vec<f64> = input.iter().map(|x| convolver.process(*x)).collect();
+        assert!(output[..latency].iter().all(|y| *y == 0.0));
+        for (y, e) in output[latency..].iter().zip(&expected) {
+            assert!((y - e).abs() < 1e-9, "{:?}", partitioning);
+        }
+    }
+
+    // In f32.
+    let mut convolver = Convolver::<f32>::new(&ir, 32, Partitioning::NonUniform { max_block: 512 });
+    let output: Vec<f32> = input.iter().map(|x| convolver.process(*x as f32)).collect();
+    for (y, e) in output[convolver.latency()..].iter().zip(&expected) {
+        assert!((*y as f64 - e).abs() < 1e-4);
+    }
+
+    // Shorter than a block.
+    let mut convolver =
+        Convolver::<f64>::new(&[0.5, 0.25], 4, Partitioning::NonUniform { max_block: 64 });
+    let output: Vec<f64> = [1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]
+        .iter()
+        .map(|x| convolver.process(*x))
+        .collect();
+    for (y, e) in output.iter().zip([0.0, 0.0, 0.0, 0.0, 0.5, 0.25, 0.0, 0.0]) {
+        assert!((y - e).abs() < 1e-12);
+    }
+}
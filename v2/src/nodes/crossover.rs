@@ -0,0 +1,130 @@
+use num_traits::FromPrimitive;
+
+use crate::{nodes::zdf_filter::StateVariableFilter, signal::Signal, ProcessContext};
+
+/// 4th-order Linkwitz-Riley crossover: two cascaded Butterworth sections on each side.
+/// The outputs sum to an all-pass.
+pub struct LinkwitzRiley<S: Signal> {
+    low: [StateVariableFilter<S>; 2],
+    high: [StateVariableFilter<S>; 2],
+}
+
+impl<S: Signal> LinkwitzRiley<S>
+where
+    S::Float: FromPrimitive,
+{
+    pub fn new() -> Self {
+        Self {
+            low: [StateVariableFilter::new(), StateVariableFilter::new()],
+            high: [StateVariableFilter::new(), StateVariableFilter::new()],
+        }
+    }
+
+    /// Returns the low and the high bands.
+    pub fn process(&mut self, ctx: &ProcessContext, frequency: S::Float, x: S) -> (S, S) {
+        let q = S::float_from_f64(std::f64::consts::FRAC_1_SQRT_2);
+        let [l1, l2] = &mut self.low;
+        let [h1, h2] = &mut self.high;
+        let low = l1.process(ctx, frequency, q, x).low;
+        let low = l2.process(ctx, frequency, q, low).low;
+        let high = h1.process(ctx, frequency, q, x).high;
+        let high = h2.process(ctx, frequency, q, high).high;
+        (low, high)
+    }
+
+    /// The phase the crossover gives, for aligning bands split elsewhere.
+    pub fn all_pass(&mut self, ctx: &ProcessContext, frequency: S::Float, x: S) -> S {
+        let (low, high) = self.process(ctx, frequency, x);
+        low + high
+    }
+}
+
+/// Splits a signal into bands that sum flat, lowest first.
+pub struct Splitter<S: Signal> {
+    crossovers: Vec<LinkwitzRiley<S>>,
+    /// Per band, the all-passes of the crossovers above it that it did not go through.
+    all_passes: Vec<Vec<LinkwitzRiley<S>>>,
+}
+
+impl<S: Signal> Splitter<S>
+where
+    S::Float: FromPrimitive,
+{
+    pub fn new(bands: usize) -> Self {
+        assert!(bands > 0);
+        Self {
+            crossovers: (1..bands).map(|_| LinkwitzRiley::new()).collect(),
+            all_passes: (0..bands)
+                .map(|k| {
+                    (k + 1..bands.saturating_sub(1))
+                        .map(|_| LinkwitzRiley::new())
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+
+    pub fn bands(&self) -> usize {
+        self.all_passes.len()
+    }
+
+    /// `frequencies` are the ascending crossover frequencies, one fewer than the bands.
+    pub fn process(
+        &mut self,
+        ctx: &ProcessContext,
+        frequencies: &[S::Float],
+        x: S,
+        bands: &mut [S],
+    ) {
+        debug_assert_eq!(frequencies.len() + 1, self.bands());
+        let mut rest = x;
+        for ((crossover, frequency), band) in self
+            .crossovers
+            .iter_mut()
+            .zip(frequencies)
+            .zip(bands.iter_mut())
+        {
+            let (low, high) = crossover.process(ctx, *frequency, rest);
+            *band = low;
+            rest = high;
+        }
+        bands[self.crossovers.len()] = rest;
+
+        for (k, (all_passes, band)) in self.all_passes.iter_mut().zip(bands).enumerate() {
+            for (all_pass, frequency) in all_passes.iter_mut().zip(frequencies.iter().skip(k + 1)) {
+                *band = all_pass.all_pass(ctx, *frequency, *band);
+            }
+        }
+    }
+}
+
+#[test]
+fn test() {
+    let sample_rate = 48000.0;
+    let frequencies = [200.0, 1000.0, 5000.0];
+    for frequency in [50.0, 200.0, 700.0, 1000.0, 3000.0, 5000.0, 12000.0] {
+        let mut splitter = Splitter::<f64>::new(4);
+        let mut ctx = ProcessContext::new(sample_rate);
+        let mut bands = [0.0; 4];
+        // Mean squares of the bands and their sum.
+        let mut powers = [0.0; 5];
+        for i in 0..48000 {
+            let x = (i as f64 * frequency / sample_rate * std::f64::consts::TAU).sin();
+            splitter.process(&ctx, &frequencies, x, &mut bands);
+            if 24000 <= i {
+                let sum = bands.iter().sum::<f64>();
+                for (power, y) in powers.iter_mut().zip(bands.iter().chain([&sum])) {
+                    *power += y * y / 12000.0;
+                }
+            }
+            ctx.next();
+        }
+        let amps = powers.map(f64::sqrt);
+        // Flat sum; each band -6 dB at its edges.
+        assert!((amps[4] - 1.0).abs() < 0.01, "{} {:?}", frequency, amps);
+        if let Some(k) = frequencies.iter().position(|f| *f == frequency) {
+            assert!((amps[k] - 0.5).abs() < 0.02, "{} {:?}", frequency, amps);
+            assert!((amps[k + 1] - 0.5).abs() < 0.02, "{} {:?}", frequency, amps);
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{nodes::multi_tap_delay::MultiTapDelay, signal::Signal, ProcessContext};
+use crate::{nodes::multi_tap_delay::MultiTapDelay, signal::Signal, Interpolation, ProcessContext};
 use num_traits::{FromPrimitive, ToPrimitive};
 
 use super::super::sine::Sine;
@@ -22,6 +22,10 @@ where
         }
     }
 
+    pub fn set_interpolation(&mut self, interpolation: Interpolation) {
+        self.multi_tap_delay.set_interpolation(interpolation);
+    }
+
     pub fn process(&mut self, ctx: &ProcessContext, delay: S::Float, depth: S::Float, x: S) -> S {
         self.multi_tap_delay.process(
             ctx,
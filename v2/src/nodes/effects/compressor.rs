@@ -1,49 +1,119 @@
-use num_traits::{Float, FromPrimitive};
+use num_traits::{Float, FromPrimitive, ToPrimitive};
 
-use crate::{signal::float_array::FloatArray, ProcessContext};
+use super::dynamics::{compressor_gain, db_to_amp, Detection, Detector};
+use crate::{ring_buffer::RingBuffer, signal::float_array::FloatArray, ProcessContext};
 
-pub struct Compressor<F: Float> {
-    scale: F,
+pub struct Param<F: Float> {
+    pub threshold: F,         // in dB
+    pub ratio: F,             // 1.0 ~
+    pub knee: F,              // in dB
+    pub attack: F,            // in seconds
+    pub release: F,           // in seconds
+    pub makeup: F,            // in dB
+    pub lookahead: F,         // in seconds
+    pub sidechain_low_cut: F, // in Hz, 0.0: off
+    pub detection: Detection,
 }
 
-pub struct Param<F: Float> {
-    pub threshold: F,
-    pub ratio: F, // 0.0 ~ 1.0
-    pub attack: F, // in seconds
-    pub release: F, // in seconds
-    pub gain: F,
+/// Feed-forward compressor. The audio is delayed by the lookahead.
+pub struct Compressor<const N: usize, F: Float> {
+    detector: Detector<N, F>,
+    buffer: RingBuffer<FloatArray<N, F>>,
+    gain: F,
+    delay: usize,
 }
 
-impl<F: Float> Compressor<F> {
-    pub fn new() -> Self {
-        Self { scale: F::one() }
+impl<const N: usize, F: Float + FromPrimitive + ToPrimitive> Compressor<N, F> {
+    /// `len` is the longest lookahead in samples.
+    pub fn new(len: usize) -> Self {
+        Self {
+            detector: Detector::new(),
+            buffer: RingBuffer::new(len + 1),
+            gain: F::zero(),
+            delay: 0,
+        }
+    }
+
+    /// In samples, the last lookahead.
+    pub fn latency(&self) -> usize {
+        self.delay
     }
-}
 
-impl<F: Float + FromPrimitive> Compressor<F> {
-    pub fn process<const N: usize>(
+    /// The last gain reduction in dB, for metering.
+    pub fn gain_reduction(&self) -> F {
+        -self.gain
+    }
+
+    pub fn process(
         &mut self,
         param: &Param<F>,
         ctx: &ProcessContext,
         x: FloatArray<N, F>,
     ) -> FloatArray<N, F> {
-        let level = x
-            .iter()
-            .map(|x| x.abs())
-            .max_by(|a, b| a.partial_cmp(b).unwrap())
-            .unwrap();
-        let desire_scale = if param.threshold < level {
-            ((level - param.threshold) * param.ratio + param.threshold) / level
-        } else {
-            F::one()
-        };
-        if desire_scale < self.scale {
-            let attack = F::from_f64(ctx.dtime()).unwrap() / param.attack;
-            self.scale = self.scale - attack;
-        } else {
-            let release = F::from_f64(ctx.dtime()).unwrap() / param.release;
-            self.scale = self.scale + release;
-        }
-        x * self.scale * param.gain
+        self.process_sidechain(param, ctx, x, x)
+    }
+
+    /// Compresses `x` by the level of `sidechain`.
+    pub fn process_sidechain(
+        &mut self,
+        param: &Param<F>,
+        ctx: &ProcessContext,
+        x: FloatArray<N, F>,
+        sidechain: FloatArray<N, F>,
+    ) -> FloatArray<N, F> {
+        let level = self.detector.process(
+            ctx,
+            param.detection,
+            param.attack,
+            param.release,
+            param.sidechain_low_cut,
+            std::array::from_fn(|i| sidechain[i]),
+        );
+        self.gain = compressor_gain(level, param.threshold, param.ratio, param.knee);
+
+        self.buffer.push(x);
+        let delay = (param.lookahead.to_f64().unwrap() * ctx.sample_rate()).round() as usize;
+        self.delay = delay.min(self.buffer.size() - 1);
+        let x = self.buffer.get(self.delay);
+        x * db_to_amp(self.gain + param.makeup)
     }
 }
+
+#[test]
+fn test() {
+    use crate::signal::StereoF64;
+
+    let mut param = Param {
+        threshold: -20.0,
+        ratio: 4.0,
+        knee: 0.0,
+        attack: 0.001,
+        release: 0.1,
+        makeup: 0.0,
+        lookahead: 0.0,
+        sidechain_low_cut: 0.0,
+        detection: Detection::Peak,
+    };
+    let run = |param: &Param<f64>, amp: f64| {
+        let mut compressor = Compressor::<2, f64>::new(1000);
+        let mut ctx = ProcessContext::new(48000.0);
+        let mut peak: f64 = 0.0;
+        for i in 0..48000 {
+            let x = (i as f64 * 0.1).sin() * amp;
+            let y = compressor.process(param, &ctx, StereoF64::from([x, x * 0.5]));
+            if 24000 < i {
+                peak = peak.max(y[0].abs());
+            }
+            ctx.next();
+        }
+        20.0 * peak.log10()
+    };
+
+    // 20 dB over the threshold comes out 5 dB over.
+    assert!((run(&param, 1.0) + 15.0).abs() < 0.3);
+    // Below the threshold, untouched.
+    assert!((run(&param, 0.01) + 40.0).abs() < 0.01);
+    param.makeup = 6.0;
+    param.lookahead = 0.005;
+    assert!((run(&param, 1.0) + 9.0).abs() < 0.3);
+}
@@ -0,0 +1,241 @@
+use num_traits::Float;
+use rustfft::FftNum;
+
+use crate::{
+    nodes::convolver::{Convolver, Partitioning},
+    signal::Stereo,
+};
+
+#[derive(Debug, Clone)]
+pub struct ImpulseResponse {
+    pub sample_rate: f64,
+    /// 1: mono, 2: left and right, 4: true stereo in the order L→L, L→R, R→L, R→R.
+    pub channels: Vec<Vec<f64>>,
+}
+
+impl ImpulseResponse {
+    pub fn new(sample_rate: f64, channels: Vec<Vec<f64>>) -> Self {
+        assert!(matches!(channels.len(), 1 | 2 | 4));
+        Self {
+            sample_rate,
+            channels,
+        }
+    }
+
+    #[cfg(feature = "wav")]
+    pub fn from_wav(path: impl AsRef<std::path::Path>) -> Result<Self, hound::Error> {
+        let mut reader = hound::WavReader::open(path)?;
+        let spec = reader.spec();
+        let samples: Vec<f64> = match spec.sample_format {
+            hound::SampleFormat::Float => reader
+                .samples::<f32>()
+                .map(|s| s.map(|s| s as f64))
+                .collect::<Result<_, _>>()?,
+            hound::SampleFormat::Int => {
+                let scale = (1i64 << (spec.bits_per_sample - 1)) as f64;
+                reader
+                    .samples::<i32>()
+                    .map(|s| s.map(|s| s as f64 / scale))
+                    .collect::<Result<_, _>>()?
+            }
+        };
+        let n = spec.channels as usize;
+        if !matches!(n, 1 | 2 | 4) {
+            return Err(hound::Error::Unsupported);
+        }
+        let channels = (0..n)
+            .map(|c| samples.iter().skip(c).step_by(n).copied().collect())
+            .collect();
+        Ok(Self::new(spec.sample_rate as f64, channels))
+    }
+
+    pub fn len(&self) -> usize {
+        self.channels[0].len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Cuts off the leading silence and the tail quieter than `threshold_db` below the peak.
+    pub fn trim(&mut self, threshold_db: f64) {
+        let peak = self.peak();
+        if peak == 0.0 {
+            return;
+        }
+        let threshold = peak * 10.0f64.powf(threshold_db.min(0.0) / 20.0);
+        let loud = |i: usize| self.channels.iter().any(|c| c[i].abs() >= threshold);
+        let start = (0..self.len()).find(|i| loud(*i)).unwrap_or(0);
+        let end = (0..self.len()).rev().find(|i| loud(*i)).unwrap_or(0) + 1;
+        for channel in &mut self.channels {
+            channel.truncate(end);
+            channel.drain(..start);
+        }
+    }
+
+    /// Scales so that the louder output channel has unit energy.
+    pub fn normalize(&mut self) {
+        let energy = |c: &Vec<f64>| c.iter().map(|x| x * x).sum::<f64>();
+        let energies: Vec<f64> = self.channels.iter().map(energy).collect();
+        let max = match energies.len() {
+            4 => (energies[0] + energies[2]).max(energies[1] + energies[3]),
+            _ => energies.iter().copied().fold(0.0, f64::max),
+        };
+        if max == 0.0 {
+            return;
+        }
+        let gain = max.sqrt().recip();
+        for x in self.channels.iter_mut().flatten() {
+            *x *= gain;
+        }
+    }
+
+    /// Prepends `seconds` of silence.
+    pub fn pre_delay(&mut self, seconds: f64) {
+        let n = (seconds.max(0.0) * self.sample_rate).round() as usize;
+        for channel in &mut self.channels {
+            channel.splice(0..0, std::iter::repeat_n(0.0, n));
+        }
+    }
+
+    pub fn resample(&self, sample_rate: f64) -> Self {
+        Self {
+            sample_rate,
+            channels: self
+                .channels
+                .iter()
+                .map(|c| {
+                    resampler::resample_buffer(
+                        c,
+                        self.sample_rate,
+                        sample_rate,
+                        resampler::Quality::Medium,
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    fn peak(&self) -> f64 {
+        self.channels
+            .iter()
+            .flatten()
+            .fold(0.0, |a, x| a.max(x.abs()))
+    }
+}
+
+/// Convolves with an impulse response. Returns the wet signal only, `latency()` samples late.
+pub struct ConvolutionReverb<F: FftNum> {
+    convolvers: Vec<Convolver<F>>,
+}
+
+impl<F: FftNum + Float> ConvolutionReverb<F> {
+    pub fn new(
+        ir: &ImpulseResponse,
+        sample_rate: f64,
+        block: usize,
+        partitioning: Partitioning,
+    ) -> Self {
+        let resampled;
+        let ir = if ir.sample_rate == sample_rate {
+            ir
+        } else {
+            resampled = ir.resample(sample_rate);
+            &resampled
+        };
+        let channels = if ir.channels.len() == 1 {
+            vec![&ir.channels[0]; 2]
+        } else {
+            ir.channels.iter().collect()
+        };
+        Self {
+            convolvers: channels
+                .into_iter()
+                .map(|c| Convolver::new(c, block, partitioning))
+                .collect(),
+        }
+    }
+
+    pub fn latency(&self) -> usize {
+        self.convolvers[0].latency()
+    }
+
+    pub fn process<S: Stereo<Float = F>>(&mut self, x: S) -> S {
+        let (l, r) = (x.get_l(), x.get_r());
+        let (l, r) = match self.convolvers.as_mut_slice() {
+            [cl, cr] => (cl.process(l), cr.process(r)),
+            [ll, lr, rl, rr] => (ll.process(l) + rl.process(r), lr.process(l) + rr.process(r)),
+            _ => unreachable!(),
+        };
+        S::from_lr(l, r)
+    }
+}
+
+#[test]
+fn test() {
+    use crate::signal::StereoF64;
+
+    let mut ir = ImpulseResponse::new(
+        24000.0,
+        vec![
+            vec![0.0, 0.0, 1.0, 0.0, 0.5, 0.0, 0.001, 0.0],
+            vec![0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+            vec![0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+            vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0],
+        ],
+    );
+    ir.trim(-40.0);
+    assert_eq!(ir.channels[0], vec![1.0, 0.0, 0.5]);
+    assert_eq!(ir.channels[3], vec![0.0, 1.0, 0.0]);
+    ir.normalize();
+    assert!((ir.channels[0].iter().map(|x| x * x).sum::<f64>() - 1.0).abs() < 1e-12);
+    ir.pre_delay(2.0 / 24000.0);
+    assert_eq!(ir.len(), 5);
+
+    // True stereo: the left input only reaches the left output, the right one a sample later.
+    let mut reverb = ConvolutionReverb::<f64>::new(&ir, 24000.0, 4, Partitioning::Uniform);
+    let latency = reverb.latency();
+    let output: Vec<StereoF64> = (0..16)
+        .map(|i| {
+            let x = if i == 0 { 1.0 } else { 0.0 };
+            reverb.process(StereoF64::from_lr(x, x))
+        })
+        .collect();
+    let close = |a: f64, b: f64| (a - b).abs() < 1e-12;
+    assert!(close(output[latency + 2].get_l(), ir.channels[0][2]));
+    assert!(close(output[latency + 2].get_r(), 0.0));
+    assert!(close(output[latency + 3].get_r(), ir.channels[3][3]));
+    assert!(0.0 < ir.channels[3][3]);
+
+    // Resampled to the processing rate, and the mono channel on both sides.
+    let mono = ImpulseResponse::new(
+        24000.0,
+        vec![(0..300)
+            .map(|i| (i as f64 * 0.1).sin() * 0.99f64.powi(i))
+            .collect()],
+    );
+    let resampled = mono.resample(48000.0);
+    let mut reverb = ConvolutionReverb::<f64>::new(
+        &mono,
+        48000.0,
+        16,
+        Partitioning::NonUniform { max_block: 256 },
+    );
+    let latency = reverb.latency();
+    let input: Vec<f64> = (0..2000)
+        .map(|i| ((i * 7919) % 13) as f64 / 13.0 - 0.5)
+        .collect();
+    for (n, x) in input.iter().enumerate() {
+        let y = reverb.process(StereoF64::from_lr(*x, *x));
+        let Some(n) = n.checked_sub(latency) else {
+            continue;
+        };
+        let expected: f64 = resampled.channels[0]
+            .iter()
+            .take(n + 1)
+            .enumerate()
+            .map(|(j, h)| h * input[n - j])
+            .sum();
+        assert!(close(y.get_l(), expected) && close(y.get_r(), expected));
+    }
+}
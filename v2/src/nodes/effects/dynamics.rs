@@ -0,0 +1,155 @@
+use num_traits::{Float, FromPrimitive};
+
+use crate::ProcessContext;
+
+/// Below this, levels are treated as silence.
+const FLOOR_DB: f64 = -200.0;
+
+#[inline]
+fn c<F: FromPrimitive>(x: f64) -> F {
+    F::from_f64(x).unwrap()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Detection {
+    Peak,
+    Rms,
+}
+
+/// Level detector shared by the dynamics processors. Links the channels by their maximum.
+pub struct Detector<const N: usize, F: Float> {
+    /// Previous input and output of the sidechain high-pass of each channel.
+    low_cut: [(F, F); N],
+    /// Squared for `Detection::Rms`.
+    envelope: F,
+}
+
+impl<const N: usize, F: Float + FromPrimitive> Detector<N, F> {
+    pub fn new() -> Self {
+        Self {
+            low_cut: [(F::zero(), F::zero()); N],
+            envelope: F::zero(),
+        }
+    }
+
+    /// Returns the level in dB. `attack` and `release` are time constants in seconds;
+    /// `low_cut` filters the sidechain in Hz, 0.0 for none.
+    pub fn process(
+        &mut self,
+        ctx: &ProcessContext,
+        detection: Detection,
+        attack: F,
+        release: F,
+        low_cut: F,
+        x: [F; N],
+    ) -> F {
+        let k = (-c::<F>(std::f64::consts::TAU * ctx.dtime()) * low_cut).exp();
+        let mut level = F::zero();
+        for (x, (prev_x, prev_y)) in x.into_iter().zip(self.low_cut.iter_mut()) {
+            let y = if F::zero() < low_cut {
+                (*prev_y + x - *prev_x) * k
+            } else {
+                x
+            };
+            *prev_x = x;
+            *prev_y = y;
+            level = level.max(y.abs());
+        }
+
+        let target = match detection {
+            Detection::Peak => level,
+            Detection::Rms => level * level,
+        };
+        let time = if self.envelope < target {
+            attack
+        } else {
+            release
+        };
+        self.envelope = target + (self.envelope - target) * coefficient(ctx, time);
+        match detection {
+            Detection::Peak => amp_to_db(self.envelope),
+            Detection::Rms => amp_to_db(self.envelope.sqrt()),
+        }
+    }
+}
+
+/// One-pole coefficient for a time constant in seconds.
+pub fn coefficient<F: Float + FromPrimitive>(ctx: &ProcessContext, time: F) -> F {
+    if F::zero() < time {
+        (-c::<F>(ctx.dtime()) / time).exp()
+    } else {
+        F::zero()
+    }
+}
+
+pub fn amp_to_db<F: Float + FromPrimitive>(x: F) -> F {
+    (c::<F>(20.0) * x.log10()).max(c(FLOOR_DB))
+}
+
+pub fn db_to_amp<F: Float + FromPrimitive>(x: F) -> F {
+    c::<F>(10.0).powf(x / c(20.0))
+}
+
+/// Gain in dB of a compressor for `level` in dB, with a quadratic knee `knee` dB wide.
+pub fn compressor_gain<F: Float + FromPrimitive>(level: F, threshold: F, ratio: F, knee: F) -> F {
+    let half = knee * c(0.5);
+    let over = level - threshold;
+    let slope = ratio.max(F::one()).recip() - F::one();
+    if knee <= F::zero() || half < over.abs() {
+        slope * over.max(F::zero())
+    } else {
+        slope * (over + half).powi(2) / (knee + knee)
+    }
+}
+
+/// Gain in dB of a downward expander for `level` in dB. A large `ratio` makes it a gate.
+pub fn expander_gain<F: Float + FromPrimitive>(level: F, threshold: F, ratio: F, knee: F) -> F {
+    let half = knee * c(0.5);
+    let under = level - threshold;
+    let slope = ratio.max(F::one()) - F::one();
+    if knee <= F::zero() || half < under.abs() {
+        slope * under.min(F::zero())
+    } else {
+        -slope * (under - half).powi(2) / (knee + knee)
+    }
+}
+
+#[test]
+fn test() {
+    // The knee meets the straight lines at its edges.
+    for knee in [0.0, 6.0] {
+        let gain = |level| compressor_gain(level, -20.0, 4.0, knee);
+        assert_eq!(gain(-40.0), 0.0);
+        assert!((gain(-20.0 - knee / 2.0)).abs() < 1e-12);
+        assert!((gain(-20.0 + knee / 2.0) + knee / 2.0 * 0.75).abs() < 1e-12);
+        assert!((gain(0.0) + 15.0).abs() < 1e-12);
+        let gain = |level| expander_gain(level, -20.0, 3.0, knee);
+        assert_eq!(gain(0.0), 0.0);
+        assert!((gain(-20.0 - knee / 2.0) + knee).abs() < 1e-12);
+        assert!((gain(-30.0) + 20.0).abs() < 1e-12);
+    }
+
+    // Peak and RMS of a full-scale sine settle at 0 dB and -3 dB.
+    let mut ctx = ProcessContext::new(48000.0);
+    let mut peak = Detector::<1, f64>::new();
+    let mut rms = Detector::<1, f64>::new();
+    let (mut p, mut r) = (0.0, 0.0);
+    for i in 0..48000 {
+        let x = (i as f64 * 0.1).sin();
+        p = peak.process(&ctx, Detection::Peak, 0.0, 1.0, 0.0, [x]);
+        r = rms.process(&ctx, Detection::Rms, 0.1, 0.1, 0.0, [x]);
+        ctx.next();
+    }
+    assert!(p.abs() < 0.05, "{}", p);
+    assert!((r + 3.01).abs() < 0.2, "{}", r);
+
+    // The sidechain high-pass ignores DC, also in f32.
+    let mut detector = Detector::<2, f32>::new();
+    let mut p: f32 = 0.0;
+    for _ in 0..48000 {
+        p = detector.process(&ctx, Detection::Peak, 0.001, 0.01, 100.0, [1.0, -1.0]);
+        ctx.next();
+    }
+    assert!(p < -60.0);
+}
@@ -0,0 +1,84 @@
+use num_traits::{Float, FromPrimitive};
+
+use super::dynamics::{db_to_amp, expander_gain, Detection, Detector};
+use crate::{signal::float_array::FloatArray, ProcessContext};
+
+pub struct Param<F: Float> {
+    pub threshold: F,         // in dB
+    pub ratio: F,             // 1.0 ~, large values gate
+    pub knee: F,              // in dB
+    pub range: F,             // the deepest attenuation in dB
+    pub attack: F,            // in seconds
+    pub release: F,           // in seconds
+    pub sidechain_low_cut: F, // in Hz, 0.0: off
+    pub detection: Detection,
+}
+
+/// Downward expander and gate.
+pub struct Expander<const N: usize, F: Float> {
+    detector: Detector<N, F>,
+}
+
+impl<const N: usize, F: Float + FromPrimitive> Expander<N, F> {
+    pub fn new() -> Self {
+        Self {
+            detector: Detector::new(),
+        }
+    }
+
+    pub fn process(
+        &mut self,
+        param: &Param<F>,
+        ctx: &ProcessContext,
+        x: FloatArray<N, F>,
+    ) -> FloatArray<N, F> {
+        self.process_sidechain(param, ctx, x, x)
+    }
+
+    /// Expands `x` by the level of `sidechain`.
+    pub fn process_sidechain(
+        &mut self,
+        param: &Param<F>,
+        ctx: &ProcessContext,
+        x: FloatArray<N, F>,
+        sidechain: FloatArray<N, F>,
+    ) -> FloatArray<N, F> {
+        let level = self.detector.process(
+            ctx,
+            param.detection,
+            param.attack,
+            param.release,
+            param.sidechain_low_cut,
+            std::array::from_fn(|i| sidechain[i]),
+        );
+        let gain = expander_gain(level, param.threshold, param.ratio, param.knee).max(-param.range);
+        x * db_to_amp(gain)
+    }
+}
+
+#[test]
+fn test() {
+    let param = Param {
+        threshold: -40.0,
+        ratio: 100.0,
+        knee: 0.0,
+        range: 60.0,
+        attack: 0.001,
+        release: 0.01,
+        sidechain_low_cut: 0.0,
+        detection: Detection::Rms,
+    };
+    let mut gate = Expander::<1, f64>::new();
+    let mut ctx = ProcessContext::new(48000.0);
+    let mut y = FloatArray::from([0.0]);
+    for (amp, gain) in [(0.1, 1.0), (0.001, 0.001), (0.1, 1.0)] {
+        for i in 0..4800 {
+            y = gate.process(&param, &ctx, FloatArray::from([amp]));
+            if i == 4799 {
+                assert!((y[0] / amp - gain).abs() < 1e-3, "{}", y[0]);
+            }
+            ctx.next();
+        }
+    }
+    assert_eq!(y[0], 0.1);
+}
@@ -0,0 +1,201 @@
+use std::f64::consts::TAU;
+
+use num_traits::{Float, FromPrimitive, ToPrimitive, Zero};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    nodes::sine::Sine,
+    ring_buffer::{len_for_delay, RingBuffer},
+    signal::{Mono, Signal, Stereo},
+    Interpolation, Interpolator, ProcessContext,
+};
+
+const N: usize = 8;
+/// Line lengths in seconds at size 1, mutually prime in samples at the usual rates.
+const DELAYS: [f64; N] = [
+    0.0297, 0.0371, 0.0411, 0.0437, 0.0503, 0.0571, 0.0617, 0.0683,
+];
+const LFO_FREQUENCIES: [f64; N] = [0.31, 0.37, 0.43, 0.53, 0.59, 0.67, 0.73, 0.83];
+/// Excursion of the lines in seconds at modulation 1.
+const MODULATION_DEPTH: f64 = 0.0005;
+/// Largest `Param::size`.
+pub const MAX_SIZE: f64 = 2.0;
+
+/// Feedback matrix mixing the lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Matrix {
+    /// Every line feeds every other with equal magnitude.
+    Hadamard,
+    /// Lines mostly feed back to themselves; sparser early echoes.
+    Householder,
+}
+
+pub struct Param<F: Float> {
+    pub pre_delay: F,  // in seconds
+    pub size: F,       // scales the line lengths, 0.5 ~ 2.0
+    pub rt60_low: F,   // in seconds, below the crossover
+    pub rt60_high: F,  // in seconds, above the crossover
+    pub crossover: F,  // in Hz
+    pub modulation: F, // 0.0 ~ 1.0
+    pub matrix: Matrix,
+}
+
+/// Feedback delay network reverb. The left input feeds the even lines, the right the odd ones.
+/// Returns the wet signal only.
+pub struct FdnReverb<S: Stereo>
+where
+    S::Float: FromPrimitive,
+{
+    pre_delay: RingBuffer<S>,
+    pre_delay_interpolator: Interpolator<S>,
+    lines: [Line<S::Mono>; N],
+    lfos: [Sine<S::Float>; N],
+}
+
+struct Line<M: Signal> {
+    buffer: RingBuffer<M>,
+    interpolator: Interpolator<M>,
+    /// Low band of the line output.
+    low: M,
+}
+
+impl<S: Stereo> FdnReverb<S>
+where
+    S::Float: FromPrimitive,
+{
+    /// Allocates for `pre_delay` up to `max_pre_delay` seconds and `size` up to `MAX_SIZE`.
+    pub fn new(sample_rate: f64, max_pre_delay: f64) -> Self {
+        Self {
+            pre_delay: RingBuffer::new(len_for_delay(max_pre_delay, sample_rate)),
+            pre_delay_interpolator: Interpolator::new(Interpolation::Linear),
+            lines: DELAYS.map(|delay| Line {
+                buffer: RingBuffer::new(len_for_delay(
+                    delay * MAX_SIZE + MODULATION_DEPTH,
+                    sample_rate,
+                )),
+                interpolator: Interpolator::new(Interpolation::Lagrange3),
+                low: S::Mono::zero(),
+            }),
+            lfos: std::array::from_fn(|_| Sine::new()),
+        }
+    }
+
+    pub fn process(&mut self, param: &Param<S::Float>, ctx: &ProcessContext, x: S) -> S {
+        let f = S::float_from_f64;
+        self.pre_delay.push(x);
+        let x = self
+            .pre_delay
+            .read(&mut self.pre_delay_interpolator, ctx, param.pre_delay);
+
+        let size = param.size.to_f64().unwrap();
+        let rt60_low = param.rt60_low.to_f64().unwrap().max(1e-3);
+        let rt60_high = param.rt60_high.to_f64().unwrap().max(1e-3);
+        let modulation = param.modulation.to_f64().unwrap().clamp(0.0, 1.0);
+        let k = 1.0 - (-TAU * param.crossover.to_f64().unwrap() * ctx.dtime()).exp();
+
+        let mut ys = [S::Mono::zero(); N];
+        for (i, (line, lfo)) in self.lines.iter_mut().zip(&mut self.lfos).enumerate() {
+            let lfo = lfo.process(ctx, f(LFO_FREQUENCIES[i])).to_f64().unwrap();
+            let delay = DELAYS[i] * size + MODULATION_DEPTH * modulation * lfo;
+            let y = line.buffer.read(&mut line.interpolator, ctx, f(delay));
+            // Gains that take each band down by 60 dB in its RT60.
+            let gain_low = 10.0f64.powf(-3.0 * delay / rt60_low);
+            let gain_high = 10.0f64.powf(-3.0 * delay / rt60_high);
+            line.low = line.low + (y - line.low) * f(k);
+            ys[i] = y * f(gain_high) + line.low * f(gain_low - gain_high);
+        }
+
+        let mut feedback = ys;
+        match param.matrix {
+            Matrix::Hadamard => hadamard(&mut feedback),
+            Matrix::Householder => householder(&mut feedback),
+        }
+        let input = [S::Mono::from_m(x.get_l()), S::Mono::from_m(x.get_r())];
+        for (i, line) in self.lines.iter_mut().enumerate() {
+            line.buffer.push(feedback[i] + input[i % 2]);
+        }
+
+        let gain = f((2.0 / N as f64).sqrt());
+        let l = ys.iter().step_by(2).fold(S::Mono::zero(), |a, y| a + *y);
+        let r = ys
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .fold(S::Mono::zero(), |a, y| a + *y);
+        S::from_lr(l.get_m() * gain, r.get_m() * gain)
+    }
+}
+
+/// Orthonormal, by the fast Walsh-Hadamard transform.
+fn hadamard<M: Signal>(x: &mut [M; N]) {
+    let mut h = 1;
+    while h < N {
+        for i in (0..N).step_by(h * 2) {
+            for j in i..i + h {
+                let (a, b) = (x[j], x[j + h]);
+                x[j] = a + b;
+                x[j + h] = a - b;
+            }
+        }
+        h *= 2;
+    }
+    let scale = M::float_from_f64((N as f64).sqrt().recip());
+    for x in x.iter_mut() {
+        *x = *x * scale;
+    }
+}
+
+/// `I - 2/N * 1 1^T`
+fn householder<M: Signal>(x: &mut [M; N]) {
+    let sum = x.iter().fold(M::zero(), |a, x| a + *x) * M::float_from_f64(2.0 / N as f64);
+    for x in x.iter_mut() {
+        *x = *x - sum;
+    }
+}
+
+#[test]
+fn test() {
+    use crate::signal::StereoF64;
+
+    let sample_rate = 48000.0;
+    for matrix in [Matrix::Hadamard, Matrix::Householder] {
+        let param = Param {
+            pre_delay: 0.01,
+            size: 1.0,
+            rt60_low: 1.0,
+            rt60_high: 1.0,
+            crossover: 2000.0,
+            modulation: 0.5,
+            matrix,
+        };
+        let mut reverb = FdnReverb::<StereoF64>::new(sample_rate, param.pre_delay);
+        let mut ctx = ProcessContext::new(sample_rate);
+        let mut energy = vec![];
+        for i in 0..(sample_rate * 1.5) as usize {
+            let x = if i == 0 { 1.0 } else { 0.0 };
+            let y = reverb.process(&param, &ctx, StereoF64::from_lr(x, x));
+            energy.push(y.get_l().powi(2) + y.get_r().powi(2));
+            ctx.next();
+        }
+        let window = |t: f64| {
+            let i = (t * sample_rate) as usize;
+            energy[i..i + 4800].iter().sum::<f64>()
+        };
+        // Nothing before the pre-delay and the shortest line, then 60 dB down after the RT60.
+        assert!(energy[..1800].iter().all(|e| *e == 0.0));
+        let decay = 10.0 * (window(1.1) / window(0.1)).log10();
+        assert!((-66.0..-54.0).contains(&decay), "{:?} {}", matrix, decay);
+    }
+
+    // Orthonormal matrices keep the energy.
+    let mut x = [1.0, 2.0, -3.0, 0.5, 0.0, 4.0, -1.0, 2.5];
+    let norm = |x: &[f64; N]| x.iter().map(|x| x * x).sum::<f64>();
+    let before = norm(&x);
+    hadamard(&mut x);
+    assert!((norm(&x) - before).abs() < 1e-9);
+    householder(&mut x);
+    assert!((norm(&x) - before).abs() < 1e-9);
+}
@@ -0,0 +1,157 @@
+use num_traits::{Float, FromPrimitive};
+use resampler::Sample;
+
+use super::dynamics::{amp_to_db, coefficient, db_to_amp};
+use crate::{
+    ring_buffer::RingBuffer, signal::float_array::FloatArray, Interpolation, Interpolator,
+    ProcessContext,
+};
+
+/// Inter-sample positions checked between two samples.
+const OVERSAMPLING: usize = 4;
+/// Delay of the true-peak estimate, the latency of `Interpolation::Lagrange5`.
+const TRUE_PEAK_DELAY: usize = 2;
+
+pub struct Param<F: Float> {
+    pub ceiling: F, // in dB
+    pub release: F, // in seconds
+}
+
+/// Brickwall limiter on true peaks. The gain reaches its minimum over the lookahead
+/// before a peak, so nothing passes the ceiling.
+pub struct Limiter<const N: usize, F: Float + Sample> {
+    lookahead: usize,
+    history: [RingBuffer<F>; N],
+    interpolator: Interpolator<F>,
+    /// Gains each peak needs.
+    targets: RingBuffer<F>,
+    /// The held gains with the release applied, averaged into the output gain.
+    held: RingBuffer<F>,
+    release: F,
+    gain: F,
+    buffer: RingBuffer<FloatArray<N, F>>,
+}
+
+impl<const N: usize, F: Float + FromPrimitive + Sample> Limiter<N, F> {
+    /// `lookahead` in samples.
+    pub fn new(lookahead: usize) -> Self {
+        let lookahead = lookahead.max(1);
+        let mut targets = RingBuffer::new(lookahead);
+        let mut held = RingBuffer::new(lookahead);
+        for _ in 0..lookahead {
+            targets.push(F::one());
+            held.push(F::one());
+        }
+        Self {
+            lookahead,
+            history: std::array::from_fn(|_| RingBuffer::new(8)),
+            interpolator: Interpolator::new(Interpolation::Lagrange5),
+            targets,
+            held,
+            release: F::one(),
+            gain: F::one(),
+            buffer: RingBuffer::new(lookahead + TRUE_PEAK_DELAY),
+        }
+    }
+
+    /// In samples.
+    pub fn latency(&self) -> usize {
+        self.lookahead + TRUE_PEAK_DELAY - 1
+    }
+
+    /// The last gain reduction in dB, for metering.
+    pub fn gain_reduction(&self) -> F {
+        -amp_to_db(self.gain)
+    }
+
+    pub fn process(
+        &mut self,
+        param: &Param<F>,
+        ctx: &ProcessContext,
+        x: FloatArray<N, F>,
+    ) -> FloatArray<N, F> {
+        let ceiling = db_to_amp(param.ceiling);
+        let mut peak = F::zero();
+        for (i, history) in self.history.iter_mut().enumerate() {
+            history.push(x[i]);
+            for k in 0..OVERSAMPLING {
+                let t = TRUE_PEAK_DELAY as f64 + k as f64 / OVERSAMPLING as f64;
+                let y = self.interpolator.get(|i| history.get_or_default(i), t);
+                peak = peak.max(y.abs());
+            }
+        }
+        self.targets.push((ceiling / peak).min(F::one()));
+
+        let held = (0..self.lookahead)
+            .map(|i| self.targets.get(i))
+            .fold(F::one(), F::min);
+        self.release = if held < self.release {
+            held
+        } else {
+            held + (self.release - held) * coefficient(ctx, param.release)
+        };
+        self.held.push(self.release);
+        self.gain = (0..self.lookahead).fold(F::zero(), |acc, i| acc + self.held.get(i))
+            / F::from_usize(self.lookahead).unwrap();
+
+        self.buffer.push(x);
+        (self.buffer.get(self.latency()) * self.gain).map(|x| x.max(-ceiling).min(ceiling))
+    }
+}
+
+#[test]
+fn test() {
+    use crate::signal::StereoF64;
+
+    let param = Param {
+        ceiling: -1.0,
+        release: 0.005,
+    };
+    let ceiling = db_to_amp(-1.0);
+    let mut limiter = Limiter::<2, f64>::new(48);
+    let latency = limiter.latency();
+    let mut ctx = ProcessContext::new(48000.0);
+    let mut rand = crate::contrib::rand::Rand::new(1);
+    let input: Vec<f64> = (0..48000)
+        .map(|i| {
+            let burst = if i % 12000 < 3000 { 4.0 } else { 0.1 };
+            (rand.next_f64() - 0.5) * burst
+        })
+        .collect();
+    // Also the gain applied before the output is clamped to the ceiling.
+    let (output, gains): (Vec<StereoF64>, Vec<f64>) = input
+        .iter()
+        .map(|x| {
+            let y = limiter.process(&param, &ctx, StereoF64::from([*x, -*x]));
+            ctx.next();
+            (y, db_to_amp(-limiter.gain_reduction()))
+        })
+        .unzip();
+    let unclamped: Vec<f64> = input
+        .iter()
+        .zip(&gains[latency..])
+        .map(|(x, gain)| x * gain)
+        .collect();
+
+    // Nothing passes the ceiling or gets louder, even between samples.
+    let mut interpolator = Interpolator::new(Interpolation::Lagrange5);
+    for i in 0..unclamped.len() {
+        assert!(unclamped[i].abs() <= input[i].abs() + 1e-12);
+        for k in 0..OVERSAMPLING {
+            let t = TRUE_PEAK_DELAY as f64 + k as f64 / OVERSAMPLING as f64;
+            let get = |j: isize| {
+                usize::try_from(i as isize - j)
+                    .ok()
+                    .and_then(|n| unclamped.get(n).copied())
+                    .unwrap_or_default()
+            };
+            let y = interpolator.get(get, t);
+            assert!(y.abs() < ceiling * 1.01, "{} {}", i, y);
+        }
+        assert!(output[i + latency][0].abs() <= ceiling);
+    }
+    // Quiet parts pass untouched once released.
+    for i in 11000..11900 {
+        assert!((output[i + latency][0] - input[i]).abs() < 1e-9);
+    }
+}
@@ -1,12 +1,21 @@
 pub mod chorus;
 pub mod compressor;
+pub mod convolution_reverb;
+pub mod dynamics;
+pub mod expander;
+pub mod fdn_reverb;
+pub mod limiter;
+pub mod multiband;
+pub mod parametric_eq;
 pub mod phaser;
+pub mod plate_reverb;
+pub mod stereo_delay;
+pub mod transient_shaper;
 
 use crate::{
-    interpolate_get,
     ring_buffer::RingBuffer,
     signal::{IntoStereo, Signal, Stereo},
-    ProcessContext,
+    Interpolation, Interpolator, ProcessContext,
 };
 
 use num_traits::*;
@@ -18,6 +27,7 @@ use super::{
 
 pub struct DelayFx<S: Signal> {
     buffer: RingBuffer<S>,
+    interpolator: Interpolator<S>,
     filter: LowPassFilter<S>,
 }
 
@@ -28,10 +38,15 @@ where
     pub fn new(len: usize) -> Self {
         Self {
             buffer: RingBuffer::new(len),
+            interpolator: Interpolator::new(Interpolation::Linear),
             filter: LowPassFilter::new(),
         }
     }
 
+    pub fn set_interpolation(&mut self, interpolation: Interpolation) {
+        self.interpolator.interpolation = interpolation;
+    }
+
     pub fn process(
         &mut self,
         ctx: &ProcessContext,
@@ -40,8 +55,7 @@ where
         feedback: S::Float,
         low_pass: S::Float,
     ) -> S {
-        let i = delay * S::Float::from_f64(ctx.sample_rate()).unwrap();
-        let d = interpolate_get(i, |i| self.buffer.get(i)) * feedback;
+        let d = self.buffer.read(&mut self.interpolator, ctx, delay) * feedback;
         let y = x + self.filter.process(ctx, low_pass, d);
         self.buffer.push(y);
         y
@@ -0,0 +1,85 @@
+use num_traits::FromPrimitive;
+
+use crate::{
+    nodes::crossover::Splitter,
+    signal::{Signal, Stereo},
+    ProcessContext,
+};
+
+/// Runs an effect on each band of a Linkwitz-Riley split and sums the bands back.
+pub struct Multiband<S: Signal, T> {
+    splitter: Splitter<S>,
+    bands: Vec<T>,
+    buffer: Vec<S>,
+}
+
+impl<S: Signal, T> Multiband<S, T>
+where
+    S::Float: FromPrimitive,
+{
+    /// One effect state per band, lowest first.
+    pub fn new(bands: Vec<T>) -> Self {
+        Self {
+            splitter: Splitter::new(bands.len()),
+            buffer: vec![S::default(); bands.len()],
+            bands,
+        }
+    }
+
+    pub fn bands(&self) -> &[T] {
+        &self.bands
+    }
+
+    pub fn bands_mut(&mut self) -> &mut [T] {
+        &mut self.bands
+    }
+
+    /// `frequencies` are the ascending crossover frequencies. `f` takes the band index,
+    /// its state and its signal.
+    pub fn process(
+        &mut self,
+        ctx: &ProcessContext,
+        frequencies: &[S::Float],
+        x: S,
+        mut f: impl FnMut(usize, &mut T, S) -> S,
+    ) -> S {
+        self.splitter.process(ctx, frequencies, x, &mut self.buffer);
+        self.bands
+            .iter_mut()
+            .zip(&self.buffer)
+            .enumerate()
+            .fold(S::default(), |acc, (i, (band, x))| acc + f(i, band, *x))
+    }
+}
+
+/// Scales the side signal: 0.0 is mono, 1.0 as is.
+pub fn stereo_width<S: Stereo>(x: S, width: S::Float) -> S {
+    let half = S::float_from_f64(0.5);
+    let mid = (x.get_l() + x.get_r()) * half;
+    let side = (x.get_l() - x.get_r()) * half * width;
+    S::from_lr(mid + side, mid - side)
+}
+
+#[test]
+fn test() {
+    use crate::signal::StereoF64;
+
+    // Narrows the lows only.
+    let mut multiband = Multiband::<StereoF64, f64>::new(vec![0.0, 1.0]);
+    let mut ctx = ProcessContext::new(48000.0);
+    let mut side = [0.0; 2];
+    for (frequency, side) in [100.0, 5000.0].into_iter().zip(&mut side) {
+        for i in 0..48000 {
+            let x = (i as f64 * frequency / 48000.0 * std::f64::consts::TAU).sin();
+            let y = multiband.process(&ctx, &[1000.0], StereoF64::from_lr(x, -x), |_, width, x| {
+                stereo_width(x, *width)
+            });
+            if 24000 <= i {
+                *side = f64::max(*side, (y.get_l() - y.get_r()).abs());
+            }
+            ctx.next();
+        }
+    }
+    assert!(side[0] < 0.05, "{:?}", side);
+    assert!((side[1] - 2.0).abs() < 0.05, "{:?}", side);
+}
@@ -0,0 +1,335 @@
+use biquad::{Biquad, Coefficients, DirectForm2Transposed, ToHertz, Type};
+use biquad_filter::Response;
+use num_traits::{Float, FromPrimitive, ToPrimitive};
+
+use super::dynamics::amp_to_db;
+use crate::{signal::Stereo, ProcessContext};
+
+/// Most biquad sections a band uses, for a 48 dB/oct slope.
+const MAX_SECTIONS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Slope {
+    Db12,
+    Db24,
+    Db36,
+    Db48,
+}
+
+impl Slope {
+    pub const ALL: [Slope; 4] = [Slope::Db12, Slope::Db24, Slope::Db36, Slope::Db48];
+
+    pub fn sections(&self) -> usize {
+        match self {
+            Slope::Db12 => 1,
+            Slope::Db24 => 2,
+            Slope::Db36 => 3,
+            Slope::Db48 => 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BandType {
+    Bell,
+    LowShelf,
+    HighShelf,
+    LowPass(Slope),
+    HighPass(Slope),
+    Notch,
+}
+
+impl BandType {
+    fn sections(&self) -> usize {
+        match self {
+            BandType::LowPass(slope) | BandType::HighPass(slope) => slope.sections(),
+            _ => 1,
+        }
+    }
+}
+
+/// The channels a band filters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Placement {
+    Stereo,
+    Mid,
+    Side,
+}
+
+pub struct Band<F: Float> {
+    pub enabled: bool,
+    pub r#type: BandType,
+    pub placement: Placement,
+    pub frequency: F, // in Hz
+    pub gain: F,      // in dB, for bells and shelves
+    pub q: F,
+}
+
+/// A pass-through section.
+const IDENTITY: Coefficients<f64> = Coefficients {
+    a1: 0.0,
+    a2: 0.0,
+    b0: 1.0,
+    b1: 0.0,
+    b2: 0.0,
+};
+
+/// Sections of a band, the unused ones `IDENTITY`. Steeper passes cascade Butterworth
+/// sections, the first scaled by `q` for resonance.
+fn band_coefficients(
+    r#type: BandType,
+    sample_rate: f64,
+    frequency: f64,
+    gain: f64,
+    q: f64,
+) -> [Coefficients<f64>; MAX_SECTIONS] {
+    let n = r#type.sections();
+    let filter_type = match r#type {
+        BandType::Bell => Type::PeakingEQ(gain),
+        BandType::LowShelf => Type::LowShelf(gain),
+        BandType::HighShelf => Type::HighShelf(gain),
+        BandType::LowPass(_) => Type::LowPass,
+        BandType::HighPass(_) => Type::HighPass,
+        BandType::Notch => Type::Notch,
+    };
+    let frequency = frequency.clamp(1.0, sample_rate * 0.49);
+    std::array::from_fn(|k| {
+        if n <= k {
+            return IDENTITY;
+        }
+        let q = if n == 1 {
+            q
+        } else {
+            let butterworth =
+                0.5 / ((2 * k + 1) as f64 * std::f64::consts::PI / (4 * n) as f64).cos();
+            if k == 0 {
+                butterworth * q * std::f64::consts::SQRT_2
+            } else {
+                butterworth
+            }
+        };
+        Coefficients::from_params(filter_type, sample_rate.hz(), frequency.hz(), q.max(0.01))
+            .unwrap()
+    })
+}
+
+fn cast<F: FromPrimitive>(c: Coefficients<f64>) -> Coefficients<F> {
+    let f = |x| F::from_f64(x).unwrap();
+    Coefficients {
+        a1: f(c.a1),
+        a2: f(c.a2),
+        b0: f(c.b0),
+        b1: f(c.b1),
+        b2: f(c.b2),
+    }
+}
+
+/// As `biquad_filter::Response` takes them, `[a0, a1, a2, b0, b1, b2]`.
+fn params(c: &Coefficients<f64>) -> [f64; 6] {
+    [1.0, c.a1, c.a2, c.b0, c.b1, c.b2]
+}
+
+/// Combined response of the enabled bands in dB, for drawing the curve.
+pub fn response<F: Float + ToPrimitive>(
+    bands: &[Band<F>],
+    sample_rate: f64,
+    frequency: f64,
+) -> f64 {
+    let to = |x: F| x.to_f64().unwrap();
+    let sections: Vec<[f64; 6]> = bands
+        .iter()
+        .filter(|band| band.enabled)
+        .flat_map(|band| {
+            band_coefficients(
+                band.r#type,
+                sample_rate,
+                to(band.frequency),
+                to(band.gain),
+                to(band.q),
+            )
+        })
+        .map(|c| params(&c))
+        .collect();
+    amp_to_db(sections.magnitude(frequency / sample_rate))
+}
+
+struct BandState<F: Float> {
+    r#type: BandType,
+    /// The frequency, the gain and the q the coefficients were made for.
+    current: Option<[F; 3]>,
+    /// Per section, the mid and the side filters.
+    filters: [[DirectForm2Transposed<F>; 2]; MAX_SECTIONS],
+}
+
+/// Parametric EQ processed in mid/side. The band parameters are expected to be smoothed by
+/// the caller. The coefficients are designed in f64 and run in `F`.
+pub struct ParametricEq<F: Float> {
+    bands: Vec<BandState<F>>,
+}
+
+impl<F: Float + FromPrimitive + ToPrimitive> ParametricEq<F> {
+    pub fn new(bands: usize) -> Self {
+        Self {
+            bands: (0..bands)
+                .map(|_| BandState {
+                    r#type: BandType::Bell,
+                    current: None,
+                    filters: [[DirectForm2Transposed::new(cast(IDENTITY)); 2]; MAX_SECTIONS],
+                })
+                .collect(),
+        }
+    }
+
+    /// `bands` are as many as given to `new` at most.
+    pub fn process<S: Stereo<Float = F>>(
+        &mut self,
+        ctx: &ProcessContext,
+        bands: &[Band<F>],
+        x: S,
+    ) -> S {
+        let to = |x: F| x.to_f64().unwrap();
+        let half = S::float_from_f64(0.5);
+        let mut mid = (x.get_l() + x.get_r()) * half;
+        let mut side = (x.get_l() - x.get_r()) * half;
+        for (band, state) in bands.iter().zip(self.bands.iter_mut()) {
+            if !band.enabled {
+                state.current = None;
+                continue;
+            }
+            let target = [band.frequency, band.gain, band.q];
+            if state.r#type != band.r#type {
+                state.r#type = band.r#type;
+                state.current = None;
+            }
+            if state.current.is_none() {
+                // Starts clean once enabled, since there is nothing to click from.
+                state
+                    .filters
+                    .iter_mut()
+                    .flatten()
+                    .for_each(|f| f.reset_state());
+            }
+            if state.current != Some(target) {
+                state.current = Some(target);
+                let coefficients = band_coefficients(
+                    band.r#type,
+                    ctx.sample_rate(),
+                    to(band.frequency),
+                    to(band.gain),
+                    to(band.q),
+                );
+                for (filters, coefficients) in state.filters.iter_mut().zip(coefficients) {
+                    filters
+                        .iter_mut()
+                        .for_each(|f| f.update_coefficients(cast(coefficients)));
+                }
+            }
+
+            for filters in state.filters.iter_mut().take(band.r#type.sections()) {
+                if band.placement != Placement::Side {
+                    mid = filters[0].run(mid);
+                }
+                if band.placement != Placement::Mid {
+                    side = filters[1].run(side);
+                }
+            }
+        }
+        S::from_lr(mid + side, mid - side)
+    }
+}
+
+#[test]
+fn test() {
+    use crate::signal::StereoF64;
+
+    let sample_rate = 48000.0;
+    let band = |r#type, placement, frequency, gain| Band {
+        enabled: true,
+        r#type,
+        placement,
+        frequency,
+        gain,
+        q: std::f64::consts::FRAC_1_SQRT_2,
+    };
+
+    // The drawn curve.
+    let bell = [band(BandType::Bell, Placement::Stereo, 1000.0, 6.0)];
+    assert!((response(&bell, sample_rate, 1000.0) - 6.0).abs() < 1e-6);
+    assert!(response(&bell, sample_rate, 20.0).abs() < 0.1);
+    let low_pass = [band(
+        BandType::LowPass(Slope::Db48),
+        Placement::Stereo,
+        1000.0,
+        0.0,
+    )];
+    assert!((response(&low_pass, sample_rate, 1000.0) + 3.0).abs() < 0.1);
+    assert!((response(&low_pass, sample_rate, 4000.0) + 96.0).abs() < 3.0);
+
+    // Gains of a sine through the EQ, on the mid and the side.
+    let run = |bands: &[Band<f64>], frequency: f64| {
+        let mut eq = ParametricEq::new(bands.len());
+        let mut ctx = ProcessContext::new(sample_rate);
+        let (mut mid, mut side) = (0.0, 0.0);
+        for i in 0..48000 {
+            let x = (i as f64 * frequency / sample_rate * std::f64::consts::TAU).sin();
+            let y = eq.process(&ctx, bands, StereoF64::from_lr(x, 0.0));
+            if 24000 <= i {
+                mid += (y.get_l() + y.get_r()).powi(2) / 12000.0;
+                side += (y.get_l() - y.get_r()).powi(2) / 12000.0;
+            }
+            ctx.next();
+        }
+        (mid.sqrt(), side.sqrt())
+    };
+    let (mid, side) = run(&bell, 1000.0);
+    assert!((amp_to_db(mid) - 6.0).abs() < 0.01 && (amp_to_db(side) - 6.0).abs() < 0.01);
+    let (mid, side) = run(&low_pass, 4000.0);
+    assert!(amp_to_db(mid) < -90.0 && amp_to_db(side) < -90.0);
+    let (mid, side) = run(
+        &[band(BandType::Notch, Placement::Side, 1000.0, 0.0)],
+        1000.0,
+    );
+    assert!((mid - 1.0).abs() < 1e-3 && side < 1e-3, "{} {}", mid, side);
+
+    // The same bell in f32.
+    let mut eq = ParametricEq::<f32>::new(1);
+    let mut ctx = ProcessContext::new(sample_rate);
+    let bell = [Band {
+        enabled: true,
+        r#type: BandType::Bell,
+        placement: Placement::Stereo,
+        frequency: 1000.0f32,
+        gain: 6.0,
+        q: std::f32::consts::FRAC_1_SQRT_2,
+    }];
+    let mut power = 0.0;
+    for i in 0..48000 {
+        let x = (i as f64 * 1000.0 / sample_rate * std::f64::consts::TAU).sin() as f32;
+        let y = eq.process(&ctx, &bell, crate::signal::StereoF32::from_lr(x, x));
+        if 24000 <= i {
+            power += y.get_l().powi(2) as f64 / 24000.0;
+        }
+        ctx.next();
+    }
+    let gain = amp_to_db(power.sqrt() * std::f64::consts::SQRT_2);
+    assert!((gain - 6.0).abs() < 0.01, "{}", gain);
+
+    // A gain moved smoothly by the caller glides.
+    let mut eq = ParametricEq::new(1);
+    let mut ctx = ProcessContext::new(sample_rate);
+    let mut bands = [band(BandType::Bell, Placement::Stereo, 50.0, 0.0)];
+    let mut prev: f64 = 0.0;
+    for i in 0..4800 {
+        if 2400 <= i {
+            bands[0].gain = ((i - 2400) as f64 / 960.0).min(1.0) * 24.0;
+        }
+        let x = (i as f64 * 50.0 / sample_rate * std::f64::consts::TAU).sin();
+        let y = eq.process(&ctx, &bands, StereoF64::from_lr(x, x)).get_l();
+        assert!((y - prev).abs() < 0.15, "{} {}", i, y - prev);
+        prev = y;
+        ctx.next();
+    }
+}
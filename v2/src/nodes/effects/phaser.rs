@@ -1,4 +1,4 @@
-use crate::{signal::Signal, ProcessContext};
+use crate::{signal::Signal, Interpolation, ProcessContext};
 use num_traits::FromPrimitive;
 
 use super::super::{all_pass_filter::AllPassFilter, sine::Sine};
@@ -24,6 +24,12 @@ where
         }
     }
 
+    pub fn set_interpolation(&mut self, interpolation: Interpolation) {
+        for all_pass_filter in &mut self.all_pass_filters {
+            all_pass_filter.set_interpolation(interpolation);
+        }
+    }
+
     pub fn process(&mut self, ctx: &ProcessContext, x: S) -> S {
         let feedback = S::from(S::Float::from_f64(0.5).unwrap());
         let depth = S::Float::from_f64(2.0).unwrap();
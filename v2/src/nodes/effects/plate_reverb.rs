@@ -0,0 +1,230 @@
+use num_traits::{Float, FromPrimitive, ToPrimitive, Zero};
+
+use crate::{
+    nodes::{all_pass_filter::AllPassFilter, sine::Sine},
+    ring_buffer::{len_for_delay, RingBuffer},
+    signal::{Mono, Stereo},
+    Interpolation, ProcessContext,
+};
+
+/// The sample rate the lengths below are given in, after Dattorro's "Effect Design Part 1".
+const RATE: f64 = 29761.0;
+const INPUT_DIFFUSERS: [(f64, f64); 4] =
+    [(142.0, 0.75), (107.0, 0.75), (379.0, 0.625), (277.0, 0.625)];
+/// Lengths of the modulated all-pass, delay, all-pass and delay of each half of the tank.
+const TANK: [[f64; 4]; 2] = [
+    [672.0, 4453.0, 1800.0, 3720.0],
+    [908.0, 4217.0, 2656.0, 3163.0],
+];
+const DECAY_DIFFUSION: f64 = 0.7;
+/// Excursion of the modulated all-passes at modulation 1.
+const EXCURSION: f64 = 16.0;
+const LFO_FREQUENCY: f64 = 1.0;
+/// Largest `Param::size`.
+pub const MAX_SIZE: f64 = 2.0;
+
+pub struct Param<F: Float> {
+    pub pre_delay: F,  // in seconds
+    pub size: F,       // scales the lengths, 0.5 ~ 2.0
+    pub decay: F,      // 0.0 ~ 1.0
+    pub damping: F,    // 0.0 ~ 1.0
+    pub modulation: F, // 0.0 ~ 1.0
+    pub width: F,      // 0.0: mono, 1.0: as is
+    pub freeze: bool,  // recirculates the tank forever and ignores the input
+}
+
+/// Dattorro's plate reverb. Returns the wet signal only.
+pub struct PlateReverb<S: Stereo>
+where
+    S::Float: FromPrimitive,
+{
+    pre_delay: RingBuffer<S::Mono>,
+    bandwidth: S::Mono,
+    input_diffusers: [AllPassFilter<S::Mono>; 4],
+    halves: [Half<S>; 2],
+}
+
+struct Half<S: Stereo>
+where
+    S::Float: FromPrimitive,
+{
+    modulated: AllPassFilter<S::Mono>,
+    delay1: RingBuffer<S::Mono>,
+    damping: S::Mono,
+    all_pass: AllPassFilter<S::Mono>,
+    delay2: RingBuffer<S::Mono>,
+    lfo: Sine<S::Float>,
+    /// Fed to the other half.
+    out: S::Mono,
+}
+
+impl<S: Stereo> PlateReverb<S>
+where
+    S::Float: FromPrimitive,
+{
+    /// Allocates for `pre_delay` up to `max_pre_delay` seconds and `size` up to `MAX_SIZE`.
+    pub fn new(sample_rate: f64, max_pre_delay: f64) -> Self {
+        let len = |n: f64| len_for_delay(n / RATE * MAX_SIZE, sample_rate);
+        Self {
+            pre_delay: RingBuffer::new(len_for_delay(max_pre_delay, sample_rate)),
+            bandwidth: S::Mono::zero(),
+            input_diffusers: INPUT_DIFFUSERS.map(|(n, _)| AllPassFilter::new(len(n))),
+            halves: TANK.map(|lengths| {
+                let mut modulated = AllPassFilter::new(len(lengths[0] + EXCURSION));
+                modulated.set_interpolation(Interpolation::Lagrange3);
+                Half {
+                    modulated,
+                    delay1: RingBuffer::new(len(lengths[1])),
+                    damping: S::Mono::zero(),
+                    all_pass: AllPassFilter::new(len(lengths[2])),
+                    delay2: RingBuffer::new(len(lengths[3])),
+                    lfo: Sine::new(),
+                    out: S::Mono::zero(),
+                }
+            }),
+        }
+    }
+
+    pub fn process(&mut self, param: &Param<S::Float>, ctx: &ProcessContext, x: S) -> S {
+        let f = S::float_from_f64;
+        let size = param.size.to_f64().unwrap();
+        let t = |n: f64| n / RATE * size;
+        let (decay, damping) = if param.freeze {
+            (1.0, 0.0)
+        } else {
+            (
+                param.decay.to_f64().unwrap(),
+                param.damping.to_f64().unwrap(),
+            )
+        };
+
+        let x = if param.freeze {
+            S::Mono::zero()
+        } else {
+            S::Mono::from_m((x.get_l() + x.get_r()) * f(0.5))
+        };
+        self.pre_delay.push(x);
+        let x = self.pre_delay.tap(ctx, param.pre_delay.to_f64().unwrap());
+        self.bandwidth = self.bandwidth + (x - self.bandwidth) * f(0.9995);
+        let mut x = self.bandwidth;
+        for (diffuser, (n, g)) in self.input_diffusers.iter_mut().zip(INPUT_DIFFUSERS) {
+            x = diffuser.process(ctx, x, f(t(n)), f(g));
+        }
+        // What is left in the pre-delay and the diffusers is dropped too.
+        if param.freeze {
+            x = S::Mono::zero();
+        }
+
+        let decay_diffusion = (decay + 0.15).clamp(0.25, 0.5);
+        let inputs = [
+            x + self.halves[1].out * f(decay),
+            x + self.halves[0].out * f(decay),
+        ];
+        for ((half, lengths), x) in self.halves.iter_mut().zip(TANK).zip(inputs) {
+            let excursion = EXCURSION * param.modulation.to_f64().unwrap().clamp(0.0, 1.0);
+            let lfo = half.lfo.process(ctx, f(LFO_FREQUENCY)).to_f64().unwrap();
+            let y = half.modulated.process(
+                ctx,
+                x,
+                f(t(lengths[0]) + excursion / RATE * lfo),
+                f(-DECAY_DIFFUSION),
+            );
+            half.delay1.push(y);
+            let y = half.delay1.tap(ctx, t(lengths[1]));
+            half.damping = y * f(1.0 - damping) + half.damping * f(damping);
+            let y = half.all_pass.process(
+                ctx,
+                half.damping * f(decay),
+                f(t(lengths[2])),
+                f(decay_diffusion),
+            );
+            half.delay2.push(y);
+            half.out = half.delay2.tap(ctx, t(lengths[3]));
+        }
+
+        let [a, b] = &self.halves;
+        let l = output_taps(b, ctx, t, [266.0, 2974.0, 1913.0, 1996.0])
+            - output_taps(a, ctx, t, [1990.0, 0.0, 187.0, 1066.0]);
+        let r = output_taps(a, ctx, t, [353.0, 3627.0, 1228.0, 2673.0])
+            - output_taps(b, ctx, t, [2111.0, 0.0, 335.0, 121.0]);
+        let (l, r) = (l.get_m() * f(0.6), r.get_m() * f(0.6));
+        let mid = (l + r) * f(0.5);
+        let side = (l - r) * f(0.5) * param.width;
+        S::from_lr(mid + side, mid - side)
+    }
+}
+
+/// Sums the output taps of a half: two on the first delay, minus one on the all-pass,
+/// plus one on the second delay. A position of 0 is left out.
+fn output_taps<S: Stereo>(
+    half: &Half<S>,
+    ctx: &ProcessContext,
+    t: impl Fn(f64) -> f64,
+    positions: [f64; 4],
+) -> S::Mono
+where
+    S::Float: FromPrimitive,
+{
+    let tap = |buffer: &RingBuffer<S::Mono>, n: f64| {
+        if n == 0.0 {
+            S::Mono::zero()
+        } else {
+            buffer.tap(ctx, t(n))
+        }
+    };
+    tap(&half.delay1, positions[0]) + tap(&half.delay1, positions[1])
+        - half.all_pass.tap(ctx, t(positions[2]))
+        + tap(&half.delay2, positions[3])
+}
+
+#[test]
+fn test() {
+    use crate::signal::StereoF64;
+
+    let sample_rate = 48000.0;
+    let mut param = Param {
+        pre_delay: 0.0,
+        size: 1.0,
+        decay: 0.5,
+        damping: 0.0005,
+        modulation: 1.0,
+        width: 1.0,
+        freeze: false,
+    };
+    let mut reverb = PlateReverb::<StereoF64>::new(sample_rate, 0.0);
+    let mut ctx = ProcessContext::new(sample_rate);
+    let mut run = |param: &Param<f64>, len: f64, input: bool| {
+        let mut energy = 0.0;
+        let mut side = 0.0;
+        for i in 0..(sample_rate * len) as usize {
+            let x = if input && i < 1000 { 1.0 } else { 0.0 };
+            let y = reverb.process(param, &ctx, StereoF64::from_lr(x, x));
+            energy += y.get_l().powi(2) + y.get_r().powi(2);
+            side += (y.get_l() - y.get_r()).powi(2);
+            ctx.next();
+        }
+        (energy, side)
+    };
+
+    let (energy, side) = run(&param, 0.5, true);
+    assert!(0.0 < energy && 0.0 < side);
+
+    // Frozen, the tail neither decays nor takes the new input.
+    param.freeze = true;
+    let (frozen1, _) = run(&param, 0.5, false);
+    let (frozen2, _) = run(&param, 0.5, true);
+    assert!(
+        (frozen2 / frozen1 - 1.0).abs() < 0.1,
+        "{} {}",
+        frozen1,
+        frozen2
+    );
+
+    // Released, it decays.
+    param.freeze = false;
+    param.width = 0.0;
+    let (decaying, side) = run(&param, 2.0, false);
+    let (decayed, _) = run(&param, 0.5, false);
+    assert!(decayed < decaying * 1e-3);
+    assert_eq!(side, 0.0);
+}
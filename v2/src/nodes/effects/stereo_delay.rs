@@ -0,0 +1,209 @@
+use num_traits::{Float, FromPrimitive, ToPrimitive};
+
+use super::dynamics::{db_to_amp, Detection, Detector};
+use crate::{
+    nodes::{
+        first_order_filter::{HighPassFilter, LowPassFilter},
+        sine::Sine,
+    },
+    ring_buffer::RingBuffer,
+    signal::{Mono, Stereo},
+    Interpolation, Interpolator, ProcessContext,
+};
+
+const WOW_FREQUENCY: f64 = 0.7;
+const FLUTTER_FREQUENCY: f64 = 7.3;
+/// Excursions at wow and flutter 1, in seconds.
+pub const WOW_EXCURSION: f64 = 0.003;
+pub const FLUTTER_EXCURSION: f64 = 0.0003;
+/// In seconds.
+const DUCK_ATTACK: f64 = 0.005;
+
+pub struct Param<F: Float> {
+    pub time_l: F,       // in seconds
+    pub time_r: F,       // in seconds
+    pub feedback: F,     // 0.0 ~ 1.0
+    pub cross_feed: F,   // 0.0: straight, 1.0: ping-pong
+    pub low_cut: F,      // in Hz, in the feedback path
+    pub high_cut: F,     // in Hz, in the feedback path
+    pub drive: F,        // saturation in the feedback path, 0.0: clean
+    pub wow: F,          // 0.0 ~ 1.0
+    pub flutter: F,      // 0.0 ~ 1.0
+    pub duck: F,         // 0.0 ~ 1.0, how far the wet signal drops under the dry one
+    pub duck_release: F, // in seconds
+}
+
+/// Converts a note length in beats to seconds.
+pub fn beats_to_seconds(beats: f64, tempo: f64) -> f64 {
+    beats * 60.0 / tempo
+}
+
+/// Tape-style stereo delay. Returns the wet signal only.
+pub struct StereoDelay<S: Stereo>
+where
+    S::Float: FromPrimitive,
+{
+    lines: [RingBuffer<S::Mono>; 2],
+    interpolators: [Interpolator<S::Mono>; 2],
+    low_cut: HighPassFilter<S>,
+    high_cut: LowPassFilter<S>,
+    wow: Sine<S::Float>,
+    flutter: Sine<S::Float>,
+    /// Follows the peak of the dry signal.
+    ducking: Detector<2, S::Float>,
+}
+
+impl<S: Stereo> StereoDelay<S>
+where
+    S::Float: FromPrimitive + ToPrimitive,
+{
+    pub fn new(len: usize) -> Self {
+        Self {
+            lines: std::array::from_fn(|_| RingBuffer::new(len)),
+            interpolators: std::array::from_fn(|_| Interpolator::new(Interpolation::Lagrange3)),
+            low_cut: HighPassFilter::new(),
+            high_cut: LowPassFilter::new(),
+            wow: Sine::new(),
+            flutter: Sine::new(),
+            ducking: Detector::new(),
+        }
+    }
+
+    pub fn set_interpolation(&mut self, interpolation: Interpolation) {
+        for interpolator in &mut self.interpolators {
+            interpolator.interpolation = interpolation;
+        }
+    }
+
+    pub fn process(&mut self, param: &Param<S::Float>, ctx: &ProcessContext, x: S) -> S {
+        let f = S::float_from_f64;
+        let to = |x: S::Float| x.to_f64().unwrap();
+
+        // Both lines share the tape, so they wobble together.
+        let wow = to(self.wow.process(ctx, f(WOW_FREQUENCY)));
+        let flutter = to(self.flutter.process(ctx, f(FLUTTER_FREQUENCY)));
+        // The lines are read before the push, one sample later than the tap.
+        let modulation = -ctx.dtime()
+            + WOW_EXCURSION * to(param.wow) * (1.0 + wow) * 0.5
+            + FLUTTER_EXCURSION * to(param.flutter) * (1.0 + flutter) * 0.5;
+        let [l, r] = [param.time_l, param.time_r];
+        let [line_l, line_r] = &self.lines;
+        let [interpolator_l, interpolator_r] = &mut self.interpolators;
+        let y = S::from_lr(
+            line_l
+                .read(interpolator_l, ctx, f(to(l) + modulation))
+                .get_m(),
+            line_r
+                .read(interpolator_r, ctx, f(to(r) + modulation))
+                .get_m(),
+        );
+
+        let c = param.cross_feed;
+        let one = f(1.0);
+        let crossed = S::from_lr(
+            y.get_l() * (one - c) + y.get_r() * c,
+            y.get_r() * (one - c) + y.get_l() * c,
+        );
+        let dtime = ctx.dtime() * std::f64::consts::TAU;
+        let low_cut = f((-to(param.low_cut) * dtime).exp());
+        let high_cut = f(1.0 - (-to(param.high_cut) * dtime).exp());
+        let filtered =
+            self.high_cut
+                .process(ctx, high_cut, self.low_cut.process(ctx, low_cut, crossed));
+        let drive = param.drive;
+        let saturated = if drive > f(0.0) {
+            filtered.map(|x| (x * drive).tanh() / drive)
+        } else {
+            filtered
+        };
+        let fb = saturated * param.feedback;
+        self.lines[0].push(S::Mono::from_m(x.get_l() + fb.get_l()));
+        self.lines[1].push(S::Mono::from_m(x.get_r() + fb.get_r()));
+
+        let level = self.ducking.process(
+            ctx,
+            Detection::Peak,
+            f(DUCK_ATTACK),
+            param.duck_release,
+            f(0.0),
+            [x.get_l(), x.get_r()],
+        );
+        y * (one - param.duck * db_to_amp(level).min(one))
+    }
+}
+
+#[test]
+fn test() {
+    use crate::signal::StereoF64;
+
+    let sample_rate = 1000.0;
+    let mut param = Param {
+        time_l: 0.1,
+        time_r: 0.1,
+        feedback: 0.5,
+        cross_feed: 0.0,
+        low_cut: 0.0,
+        high_cut: 1e9,
+        drive: 0.0,
+        wow: 0.0,
+        flutter: 0.0,
+        duck: 0.0,
+        duck_release: 0.1,
+    };
+    let run = |param: &Param<f64>, input: &dyn Fn(usize) -> StereoF64| {
+        let mut delay = StereoDelay::<StereoF64>::new(1000);
+        let mut ctx = ProcessContext::new(sample_rate);
+        (0..500)
+            .map(|i| {
+                let y = delay.process(param, &ctx, input(i));
+                ctx.next();
+                y
+            })
+            .collect::<Vec<_>>()
+    };
+    let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+
+    // Echoes every 100 ms, halving.
+    let impulse = |i| StereoF64::from_lr(if i == 0 { 1.0 } else { 0.0 }, 0.0);
+    let y = run(&param, &impulse);
+    assert!(close(y[100].get_l(), 1.0));
+    assert!(close(y[200].get_l(), 0.5));
+    assert!(close(y[300].get_l(), 0.25));
+    assert!(y.iter().all(|y| close(y.get_r(), 0.0)));
+
+    // Ping-pong.
+    param.cross_feed = 1.0;
+    let y = run(&param, &impulse);
+    assert!(close(y[100].get_l(), 1.0) && close(y[100].get_r(), 0.0));
+    assert!(close(y[200].get_l(), 0.0) && close(y[200].get_r(), 0.5));
+    assert!(close(y[300].get_l(), 0.25) && close(y[300].get_r(), 0.0));
+
+    // Ducked while the dry signal is loud.
+    param.cross_feed = 0.0;
+    let steady = |i| StereoF64::from_lr(if i < 300 { 0.5 } else { 0.0 }, 0.0);
+    let y = run(&param, &steady);
+    param.duck = 1.0;
+    let ducked = run(&param, &steady);
+    assert!(ducked[250].get_l() < y[250].get_l() * 0.6);
+    let released = ducked[450].get_l() / y[450].get_l();
+    assert!((released - (1.0 - 0.5 * (-1.51f64).exp())).abs() < 1e-6);
+
+    // The duck follows the dry peak with the attack and release time constants: one of each
+    // into a swell from 0.1 to 1.0 and into the silence after it.
+    let swell = |i| StereoF64::from_lr(0.0, [0.1, 1.0, 0.0][i / 150 % 3]);
+    param.duck = 0.0;
+    let y = run(&param, &swell);
+    param.duck = 1.0;
+    let ducked = run(&param, &swell);
+    let gain = |i: usize| ducked[i].get_r() / y[i].get_r();
+    let e = (-1.0f64).exp();
+    assert!((gain(154) - 0.9 * e).abs() < 1e-6, "{}", gain(154));
+    assert!((gain(399) - (1.0 - e)).abs() < 1e-6, "{}", gain(399));
+
+    // Saturation bounds the feedback.
+    param.duck = 0.0;
+    param.feedback = 1.0;
+    param.drive = 2.0;
+    let y = run(&param, &steady);
+    assert!(y.iter().all(|y| y.get_l().abs() < 1.0));
+}
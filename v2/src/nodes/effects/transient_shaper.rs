@@ -0,0 +1,81 @@
+use num_traits::{Float, FromPrimitive};
+
+use super::dynamics::{db_to_amp, Detection, Detector};
+use crate::{signal::float_array::FloatArray, ProcessContext};
+
+/// The most gain either way, in dB.
+const MAX_GAIN: f64 = 24.0;
+
+pub struct Param<F: Float> {
+    pub attack: F,  // -1.0 ~ 1.0, cuts or boosts the onsets
+    pub sustain: F, // -1.0 ~ 1.0, cuts or boosts the tails
+}
+
+/// Shapes the envelope by the difference of fast and slow detectors, independent of the level.
+pub struct TransientShaper<const N: usize, F: Float> {
+    fast: Detector<N, F>,
+    slow_attack: Detector<N, F>,
+    slow_release: Detector<N, F>,
+}
+
+impl<const N: usize, F: Float + FromPrimitive> TransientShaper<N, F> {
+    pub fn new() -> Self {
+        Self {
+            fast: Detector::new(),
+            slow_attack: Detector::new(),
+            slow_release: Detector::new(),
+        }
+    }
+
+    pub fn process(
+        &mut self,
+        param: &Param<F>,
+        ctx: &ProcessContext,
+        x: FloatArray<N, F>,
+    ) -> FloatArray<N, F> {
+        let c = |x: f64| F::from_f64(x).unwrap();
+        let x = std::array::from_fn(|i| x[i]);
+        let fast = self
+            .fast
+            .process(ctx, Detection::Peak, c(0.0005), c(0.05), F::zero(), x);
+        let slow_attack =
+            self.slow_attack
+                .process(ctx, Detection::Peak, c(0.02), c(0.05), F::zero(), x);
+        let slow_release =
+            self.slow_release
+                .process(ctx, Detection::Peak, c(0.0005), c(0.3), F::zero(), x);
+        let onset = (fast - slow_attack).max(F::zero());
+        let tail = (slow_release - fast).max(F::zero());
+        let gain = (param.attack * onset + param.sustain * tail)
+            .max(c(-MAX_GAIN))
+            .min(c(MAX_GAIN));
+        FloatArray::from(x) * db_to_amp(gain)
+    }
+}
+
+#[test]
+fn test() {
+    // Energies of the onset and the tail of a decaying hit.
+    let run = |attack: f64, sustain: f64| {
+        let mut shaper = TransientShaper::<1, f64>::new();
+        let mut ctx = ProcessContext::new(48000.0);
+        let param = Param { attack, sustain };
+        let (mut onset, mut tail) = (0.0, 0.0);
+        for i in 0..24000 {
+            let x = (i as f64 * 0.1).sin() * (-(i as f64) / 4800.0).exp();
+            let y = shaper.process(&param, &ctx, FloatArray::from([x]))[0];
+            if i < 480 {
+                onset += y * y;
+            } else if 9600 <= i {
+                tail += y * y;
+            }
+            ctx.next();
+        }
+        (onset, tail)
+    };
+    let (onset, tail) = run(0.0, 0.0);
+    assert!(onset < run(1.0, 0.0).0);
+    assert!(run(-1.0, 0.0).0 < onset);
+    assert!(tail < run(0.0, 1.0).1);
+    assert!(run(0.0, -1.0).1 < tail);
+}
@@ -1,16 +1,21 @@
 pub mod all_pass_filter;
 pub mod biquad_filter;
 pub mod comb_filter;
+pub mod crossover;
+pub mod convolver;
 pub mod effects;
 pub mod envelope;
 pub mod first_order_filter;
 pub mod impulse;
 pub mod mix;
 pub mod multi_tap_delay;
+pub mod oversample;
 pub mod param;
 pub mod phase;
 pub mod poly_synth;
 pub mod sine;
+pub mod smoother;
 pub mod unison;
 pub mod voice_manager;
 pub mod voice_manager2;
+pub mod zdf_filter;
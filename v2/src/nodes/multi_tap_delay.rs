@@ -1,12 +1,18 @@
-use crate::{interpolate_get, ring_buffer::RingBuffer, signal::Signal, ProcessContext};
+use crate::{ring_buffer::RingBuffer, signal::Signal, Interpolation, Interpolator, ProcessContext};
 
 use num_traits::*;
 
+/// Taps beyond this are ignored.
+pub const MAX_TAPS: usize = 16;
+
 pub struct MultiTapDelay<S: Signal>
 where
     S::Float: FromPrimitive + ToPrimitive,
 {
     buffer: RingBuffer<S>,
+    interpolation: Interpolation,
+    /// One per tap, for the stateful interpolations.
+    interpolators: Vec<Interpolator<S>>,
 }
 
 impl<S: Signal> MultiTapDelay<S>
@@ -16,14 +22,26 @@ where
     pub fn new(len: usize) -> Self {
         Self {
             buffer: RingBuffer::new(len),
+            interpolation: Interpolation::Linear,
+            interpolators: Vec::with_capacity(MAX_TAPS),
+        }
+    }
+
+    pub fn set_interpolation(&mut self, interpolation: Interpolation) {
+        self.interpolation = interpolation;
+        for interpolator in &mut self.interpolators {
+            interpolator.interpolation = interpolation;
         }
     }
 
     pub fn process(&mut self, ctx: &ProcessContext, taps: &[(S::Float, S)], x: S) -> S {
+        let interpolation = self.interpolation;
+        let taps = &taps[..taps.len().min(MAX_TAPS)];
+        self.interpolators
+            .resize_with(taps.len(), || Interpolator::new(interpolation));
         let mut y = S::default();
-        for (delay, gain) in taps {
-            let i = *delay * S::Float::from_f64(ctx.sample_rate()).unwrap();
-            let v = interpolate_get(i, |i| self.buffer.get(i));
+        for ((delay, gain), interpolator) in taps.iter().zip(&mut self.interpolators) {
+            let v = self.buffer.read(interpolator, ctx, *delay);
             y = y + v * *gain;
         }
         self.buffer.push(x);
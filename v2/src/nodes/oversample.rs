@@ -0,0 +1,191 @@
+use num_traits::{Float, FromPrimitive};
+
+use crate::{ring_buffer::RingBuffer, signal::Signal, ProcessContext};
+
+/// Runs a per-sample process at 2x, 4x or 8x the sample rate.
+/// Each 2x stage is a pair of polyphase half-band FIR filters.
+pub struct Oversample<S: Signal> {
+    stages: Vec<Stage<S>>,
+    inner_ctx: ProcessContext,
+    latency: f64,
+}
+
+struct Stage<S: Signal> {
+    up: HalfBandUp<S>,
+    down: HalfBandDown<S>,
+}
+
+impl<S: Signal> Oversample<S>
+where
+    S::Float: FromPrimitive,
+{
+    /// `ratio` must be 1, 2, 4 or 8.
+    /// `taps` is the number of non-zero side taps per phase; 8 to 32 is reasonable.
+    pub fn new(ratio: usize, taps: usize) -> Self {
+        assert!(matches!(ratio, 1 | 2 | 4 | 8));
+        assert!(taps > 0);
+        let coefficients = half_band_coefficients::<S::Float>(taps);
+        let stages: Vec<_> = (0..ratio.trailing_zeros())
+            .map(|_| Stage {
+                up: HalfBandUp::new(coefficients.clone()),
+                down: HalfBandDown::new(coefficients.clone()),
+            })
+            .collect();
+        // Each filter delays by `2 * taps - 1` samples at its own rate.
+        let latency = (0..stages.len())
+            .map(|i| 2.0 * (2 * taps - 1) as f64 / (2 << i) as f64)
+            .sum();
+        Self {
+            stages,
+            inner_ctx: ProcessContext::new(0.0),
+            latency,
+        }
+    }
+
+    pub fn ratio(&self) -> usize {
+        1 << self.stages.len()
+    }
+
+    /// Latency in samples at the base rate.
+    pub fn latency(&self) -> f64 {
+        self.latency
+    }
+
+    /// `f` receives a context running at the oversampled rate.
+    pub fn process(
+        &mut self,
+        ctx: &ProcessContext,
+        x: S,
+        mut f: impl FnMut(&ProcessContext, S) -> S,
+    ) -> S {
+        let sample_rate = ctx.sample_rate() * self.ratio() as f64;
+        if self.inner_ctx.sample_rate() != sample_rate {
+            self.inner_ctx = ProcessContext::new(sample_rate);
+        }
+        process_stages(&mut self.stages, &mut self.inner_ctx, x, &mut f)
+    }
+}
+
+fn process_stages<S: Signal>(
+    stages: &mut [Stage<S>],
+    ctx: &mut ProcessContext,
+    x: S,
+    f: &mut impl FnMut(&ProcessContext, S) -> S,
+) -> S {
+    let Some((stage, rest)) = stages.split_first_mut() else {
+        let y = f(ctx, x);
+        ctx.next();
+        return y;
+    };
+    let [a, b] = stage.up.process(x);
+    let a = process_stages(rest, ctx, a, f);
+    let b = process_stages(rest, ctx, b, f);
+    stage.down.process(a, b)
+}
+
+/// Upsamples by 2. The output is the even phase followed by the odd phase.
+pub struct HalfBandUp<S: Signal> {
+    coefficients: Vec<S::Float>,
+    buffer: RingBuffer<S>,
+}
+
+impl<S: Signal> HalfBandUp<S> {
+    pub fn new(coefficients: Vec<S::Float>) -> Self {
+        let len = coefficients.len() * 2;
+        Self {
+            coefficients,
+            buffer: RingBuffer::new(len),
+        }
+    }
+
+    pub fn process(&mut self, x: S) -> [S; 2] {
+        self.buffer.push(x);
+        let taps = self.coefficients.len();
+        let mut even = S::default();
+        for (i, c) in self.coefficients.iter().enumerate() {
+            // Symmetric pairs around the centre tap.
+            even = even + (self.buffer.get(taps - 1 - i) + self.buffer.get(taps + i)) * *c;
+        }
+        let odd = self.buffer.get(taps - 1);
+        [even * S::float_from_f64(2.0), odd]
+    }
+}
+
+/// Downsamples by 2. Takes the even phase followed by the odd phase.
+pub struct HalfBandDown<S: Signal> {
+    coefficients: Vec<S::Float>,
+    even: RingBuffer<S>,
+    odd: RingBuffer<S>,
+}
+
+impl<S: Signal> HalfBandDown<S> {
+    pub fn new(coefficients: Vec<S::Float>) -> Self {
+        let len = coefficients.len() * 2;
+        Self {
+            even: RingBuffer::new(len),
+            odd: RingBuffer::new(coefficients.len() + 1),
+            coefficients,
+        }
+    }
+
+    pub fn process(&mut self, even: S, odd: S) -> S {
+        self.even.push(even);
+        self.odd.push(odd);
+        let taps = self.coefficients.len();
+        let mut y = S::default();
+        for (i, c) in self.coefficients.iter().enumerate() {
+            y = y + (self.even.get(taps - 1 - i) + self.even.get(taps + i)) * *c;
+        }
+        y + self.odd.get(taps) * S::float_from_f64(0.5)
+    }
+}
+
+/// Blackman-windowed half-band lowpass.
+/// Returns the non-zero side taps nearest the centre first; the centre tap is 0.5.
+pub fn half_band_coefficients<F: Float + FromPrimitive>(taps: usize) -> Vec<F> {
+    let len = 4 * taps - 1;
+    let center = (len / 2) as f64;
+    let window = |k: f64| {
+        let r = k / (len - 1) as f64;
+        0.42 - 0.5 * (std::f64::consts::TAU * r).cos()
+            + 0.08 * (2.0 * std::f64::consts::TAU * r).cos()
+    };
+    let coefficients: Vec<f64> = (0..taps)
+        .map(|i| {
+            let d = (2 * i + 1) as f64;
+            let x = std::f64::consts::PI * d / 2.0;
+            0.5 * x.sin() / x * window(center + d)
+        })
+        .collect();
+    // Normalize the DC gain of the even phase to 0.5.
+    let sum: f64 = coefficients.iter().sum::<f64>() * 2.0;
+    coefficients
+        .into_iter()
+        .map(|c| F::from_f64(c * 0.5 / sum).unwrap())
+        .collect()
+}
+
+#[test]
+fn test() {
+    let mut ctx = ProcessContext::new(48000.0);
+    let mut os = Oversample::<f64>::new(4, 16);
+    assert_eq!(os.ratio(), 4);
+
+    // DC passes with unit gain.
+    let mut y = 0.0;
+    for _ in 0..200 {
+        y = os.process(&ctx, 1.0, |_, x| x);
+        ctx.next();
+    }
+    assert!((y - 1.0).abs() < 1e-3);
+
+    // The impulse response peaks at the reported latency.
+    let mut os = Oversample::<f64>::new(2, 16);
+    let peak = (0..200)
+        .map(|i| os.process(&ctx, if i == 0 { 1.0 } else { 0.0 }, |_, x| x))
+        .enumerate()
+        .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+        .unwrap()
+        .0;
+    assert!((peak as f64 - os.latency()).abs() <= 1.0);
+}
@@ -0,0 +1,155 @@
+use num_traits::{Float, FromPrimitive};
+
+use crate::ProcessContext;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Smoothing {
+    /// Constant steps, reaching the target in `time`.
+    Linear,
+    /// Exponential approach with `time` as the time constant.
+    #[default]
+    OnePole,
+    /// Constant ratios, reaching the target in `time`. Suits frequencies and gains;
+    /// falls back to linear unless both ends are positive.
+    Log,
+}
+
+/// Glides a value toward a target that may jump, such as a parameter edited from a GUI.
+pub struct Smoother<F: Float + FromPrimitive> {
+    value: Option<F>,
+    target: F,
+    /// Increment, or ratio if `log`.
+    step: F,
+    log: bool,
+    remaining: usize,
+    /// The mode of the last `process`.
+    mode: Smoothing,
+}
+
+impl<F: Float + FromPrimitive> Smoother<F> {
+    pub fn new() -> Self {
+        Self {
+            value: None,
+            target: F::zero(),
+            step: F::zero(),
+            log: false,
+            remaining: 0,
+            mode: Smoothing::default(),
+        }
+    }
+
+    /// The current value, `None` until the first `process` or `reset`.
+    pub fn value(&self) -> Option<F> {
+        self.value
+    }
+
+    /// Jumps to `value`.
+    pub fn reset(&mut self, value: F) {
+        self.value = Some(value);
+        self.target = value;
+        self.remaining = 0;
+    }
+
+    /// Starts from the first target without gliding. `time` is in seconds.
+    pub fn process(&mut self, ctx: &ProcessContext, mode: Smoothing, time: F, target: F) -> F {
+        let value = match self.value {
+            Some(value) => value,
+            None => {
+                self.reset(target);
+                return target;
+            }
+        };
+        let samples = (time * F::from_f64(ctx.sample_rate()).unwrap()).round();
+        if samples < F::one() {
+            self.reset(target);
+            return target;
+        }
+
+        let previous_mode = std::mem::replace(&mut self.mode, mode);
+        let value = match mode {
+            Smoothing::OnePole => {
+                self.target = target;
+                self.remaining = 0;
+                value + (target - value) * (F::one() - (-samples.recip()).exp())
+            }
+            Smoothing::Linear | Smoothing::Log => {
+                // Also picks up from where another mode or a finished ramp left off.
+                if target != self.target
+                    || mode != previous_mode
+                    || (self.remaining == 0 && value != target)
+                {
+                    self.target = target;
+                    self.remaining = samples.to_usize().unwrap();
+                    self.log = mode == Smoothing::Log && value > F::zero() && target > F::zero();
+                    self.step = if self.log {
+                        (target / value).powf(samples.recip())
+                    } else {
+                        (target - value) / samples
+                    };
+                }
+                if self.remaining <= 1 {
+                    self.remaining = 0;
+                    target
+                } else {
+                    self.remaining -= 1;
+                    if self.log {
+                        value * self.step
+                    } else {
+                        value + self.step
+                    }
+                }
+            }
+        };
+        self.value = Some(value);
+        value
+    }
+}
+
+#[test]
+fn test() {
+    let mut ctx = ProcessContext::new(1000.0);
+    for mode in [Smoothing::Linear, Smoothing::OnePole, Smoothing::Log] {
+        let mut smoother = Smoother::new();
+        assert_eq!(smoother.process(&ctx, mode, 0.1, 100.0), 100.0);
+        let mut prev = 100.0;
+        for i in 0..2000 {
+            let x = smoother.process(&ctx, mode, 0.1, 1000.0);
+            assert!(prev < x && x <= 1000.0 || x == 1000.0);
+            if mode != Smoothing::OnePole && i == 99 {
+                assert_eq!(x, 1000.0);
+            }
+            prev = x;
+            ctx.next();
+        }
+        assert!((prev - 1000.0).abs() < 1e-3);
+    }
+
+    // Halfway through in ratio for `Log`.
+    let mut smoother = Smoother::new();
+    smoother.reset(100.0);
+    for _ in 0..50 {
+        smoother.process(&ctx, Smoothing::Log, 0.1, 10000.0);
+    }
+    assert!((smoother.value().unwrap() - 1000.0).abs() < 1e-6);
+
+    // Switched mid-glide, the ramp goes on from the current value.
+    for mode in [Smoothing::Linear, Smoothing::Log] {
+        let mut smoother = Smoother::new();
+        smoother.reset(100.0);
+        let mut prev = 100.0;
+        for i in 0..200 {
+            let x = smoother.process(
+                &ctx,
+                if i < 10 { Smoothing::OnePole } else { mode },
+                0.1,
+                1000.0,
+            );
+            assert!(x - prev < 100.0, "{:?} {} {}", mode, i, x);
+            if i == 109 {
+                assert_eq!(x, 1000.0);
+            }
+            prev = x;
+        }
+    }
+}
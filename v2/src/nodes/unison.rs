@@ -1,15 +1,17 @@
+use num_traits::{Float, FromPrimitive, ToPrimitive, Zero};
+
 use crate::{
-    signal::{IntoStereo, Signal, StereoF64},
+    signal::{float_array::FloatArray, IntoStereo, Lanes, Signal},
     ProcessContext,
 };
 
 use super::phase::Phase;
 
-pub struct Unison {
-    pub phases: Vec<Phase<f64>>,
+pub struct Unison<F: Float + FromPrimitive> {
+    pub phases: Vec<Phase<F>>,
 }
 
-impl Unison {
+impl<F: Float + FromPrimitive + Default + Send + Sync + 'static> Unison<F> {
     pub fn new(n: usize) -> Self {
         Self {
             phases: (0..n).map(|_| Phase::new()).collect(),
@@ -22,36 +24,37 @@ impl Unison {
 
     pub fn reset(&mut self) {
         for phase in self.phases.iter_mut() {
-            phase.set(0.0);
+            phase.set(F::zero());
         }
     }
 
     pub fn process<
-        T: Signal<Float = f64> + IntoStereo<Output = StereoF64> + std::ops::Mul<f64, Output = T>,
+        T: Signal<Float = F> + IntoStereo<Output = FloatArray<2, F>> + std::ops::Mul<F, Output = T>,
     >(
         &mut self,
         ctx: &ProcessContext,
-        frequency: f64,
-        detune: f64,
-        stereo_width: f64,
-        f: impl Fn(f64) -> T,
-    ) -> StereoF64 {
+        frequency: F,
+        detune: F,
+        stereo_width: F,
+        f: impl Fn(F) -> T,
+    ) -> FloatArray<2, F> {
         let n = self.phases.len();
-        let scale = 1.0 / (n as f64).sqrt();
-        let mut x = StereoF64::default();
+        let float = |x: f64| F::from_f64(x).unwrap();
+        let scale = float((n as f64).sqrt().recip());
+        let mut x = FloatArray::default();
         for (i, phase) in self.phases.iter_mut().enumerate() {
             let detune_amount = if n == 1 {
-                0.0
+                F::zero()
             } else {
-                detune * (i as f64 / n as f64 - 0.5)
+                detune * float(i as f64 / n as f64 - 0.5)
             };
-            let frequency = frequency * (1.0 + detune_amount);
+            let frequency = frequency * (F::one() + detune_amount);
             let phase = phase.process(ctx, frequency);
             let y = f(phase) * scale;
             let pan = if n == 1 {
-                0.0
+                F::zero()
             } else {
-                (i as f64 / (n - 1) as f64 - 0.5) * 2.0 * stereo_width
+                float((i as f64 / (n - 1) as f64 - 0.5) * 2.0) * stereo_width
             };
             // TODO: dry/wet
             x = x + y.into_stereo_with_pan(pan);
@@ -60,31 +63,32 @@ impl Unison {
     }
 
     pub fn process_range<
-        T: Signal<Float = f64> + IntoStereo<Output = StereoF64> + std::ops::Mul<f64, Output = T>,
+        T: Signal<Float = F> + IntoStereo<Output = FloatArray<2, F>> + std::ops::Mul<F, Output = T>,
     >(
         &mut self,
         ctx: &ProcessContext,
-        frequency: f64,
-        detune: f64,
-        stereo_width: f64,
-        f: impl Fn(f64, f64) -> T,
-    ) -> StereoF64 {
+        frequency: F,
+        detune: F,
+        stereo_width: F,
+        f: impl Fn(F, F) -> T,
+    ) -> FloatArray<2, F> {
         let n = self.phases.len();
-        let scale = 1.0 / (n as f64).sqrt();
-        let mut x = StereoF64::default();
+        let float = |x: f64| F::from_f64(x).unwrap();
+        let scale = float((n as f64).sqrt().recip());
+        let mut x = FloatArray::default();
         for (i, phase) in self.phases.iter_mut().enumerate() {
             let detune_amount = if n == 1 {
-                0.0
+                F::zero()
             } else {
-                detune * (i as f64 / n as f64 - 0.5)
+                detune * float(i as f64 / n as f64 - 0.5)
             };
-            let frequency = frequency * (1.0 + detune_amount);
+            let frequency = frequency * (F::one() + detune_amount);
             let (phase, next_phase) = phase.process_range(ctx, frequency);
             let y = f(phase, next_phase) * scale;
             let pan = if n == 1 {
-                0.0
+                F::zero()
             } else {
-                (i as f64 / (n - 1) as f64 - 0.5) * 2.0 * stereo_width
+                float((i as f64 / (n - 1) as f64 - 0.5) * 2.0) * stereo_width
             };
             // TODO: dry/wet
             x = x + y.into_stereo_with_pan(pan);
@@ -92,3 +96,112 @@ impl Unison {
         x
     }
 }
+
+/// `Unison` with a voice per lane of `L`, up to its lane count, processed all at once.
+pub struct UnisonLanes<L: Lanes> {
+    phase: L,
+    voice_num: usize,
+    stereo_width: L::Float,
+    detune_ratios: L,
+    gain_l: L,
+    gain_r: L,
+}
+
+impl<L: Lanes> UnisonLanes<L>
+where
+    L::Float: FromPrimitive,
+{
+    pub fn new(n: usize) -> Self {
+        let mut unison = Self {
+            phase: L::default(),
+            voice_num: 0,
+            stereo_width: L::Float::zero(),
+            detune_ratios: L::default(),
+            gain_l: L::default(),
+            gain_r: L::default(),
+        };
+        unison.set_voice_num(n);
+        unison
+    }
+
+    pub fn set_voice_num(&mut self, n: usize) {
+        assert!(n <= L::CHANNEL);
+        self.voice_num = n;
+        self.detune_ratios = L::from_fn(|i| {
+            Self::float(if n <= 1 || i >= n {
+                0.0
+            } else {
+                i as f64 / n as f64 - 0.5
+            })
+        });
+        self.update_gains();
+    }
+
+    pub fn reset(&mut self) {
+        self.phase = L::default();
+    }
+
+    fn float(x: f64) -> L::Float {
+        L::Float::from_f64(x).unwrap()
+    }
+
+    /// Pans and the loudness compensation of `Unison`, zero in the unused lanes.
+    fn update_gains(&mut self) {
+        let n = self.voice_num;
+        let scale = (n as f64).sqrt().recip();
+        let stereo_width = self.stereo_width.to_f64().unwrap();
+        let gain = |i: usize| {
+            if i >= n {
+                return (0.0, 0.0);
+            }
+            let pan = if n == 1 {
+                0.0
+            } else {
+                (i as f64 / (n - 1) as f64 - 0.5) * 2.0 * stereo_width
+            };
+            let x = (pan.clamp(-1.0, 1.0) + 1.0) * 0.5 * std::f64::consts::FRAC_PI_2;
+            (x.cos() * scale, x.sin() * scale)
+        };
+        self.gain_l = L::from_fn(|i| Self::float(gain(i).0));
+        self.gain_r = L::from_fn(|i| Self::float(gain(i).1));
+    }
+
+    /// `f` maps the phases of all voices at once.
+    pub fn process(
+        &mut self,
+        ctx: &ProcessContext,
+        frequency: L::Float,
+        detune: L::Float,
+        stereo_width: L::Float,
+        f: impl Fn(L) -> L,
+    ) -> FloatArray<2, L::Float> {
+        if stereo_width != self.stereo_width {
+            self.stereo_width = stereo_width;
+            self.update_gains();
+        }
+        let dphase =
+            (self.detune_ratios * detune + L::one()) * (frequency * Self::float(ctx.dtime()));
+        let phase = self.phase;
+        self.phase = (phase + dphase).fract();
+        let y = f(phase);
+        [(y * self.gain_l).sum(), (y * self.gain_r).sum()].into()
+    }
+}
+
+#[test]
+fn test() {
+    use crate::signal::Stereo;
+
+    let mut ctx = ProcessContext::new(44100.0);
+    let mut unison = Unison::<f64>::new(7);
+    let mut lanes = UnisonLanes::<FloatArray<8, f64>>::new(7);
+    let saw = |x: f64| x * 2.0 - 1.0;
+    for i in 0..1000 {
+        let width = if i < 500 { 0.5 } else { 1.0 };
+        let x = unison.process(&ctx, 440.0, 0.02, width, saw);
+        let y = lanes.process(&ctx, 440.0, 0.02, width, |x| x.map(saw));
+        assert!((x.get_l() - y.get_l()).abs() < 1e-9);
+        assert!((x.get_r() - y.get_r()).abs() < 1e-9);
+        ctx.next();
+    }
+}
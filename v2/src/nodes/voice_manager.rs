@@ -67,6 +67,10 @@ impl<ID: PartialEq + Default + 'static, V: 'static> VoiceManager<ID, V> {
         }
     }
 
+    pub fn iter(&self) -> impl Iterator<Item = &V> {
+        self.voices.iter().map(|v| &v.voice)
+    }
+
     pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut V> {
         self.voices.iter_mut().map(|v| &mut v.voice)
     }
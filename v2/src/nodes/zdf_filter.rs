@@ -0,0 +1,227 @@
+//! Zero-delay-feedback (topology-preserving transform) filters.
+//! They stay stable when the cutoff is modulated every sample.
+
+use num_traits::{Float, FromPrimitive, One, Zero};
+
+use crate::{signal::Signal, ProcessContext};
+
+/// Prewarped integrator gain; the cutoff is clamped below Nyquist.
+fn gain<F: Float + FromPrimitive>(ctx: &ProcessContext, frequency: F) -> F {
+    let frequency = frequency
+        .to_f64()
+        .unwrap()
+        .clamp(1.0, ctx.sample_rate() * 0.49);
+    F::from_f64((std::f64::consts::PI * frequency * ctx.dtime()).tan()).unwrap()
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SvfOutput<S: Signal> {
+    pub low: S,
+    pub band: S,
+    pub high: S,
+    pub notch: S,
+}
+
+pub struct StateVariableFilter<S: Signal> {
+    ic1eq: S,
+    ic2eq: S,
+}
+
+impl<S: Signal> StateVariableFilter<S>
+where
+    S::Float: FromPrimitive,
+{
+    pub fn new() -> Self {
+        Self {
+            ic1eq: S::default(),
+            ic2eq: S::default(),
+        }
+    }
+
+    pub fn process(
+        &mut self,
+        ctx: &ProcessContext,
+        frequency: S::Float,
+        q: S::Float,
+        x: S,
+    ) -> SvfOutput<S> {
+        let g = gain(ctx, frequency);
+        let k = q.max(S::float_from_f64(0.01)).recip();
+        let a1 = (S::Float::one() + g * (g + k)).recip();
+        let a2 = g * a1;
+        let a3 = g * a2;
+
+        let v3 = x - self.ic2eq;
+        let v1 = self.ic1eq * a1 + v3 * a2;
+        let v2 = self.ic2eq + self.ic1eq * a2 + v3 * a3;
+        self.ic1eq = v1 * S::float_from_f64(2.0) - self.ic1eq;
+        self.ic2eq = v2 * S::float_from_f64(2.0) - self.ic2eq;
+
+        let high = x - v1 * k - v2;
+        SvfOutput {
+            low: v2,
+            band: v1,
+            high,
+            notch: v2 + high,
+        }
+    }
+}
+
+/// TPT one-pole lowpass, the building block of the ladder and MS-20 filters.
+struct OnePole<S: Signal> {
+    z: S,
+}
+
+impl<S: Signal> OnePole<S> {
+    fn new() -> Self {
+        Self { z: S::default() }
+    }
+
+    /// `g` is `gain / (1 + gain)`.
+    #[inline]
+    fn low_pass(&mut self, g: S::Float, x: S) -> S {
+        let v = (x - self.z) * g;
+        let y = v + self.z;
+        self.z = y + v;
+        y
+    }
+
+    #[inline]
+    fn high_pass(&mut self, g: S::Float, x: S) -> S {
+        x - self.low_pass(g, x)
+    }
+}
+
+/// Moog-style 4-pole lowpass.
+pub struct LadderFilter<S: Signal> {
+    stages: [OnePole<S>; 4],
+}
+
+impl<S: Signal> LadderFilter<S>
+where
+    S::Float: FromPrimitive,
+{
+    pub fn new() -> Self {
+        Self {
+            stages: [
+                OnePole::new(),
+                OnePole::new(),
+                OnePole::new(),
+                OnePole::new(),
+            ],
+        }
+    }
+
+    /// `resonance` self-oscillates from 1.0. `drive` scales the input into the `tanh` saturation.
+    pub fn process(
+        &mut self,
+        ctx: &ProcessContext,
+        frequency: S::Float,
+        resonance: S::Float,
+        drive: S::Float,
+        x: S,
+    ) -> S {
+        let g = gain(ctx, frequency);
+        let one = S::Float::one();
+        let big_g = g / (one + g);
+        let k = resonance.max(S::Float::zero()) * S::float_from_f64(4.0);
+
+        // Contribution of the stage states to the output, solved without the unit delay.
+        let mut sigma = S::default();
+        let mut beta = (one + g).recip();
+        for stage in self.stages.iter().rev() {
+            sigma = sigma + stage.z * beta;
+            beta = beta * big_g;
+        }
+        let gamma = big_g * big_g * big_g * big_g;
+        let u = (x - sigma * k) * (one + k * gamma).recip();
+        let drive = drive.max(S::float_from_f64(0.01));
+        let mut y = (u * drive).tanh() * drive.recip();
+        for stage in self.stages.iter_mut() {
+            y = stage.low_pass(big_g, y);
+        }
+        y
+    }
+}
+
+/// Korg MS-20 (Korg35) style 2-pole Sallen-Key lowpass.
+pub struct Ms20Filter<S: Signal> {
+    low_pass1: OnePole<S>,
+    low_pass2: OnePole<S>,
+    high_pass: OnePole<S>,
+}
+
+impl<S: Signal> Ms20Filter<S>
+where
+    S::Float: FromPrimitive,
+{
+    pub fn new() -> Self {
+        Self {
+            low_pass1: OnePole::new(),
+            low_pass2: OnePole::new(),
+            high_pass: OnePole::new(),
+        }
+    }
+
+    /// `resonance` self-oscillates from 1.0. `drive` scales the feedback into the `tanh` saturation.
+    pub fn process(
+        &mut self,
+        ctx: &ProcessContext,
+        frequency: S::Float,
+        resonance: S::Float,
+        drive: S::Float,
+        x: S,
+    ) -> S {
+        let g = gain(ctx, frequency);
+        let one = S::Float::one();
+        let big_g = g / (one + g);
+        let k = (resonance * S::float_from_f64(2.0)).max(S::float_from_f64(0.01));
+
+        let low_pass2_beta = (k - k * big_g) / (one + g);
+        let high_pass_beta = -(one + g).recip();
+        let alpha0 = (one - k * big_g + k * big_g * big_g).recip();
+
+        let y1 = self.low_pass1.low_pass(big_g, x);
+        let s = self.high_pass.z * high_pass_beta + self.low_pass2.z * low_pass2_beta;
+        let u = (y1 + s) * alpha0;
+        let drive = drive.max(S::float_from_f64(0.01));
+        let u = (u * drive).tanh() * drive.recip();
+        let y = self.low_pass2.low_pass(big_g, u) * k;
+        self.high_pass.high_pass(big_g, y);
+        y * k.recip()
+    }
+}
+
+#[test]
+fn test() {
+    let mut ctx = ProcessContext::new(48000.0);
+    let mut svf = StateVariableFilter::<f64>::new();
+    let mut ladder = LadderFilter::<f64>::new();
+    let mut ms20 = Ms20Filter::<f64>::new();
+
+    // Cutoff swept at audio rate must not blow up.
+    for i in 0..48000 {
+        let frequency = 1000.0 + 900.0 * (i as f64 * 0.3).sin();
+        let x = if i % 100 < 50 { 1.0 } else { -1.0 };
+        let y = svf.process(&ctx, frequency * 10.0, 10.0, x);
+        assert!(y.low.abs() < 100.0 && y.high.abs() < 100.0);
+        assert!(ladder.process(&ctx, frequency, 1.1, 1.0, x).abs() < 10.0);
+        assert!(ms20.process(&ctx, frequency, 1.1, 1.0, x).abs() < 10.0);
+        ctx.next();
+    }
+
+    // DC passes through the lowpasses.
+    let mut svf = StateVariableFilter::<f64>::new();
+    let mut ladder = LadderFilter::<f64>::new();
+    let mut ms20 = Ms20Filter::<f64>::new();
+    let (mut a, mut b, mut c) = (0.0, 0.0, 0.0);
+    for _ in 0..4800 {
+        a = svf.process(&ctx, 1000.0, 0.7, 0.5).low;
+        b = ladder.process(&ctx, 1000.0, 0.0, 1.0, 0.5);
+        c = ms20.process(&ctx, 1000.0, 0.0, 1.0, 0.5);
+        ctx.next();
+    }
+    assert!((a - 0.5).abs() < 1e-6);
+    assert!((b - 0.5f64.tanh()).abs() < 1e-6);
+    assert!((c - 0.5f64.tanh()).abs() < 1e-3);
+}
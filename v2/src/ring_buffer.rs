@@ -1,3 +1,12 @@
+use num_traits::ToPrimitive;
+
+use crate::{signal::Signal, Interpolation, Interpolator, ProcessContext};
+
+/// Buffer length that can be read `max_delay` seconds back with any `Interpolation`.
+pub fn len_for_delay(max_delay: f64, sample_rate: f64) -> usize {
+    (max_delay * sample_rate).ceil() as usize + Interpolation::Sinc.min_delay() as usize + 2
+}
+
 #[derive(Clone)]
 pub struct RingBuffer<T: Clone + Default> {
     buffer: Vec<T>,
@@ -34,6 +43,22 @@ impl<T: Clone + Default> RingBuffer<T> {
         self.buffer[i].clone()
     }
 
+    /// Default outside the buffer, for reading between samples.
+    #[inline]
+    pub fn get_or_default(&self, index: isize) -> T {
+        if 0 <= index && (index as usize) < self.buffer.len() {
+            self.get(index as usize)
+        } else {
+            T::default()
+        }
+    }
+
+    /// `delay` seconds back, rounded to a sample.
+    #[inline]
+    pub fn tap(&self, ctx: &ProcessContext, delay: f64) -> T {
+        self.get_or_default((delay * ctx.sample_rate()).round() as isize)
+    }
+
     #[inline]
     pub fn fast_resize(&mut self, size: usize) {
         self.buffer.resize(size, Default::default());
@@ -41,6 +66,21 @@ impl<T: Clone + Default> RingBuffer<T> {
     }
 }
 
+impl<S: Signal> RingBuffer<S> {
+    /// Reads `delay` seconds back, no earlier than the interpolation allows.
+    #[inline]
+    pub fn read(
+        &self,
+        interpolator: &mut Interpolator<S>,
+        ctx: &ProcessContext,
+        delay: S::Float,
+    ) -> S {
+        let delay = (delay.to_f64().unwrap() * ctx.sample_rate())
+            .max(interpolator.interpolation.min_delay());
+        interpolator.get(|i| self.get_or_default(i), delay)
+    }
+}
+
 #[test]
 fn test() {
     let mut b = RingBuffer::new(4);
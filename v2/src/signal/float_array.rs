@@ -1,5 +1,8 @@
 use num_traits::{Float, One, Zero};
 
+/// Lanes of `F` in a plain array, processed lane by lane and left to the compiler to
+/// vectorize. `simd::SimdArray` is the explicitly SIMD version, behind the nightly `simd`
+/// feature.
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub struct FloatArray<const N: usize, F: Float>([F; N]);
 
@@ -2,6 +2,14 @@ use num_traits::{Float, FromPrimitive};
 
 use super::{float_array::FloatArray, Signal};
 
+impl<const N: usize, F: Float + FromPrimitive> resampler::Sample for FloatArray<N, F> {
+    #[inline]
+    fn mul_add(self, weight: f64, acc: Self) -> Self {
+        let weight = F::from_f64(weight).unwrap();
+        acc.zip_map(self, |a, x| a + x * weight)
+    }
+}
+
 impl<const N: usize, F: 'static + Default + Float + FromPrimitive + Send + Sync> Signal
     for FloatArray<N, F>
 {
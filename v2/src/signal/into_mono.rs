@@ -1,4 +1,4 @@
-use super::{Mono, Signal, Stereo, StereoF64};
+use super::{float_array::FloatArray, Mono, Signal, Stereo};
 
 pub trait IntoMono: Signal {
     type Output: Mono<Float = Self::Float>;
@@ -6,28 +6,37 @@ pub trait IntoMono: Signal {
     fn into_mono(self) -> <Self as IntoMono>::Output;
 }
 
-impl IntoMono for f64 {
-    type Output = f64;
+macro_rules! impl_into_mono {
+    ($f:ty) => {
+        impl IntoMono for $f {
+            type Output = $f;
 
-    #[inline]
-    fn into_mono(self) -> <Self as IntoMono>::Output {
-        f64::from_m(self.get_m())
-    }
-}
+            #[inline]
+            fn into_mono(self) -> <Self as IntoMono>::Output {
+                <$f>::from_m(self.get_m())
+            }
+        }
 
-impl IntoMono for StereoF64 {
-    type Output = f64;
+        impl IntoMono for FloatArray<2, $f> {
+            type Output = $f;
 
-    #[inline]
-    fn into_mono(self) -> <Self as IntoMono>::Output {
-        let l = self.get_l();
-        let r = self.get_r();
-        f64::from_m(l.powi(2).copysign(l) + r.powi(2).copysign(r))
-    }
+            #[inline]
+            fn into_mono(self) -> <Self as IntoMono>::Output {
+                let l = self.get_l();
+                let r = self.get_r();
+                <$f>::from_m(l.powi(2).copysign(l) + r.powi(2).copysign(r))
+            }
+        }
+    };
 }
 
+impl_into_mono!(f32);
+impl_into_mono!(f64);
+
 #[test]
 fn test() {
+    use super::StereoF64;
+
     assert!((f64::from(1.0).into_mono() - f64::from(1.0)).abs() < 0.000001);
     assert!((f64::from(-1.0).into_mono() - f64::from(-1.0)).abs() < 0.000001);
     assert!(
@@ -1,6 +1,4 @@
-use std::f64::consts::FRAC_PI_2;
-
-use super::{Mono, Signal, Stereo, StereoF64};
+use super::{float_array::FloatArray, Mono, Signal, Stereo};
 
 pub trait IntoStereo: Signal {
     type Output: Stereo<Float = Self::Float>;
@@ -9,51 +7,60 @@ pub trait IntoStereo: Signal {
     fn into_stereo_with_pan(self, pan: Self::Float) -> <Self as IntoStereo>::Output;
 }
 
-impl IntoStereo for f64 {
-    type Output = StereoF64;
-
-    #[inline]
-    fn into_stereo(self) -> <Self as IntoStereo>::Output {
-        StereoF64::from_lr(self.get_m(), self.get_m())
-    }
-
-    #[inline]
-    fn into_stereo_with_pan(self, pan: f64) -> <Self as IntoStereo>::Output {
-        let pan = pan.clamp(-1.0, 1.0);
-        let x = (pan + 1.0) * 0.5 * FRAC_PI_2;
-        let gain_l = x.cos();
-        let gain_r = x.sin();
-        StereoF64::from_lr(self.get_m() * gain_l, self.get_m() * gain_r)
-    }
-}
+macro_rules! impl_into_stereo {
+    ($f:ident) => {
+        impl IntoStereo for $f {
+            type Output = FloatArray<2, $f>;
+
+            #[inline]
+            fn into_stereo(self) -> <Self as IntoStereo>::Output {
+                FloatArray::from_lr(self.get_m(), self.get_m())
+            }
 
-impl IntoStereo for StereoF64 {
-    type Output = StereoF64;
-
-    #[inline]
-    fn into_stereo(self) -> <Self as IntoStereo>::Output {
-        StereoF64::from_lr(self.get_l(), self.get_r())
-    }
-
-    #[inline]
-    fn into_stereo_with_pan(self, pan: f64) -> <Self as IntoStereo>::Output {
-        let pan = pan.clamp(-1.0, 1.0);
-        if pan <= 0.0 {
-            let x = (pan + 1.0) * FRAC_PI_2;
-            let gain_l = x.cos();
-            let gain_r = x.sin();
-            StereoF64::from_lr(self.get_l() + self.get_r() * gain_l, self.get_r() * gain_r)
-        } else {
-            let x = pan * FRAC_PI_2;
-            let gain_l = x.cos();
-            let gain_r = x.sin();
-            StereoF64::from_lr(self.get_l() * gain_l, self.get_l() * gain_r + self.get_r())
+            #[inline]
+            fn into_stereo_with_pan(self, pan: $f) -> <Self as IntoStereo>::Output {
+                let pan = pan.clamp(-1.0, 1.0);
+                let x = (pan + 1.0) * 0.5 * std::$f::consts::FRAC_PI_2;
+                let gain_l = x.cos();
+                let gain_r = x.sin();
+                FloatArray::from_lr(self.get_m() * gain_l, self.get_m() * gain_r)
+            }
         }
-    }
+
+        impl IntoStereo for FloatArray<2, $f> {
+            type Output = FloatArray<2, $f>;
+
+            #[inline]
+            fn into_stereo(self) -> <Self as IntoStereo>::Output {
+                self
+            }
+
+            #[inline]
+            fn into_stereo_with_pan(self, pan: $f) -> <Self as IntoStereo>::Output {
+                let pan = pan.clamp(-1.0, 1.0);
+                if pan <= 0.0 {
+                    let x = (pan + 1.0) * std::$f::consts::FRAC_PI_2;
+                    let gain_l = x.cos();
+                    let gain_r = x.sin();
+                    FloatArray::from_lr(self.get_l() + self.get_r() * gain_l, self.get_r() * gain_r)
+                } else {
+                    let x = pan * std::$f::consts::FRAC_PI_2;
+                    let gain_l = x.cos();
+                    let gain_r = x.sin();
+                    FloatArray::from_lr(self.get_l() * gain_l, self.get_l() * gain_r + self.get_r())
+                }
+            }
+        }
+    };
 }
 
+impl_into_stereo!(f32);
+impl_into_stereo!(f64);
+
 #[test]
 fn test() {
+    use super::StereoF64;
+
     dbg!(f64::from(1.0).into_stereo_with_pan(0.0));
     dbg!(f64::from(1.0).into_stereo_with_pan(-1.0));
     dbg!(f64::from(1.0).into_stereo_with_pan(1.0));
@@ -0,0 +1,30 @@
+use num_traits::{Float, FromPrimitive};
+
+use super::{float_array::FloatArray, Signal};
+
+/// Signals whose channels are independent lanes, such as unison voices.
+pub trait Lanes: Signal {
+    fn from_fn(f: impl FnMut(usize) -> Self::Float) -> Self;
+    fn lane(&self, i: usize) -> Self::Float;
+    /// Sum of the lanes.
+    fn sum(self) -> Self::Float;
+}
+
+impl<const N: usize, F: 'static + Default + Float + FromPrimitive + Send + Sync> Lanes
+    for FloatArray<N, F>
+{
+    #[inline]
+    fn from_fn(f: impl FnMut(usize) -> F) -> Self {
+        std::array::from_fn(f).into()
+    }
+
+    #[inline]
+    fn lane(&self, i: usize) -> F {
+        self[i]
+    }
+
+    #[inline]
+    fn sum(self) -> F {
+        self.iter().fold(F::zero(), |a, b| a + *b)
+    }
+}
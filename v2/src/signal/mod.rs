@@ -2,15 +2,20 @@ pub mod float_array;
 pub mod float_array_signal;
 pub mod into_mono;
 pub mod into_stereo;
+pub mod lanes;
 pub mod mono_stereo;
+#[cfg(feature = "simd")]
+pub mod simd;
 
 pub use into_mono::IntoMono;
 pub use into_stereo::IntoStereo;
+pub use lanes::Lanes;
 pub use mono_stereo::{Mono, Stereo};
 use num_traits::{Float, NumOps, One, Zero};
 
 use self::float_array::FloatArray;
 
+pub type StereoF32 = FloatArray<2, f32>;
 pub type StereoF64 = FloatArray<2, f64>;
 
 pub trait Signal:
@@ -23,6 +28,7 @@ pub trait Signal:
     + One
     + Send
     + Sync
+    + resampler::Sample
     + 'static
 {
     type Float: Float;
@@ -1,4 +1,4 @@
-use super::{Signal, StereoF64};
+use super::{float_array::FloatArray, Signal};
 
 pub trait Mono: Signal {
     type Stereo: Stereo<Float = Self::Float>;
@@ -15,35 +15,42 @@ pub trait Stereo: Signal {
     fn get_r(&self) -> Self::Float;
 }
 
-impl Mono for f64 {
-    type Stereo = StereoF64;
-
-    #[inline]
-    fn from_m(m: f64) -> Self {
-        m
-    }
-
-    #[inline]
-    fn get_m(&self) -> f64 {
-        *self
-    }
+macro_rules! impl_mono_stereo {
+    ($f:ty) => {
+        impl Mono for $f {
+            type Stereo = FloatArray<2, $f>;
+
+            #[inline]
+            fn from_m(m: $f) -> Self {
+                m
+            }
+
+            #[inline]
+            fn get_m(&self) -> $f {
+                *self
+            }
+        }
+
+        impl Stereo for FloatArray<2, $f> {
+            type Mono = $f;
+
+            #[inline]
+            fn from_lr(l: $f, r: $f) -> Self {
+                [l, r].into()
+            }
+
+            #[inline]
+            fn get_l(&self) -> $f {
+                self[0]
+            }
+
+            #[inline]
+            fn get_r(&self) -> $f {
+                self[1]
+            }
+        }
+    };
 }
 
-impl Stereo for StereoF64 {
-    type Mono = f64;
-
-    #[inline]
-    fn from_lr(l: f64, r: f64) -> Self {
-        [l, r].into()
-    }
-
-    #[inline]
-    fn get_l(&self) -> f64 {
-        self[0]
-    }
-
-    #[inline]
-    fn get_r(&self) -> f64 {
-        self[1]
-    }
-}
+impl_mono_stereo!(f32);
+impl_mono_stereo!(f64);
@@ -0,0 +1,248 @@
+use std::simd::{prelude::*, SimdElement, StdFloat};
+
+use num_traits::{One, Zero};
+
+use super::{float_array::FloatArray, Lanes, Signal, Stereo};
+
+/// `FloatArray` with the arithmetic in SIMD lanes. Functions without a SIMD form fall back to
+/// lane by lane.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SimdArray<const N: usize, F: SimdElement>(pub Simd<F, N>);
+
+pub type SimdStereoF32 = SimdArray<2, f32>;
+pub type SimdStereoF64 = SimdArray<2, f64>;
+
+macro_rules! impl_simd_array {
+    ($f:ty) => {
+        impl<const N: usize> Default for SimdArray<N, $f> {
+            #[inline]
+            fn default() -> Self {
+                Self(Simd::splat(0.0))
+            }
+        }
+
+        impl<const N: usize> From<$f> for SimdArray<N, $f> {
+            #[inline]
+            fn from(x: $f) -> Self {
+                Self(Simd::splat(x))
+            }
+        }
+
+        impl<const N: usize> From<[$f; N]> for SimdArray<N, $f> {
+            #[inline]
+            fn from(x: [$f; N]) -> Self {
+                Self(Simd::from_array(x))
+            }
+        }
+
+        impl<const N: usize> From<FloatArray<N, $f>> for SimdArray<N, $f> {
+            #[inline]
+            fn from(x: FloatArray<N, $f>) -> Self {
+                Self(Simd::from_array(*x))
+            }
+        }
+
+        impl<const N: usize> From<SimdArray<N, $f>> for FloatArray<N, $f> {
+            #[inline]
+            fn from(x: SimdArray<N, $f>) -> Self {
+                x.0.to_array().into()
+            }
+        }
+
+        impl<const N: usize> std::ops::Neg for SimdArray<N, $f> {
+            type Output = Self;
+
+            #[inline]
+            fn neg(self) -> Self {
+                Self(-self.0)
+            }
+        }
+
+        impl_simd_array!(@op $f, Add, add);
+        impl_simd_array!(@op $f, Sub, sub);
+        impl_simd_array!(@op $f, Mul, mul);
+        impl_simd_array!(@op $f, Div, div);
+        impl_simd_array!(@op $f, Rem, rem);
+
+        impl<const N: usize> Zero for SimdArray<N, $f> {
+            #[inline]
+            fn zero() -> Self {
+                Self::default()
+            }
+
+            #[inline]
+            fn is_zero(&self) -> bool {
+                self.0 == Simd::splat(0.0)
+            }
+        }
+
+        impl<const N: usize> One for SimdArray<N, $f> {
+            #[inline]
+            fn one() -> Self {
+                Self(Simd::splat(1.0))
+            }
+        }
+
+        impl<const N: usize> resampler::Sample for SimdArray<N, $f> {
+            #[inline]
+            fn mul_add(self, weight: f64, acc: Self) -> Self {
+                Self(self.0.mul_add(Simd::splat(weight as $f), acc.0))
+            }
+        }
+
+        impl<const N: usize> Signal for SimdArray<N, $f> {
+            type Float = $f;
+            const CHANNEL: usize = N;
+
+            #[inline]
+            fn map(self, f: impl Fn($f) -> $f) -> Self {
+                Self(Simd::from_array(self.0.to_array().map(f)))
+            }
+
+            #[inline]
+            fn zip_map(self, other: Self, f: impl Fn($f, $f) -> $f) -> Self {
+                let (a, b) = (self.0.to_array(), other.0.to_array());
+                Self(Simd::from_array(std::array::from_fn(|i| f(a[i], b[i]))))
+            }
+
+            #[inline]
+            fn float_from_f64(x: f64) -> $f {
+                x as $f
+            }
+
+            #[inline]
+            fn floor(self) -> Self {
+                Self(self.0.floor())
+            }
+
+            #[inline]
+            fn ceil(self) -> Self {
+                Self(self.0.ceil())
+            }
+
+            #[inline]
+            fn round(self) -> Self {
+                Self(self.0.round())
+            }
+
+            #[inline]
+            fn trunc(self) -> Self {
+                Self(self.0.trunc())
+            }
+
+            #[inline]
+            fn fract(self) -> Self {
+                Self(self.0.fract())
+            }
+
+            #[inline]
+            fn abs(self) -> Self {
+                Self(self.0.abs())
+            }
+
+            #[inline]
+            fn mul_add(self, a: $f, b: $f) -> Self {
+                Self(self.0.mul_add(Simd::splat(a), Simd::splat(b)))
+            }
+
+            #[inline]
+            fn recip(self) -> Self {
+                Self(self.0.recip())
+            }
+
+            #[inline]
+            fn sqrt(self) -> Self {
+                Self(self.0.sqrt())
+            }
+
+            #[inline]
+            fn max(self, other: Self) -> Self {
+                Self(self.0.simd_max(other.0))
+            }
+
+            #[inline]
+            fn min(self, other: Self) -> Self {
+                Self(self.0.simd_min(other.0))
+            }
+        }
+
+        impl<const N: usize> Lanes for SimdArray<N, $f> {
+            #[inline]
+            fn from_fn(f: impl FnMut(usize) -> $f) -> Self {
+                Self(Simd::from_array(std::array::from_fn(f)))
+            }
+
+            #[inline]
+            fn lane(&self, i: usize) -> $f {
+                self.0[i]
+            }
+
+            #[inline]
+            fn sum(self) -> $f {
+                self.0.reduce_sum()
+            }
+        }
+
+        impl Stereo for SimdArray<2, $f> {
+            type Mono = $f;
+
+            #[inline]
+            fn from_lr(l: $f, r: $f) -> Self {
+                Self(Simd::from_array([l, r]))
+            }
+
+            #[inline]
+            fn get_l(&self) -> $f {
+                self.0[0]
+            }
+
+            #[inline]
+            fn get_r(&self) -> $f {
+                self.0[1]
+            }
+        }
+    };
+    (@op $f:ty, $trait:ident, $method:ident) => {
+        impl<const N: usize> std::ops::$trait for SimdArray<N, $f> {
+            type Output = Self;
+
+            #[inline]
+            fn $method(self, rhs: Self) -> Self {
+                Self(std::ops::$trait::$method(self.0, rhs.0))
+            }
+        }
+
+        impl<const N: usize> std::ops::$trait<$f> for SimdArray<N, $f> {
+            type Output = Self;
+
+            #[inline]
+            fn $method(self, rhs: $f) -> Self {
+                Self(std::ops::$trait::$method(self.0, Simd::splat(rhs)))
+            }
+        }
+    };
+}
+
+impl_simd_array!(f32);
+impl_simd_array!(f64);
+
+#[test]
+fn test() {
+    // Agrees with the scalar arrays.
+    let a = FloatArray::from([0.5f32, -1.25, 2.0, 3.75]);
+    let b = FloatArray::from([1.5f32, 0.25, -2.5, 0.125]);
+    let (sa, sb) = (SimdArray::from(a), SimdArray::from(b));
+    let check = |x: FloatArray<4, f32>, y: SimdArray<4, f32>| {
+        assert_eq!(x, FloatArray::from(y));
+    };
+    check(a + b, sa + sb);
+    check(a * b - a / b, sa * sb - sa / sb);
+    check(a % b, sa % sb);
+    check(a * 2.0 + 1.0, sa * 2.0 + 1.0);
+    check(a.fract(), sa.fract());
+    check(a.abs().sqrt(), sa.abs().sqrt());
+    check(Signal::max(a, b), Signal::max(sa, sb));
+    check(a.sin(), sa.sin());
+    assert_eq!(a.sum(), sa.sum());
+    assert_eq!(SimdStereoF64::from_lr(1.0, 2.0).get_r(), 2.0);
+}
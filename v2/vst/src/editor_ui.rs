@@ -2,12 +2,19 @@ use std::{cell::RefCell, sync::Arc};
 
 use crate::{
     synth::{
+        analyzer::{Analyzer, ANALYZER_LEN},
         bender::Bender,
-        effectors::{FilterType, ShaperType},
+        effectors::{Division, EqBand, FilterType, ShaperType, OVERSAMPLING_RATIOS},
         param_pool::ProducerId,
+        VoiceType,
     },
     MyPluginParams,
 };
+use corus_v2::nodes::effects::{
+    dynamics::Detection,
+    fdn_reverb::Matrix,
+    parametric_eq::{response, Band, BandType, Placement, Slope},
+};
 use nih_plug::prelude::*;
 use nih_plug_egui::egui::{self, emath};
 use rustfft::num_complex::Complex64;
@@ -30,6 +37,15 @@ pub fn editor_updator(
     egui::CentralPanel::default().show(egui_ctx, |ui| {
         let mut synth = state.synth.lock().unwrap();
 
+        ui.horizontal(|ui| {
+            for (name, voice_type) in [
+                ("wavetable", VoiceType::Wavetable),
+                ("benihora", VoiceType::Benihora),
+            ] {
+                ui.selectable_value(&mut synth.voice.voice_type, voice_type, name);
+            }
+        });
+
         ui.collapsing("Generator", |ui| {
             for (i, osc) in synth.voice.oscs.iter_mut().enumerate() {
                 ui.push_id(i, |ui| {
@@ -38,6 +54,14 @@ pub fn editor_updator(
             }
         });
 
+        ui.collapsing("Benihora", |ui| {
+            benihora_ui(ui, &mut synth.voice.benihora);
+        });
+
+        ui.collapsing("Vocal", |ui| {
+            vocal_ui(ui, &mut synth.voice.vocal);
+        });
+
         ui.collapsing("Envelope", |ui| {
             let mut envloc = state.envelope_location.lock().unwrap();
             ui.horizontal(|ui| {
@@ -77,12 +101,14 @@ pub fn editor_updator(
                 }
             });
 
+            let sample_rate = state.state_layout.lock().unwrap().sample_rate();
             match state.effectors_location.lock().unwrap().clone() {
                 EffectorsLocation::Voice => {
                     effectors(
                         &mut synth.voice.effectors,
                         ui,
                         true,
+                        sample_rate,
                         |i: usize, j: usize| {
                             // *state.envelope_location.lock().unwrap() =
                             //     EnvelopeLocation::VoiceEffector(i, j);
@@ -90,14 +116,24 @@ pub fn editor_updator(
                     );
                 }
                 EffectorsLocation::Master => {
-                    effectors(&mut synth.effectors, ui, false, |i: usize, j: usize| {
-                        // *state.envelope_location.lock().unwrap() =
-                        //     EnvelopeLocation::MasterEffector(i, j);
-                    });
+                    effectors(
+                        &mut synth.effectors,
+                        ui,
+                        false,
+                        sample_rate,
+                        |i: usize, j: usize| {
+                            // *state.envelope_location.lock().unwrap() =
+                            //     EnvelopeLocation::MasterEffector(i, j);
+                        },
+                    );
                 }
             }
         });
 
+        ui.collapsing("Smoothing", |ui| {
+            param_smoothing_ui(ui, &mut synth.param_smoothing);
+        });
+
         drop(synth);
         ui.collapsing("Wavetable lab", |ui| {
             state.wavetable_lab.lock().unwrap().show(
@@ -150,6 +186,8 @@ pub fn editor_updator(
         //         .text(peak_meter_text),
         // );
     });
+
+    state.sync_state();
 }
 
 fn generator_ui(ui: &mut egui::Ui, osc: &mut crate::synth::Osc) {
@@ -281,6 +319,164 @@ fn generator_ui(ui: &mut egui::Ui, osc: &mut crate::synth::Osc) {
     });
 }
 
+fn benihora_ui(ui: &mut egui::Ui, settings: &mut crate::synth::benihora_voice::BenihoraSettings) {
+    ui.horizontal(|ui| {
+        tract_editor(ui, settings);
+        vowel_pad(ui, &mut settings.vowel);
+    });
+
+    ui.horizontal(|ui| {
+        ui.add(crate::widgets::knob::knob_named(
+            0.01..0.4,
+            &mut settings.velum,
+            "velum",
+        ));
+        ui.add(crate::widgets::knob::knob_named(
+            0.0..1.0,
+            &mut settings.tenseness,
+            "tense",
+        ));
+        ui.add(crate::widgets::knob::knob_named(
+            1.0..4.0,
+            &mut settings.sound_speed,
+            "speed",
+        ));
+        add_knob(ui, &mut settings.level, 0.001..1.0, true, || ());
+    });
+}
+
+/// Diameter profile of the mouth from glottis (left) to lips (right).
+/// Drag the handles to move constrictions, double-click to add one and right-click to remove it.
+fn tract_editor(ui: &mut egui::Ui, settings: &mut crate::synth::benihora_voice::BenihoraSettings) {
+    use crate::synth::benihora_voice::MAX_CONSTRICTIONS;
+    use benihora::tract::{Diameter, ShapeSource, MOUTH_LENGTH, NOSE_LENGTH};
+
+    const MAX_DIAMETER: f32 = 4.0;
+
+    let mut source = ShapeSource::new(MOUTH_LENGTH, NOSE_LENGTH);
+    source.tongue = corus_v2::contrib::benihora::BenihoraVoice::vowel_to_tongue(
+        &source,
+        settings.vowel.0,
+        settings.vowel.1,
+    );
+    source.other_constrictions = settings.constrictions.clone();
+    let mut diameter = Diameter::new(&source);
+    source.compute_diameter(&mut diameter);
+
+    egui::Frame::canvas(ui.style()).show(ui, |ui| {
+        let (id, rect) = ui.allocate_space(egui::vec2(264.0, 100.0));
+        let range =
+            egui::Rect::from_x_y_ranges(0.0..=(MOUTH_LENGTH - 1) as f32, MAX_DIAMETER..=0.0);
+        let to_screen = emath::RectTransform::from_to(range, rect);
+        let from_screen = to_screen.inverse();
+
+        let points = diameter
+            .mouth
+            .iter()
+            .enumerate()
+            .map(|(i, d)| to_screen * egui::pos2(i as f32, *d as f32))
+            .collect();
+        ui.painter().add(egui::Shape::line(
+            points,
+            egui::Stroke::new(1.0, egui::Color32::LIGHT_BLUE),
+        ));
+
+        let background = ui.interact(rect, id, egui::Sense::click());
+        let mut remove = None;
+        for (i, constriction) in settings.constrictions.iter_mut().enumerate() {
+            let center = to_screen * egui::pos2(constriction.0 as f32, constriction.1 as f32);
+            let handle = egui::Rect::from_center_size(center, egui::Vec2::splat(10.0));
+            let response = ui.interact(handle, id.with(i), egui::Sense::click_and_drag());
+            if response.dragged() {
+                if let Some(pos) = response.interact_pointer_pos() {
+                    let pos = from_screen * rect.clamp(pos);
+                    *constriction = (pos.x as f64, pos.y as f64);
+                }
+            }
+            if response.secondary_clicked() {
+                remove = Some(i);
+            }
+            let color = if response.hovered() || response.dragged() {
+                egui::Color32::WHITE
+            } else {
+                egui::Color32::LIGHT_RED
+            };
+            ui.painter().circle_filled(center, 4.0, color);
+        }
+        if let Some(i) = remove {
+            settings.constrictions.remove(i);
+        }
+        if background.double_clicked() && settings.constrictions.len() < MAX_CONSTRICTIONS {
+            if let Some(pos) = background.interact_pointer_pos() {
+                let pos = from_screen * pos;
+                settings.constrictions.push((pos.x as f64, pos.y as f64));
+            }
+        }
+    });
+}
+
+/// Back to front horizontally and open to close vertically, like a vowel chart.
+fn vowel_pad(ui: &mut egui::Ui, vowel: &mut (f64, f64)) {
+    egui::Frame::canvas(ui.style()).show(ui, |ui| {
+        let (id, rect) = ui.allocate_space(egui::vec2(100.0, 100.0));
+        let to_screen =
+            emath::RectTransform::from_to(egui::Rect::from_x_y_ranges(0.0..=1.0, 1.0..=0.0), rect);
+        let response = ui.interact(rect, id, egui::Sense::click_and_drag());
+        if let Some(pos) = response.interact_pointer_pos() {
+            let pos = to_screen.inverse() * rect.clamp(pos);
+            *vowel = (pos.x as f64, pos.y as f64);
+        }
+        ui.painter().circle_filled(
+            to_screen * egui::pos2(vowel.0 as f32, vowel.1 as f32),
+            4.0,
+            egui::Color32::LIGHT_BLUE,
+        );
+    });
+}
+
+fn vocal_ui(ui: &mut egui::Ui, vocal: &mut Option<crate::synth::vocal::Vocal>) {
+    let mut enabled = vocal.is_some();
+    if ui.checkbox(&mut enabled, "enabled").changed() {
+        *vocal = enabled.then(crate::synth::vocal::Vocal::new);
+    }
+    let Some(vocal) = vocal else {
+        return;
+    };
+
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::widgets::DragValue::new(&mut vocal.voices)
+                .clamp_range(1..=crate::synth::vocal::MAX_VOICES),
+        );
+        ui.label("voices");
+        egui::ComboBox::from_label("vowel")
+            .selected_text(vocal.vowel.as_str())
+            .show_ui(ui, |ui| {
+                for phoneme in crate::synth::vocal::Vocal::vowels() {
+                    ui.selectable_value(
+                        &mut vocal.vowel,
+                        phoneme.symbol.to_string(),
+                        phoneme.symbol,
+                    );
+                }
+            });
+    });
+
+    ui.horizontal(|ui| {
+        ui.add(crate::widgets::knob::knob_named(
+            0.0..50.0,
+            &mut vocal.detune,
+            "detune",
+        ));
+        ui.add(crate::widgets::knob::knob_named(
+            0.0..1.0,
+            &mut vocal.stereo_spread,
+            "spread",
+        ));
+        add_knob(ui, &mut vocal.level, 0.001..1.0, true, || ());
+    });
+}
+
 fn wavetable_seed(osc: &mut crate::synth::Osc, ui: &mut egui::Ui) {
     if osc.wavetable_settings.is_custom_wavetable() {
         ui.horizontal(|ui| {
@@ -372,10 +568,30 @@ fn lfo(ui: &mut egui::Ui, lfo: &mut crate::synth::param_f64::Lfo) {
     });
 }
 
+fn param_smoothing_ui(ui: &mut egui::Ui, smoothing: &mut crate::synth::param_f64::ParamSmoothing) {
+    use corus_v2::nodes::smoother::Smoothing;
+
+    ui.horizontal(|ui| {
+        for (name, mode) in [
+            ("linear", Smoothing::Linear),
+            ("one-pole", Smoothing::OnePole),
+            ("log", Smoothing::Log),
+        ] {
+            ui.selectable_value(&mut smoothing.mode, mode, name);
+        }
+        ui.add(crate::widgets::knob::knob_log(
+            0.0001..1.0,
+            &mut smoothing.time,
+            "time",
+        ));
+    });
+}
+
 fn effectors(
     effectors: &mut Vec<(bool, crate::synth::effectors::Effector)>,
     ui: &mut egui::Ui,
     is_voice: bool,
+    sample_rate: f64,
     setter: impl Fn(usize, usize),
 ) {
     for (i, (enabled, effector)) in effectors.iter_mut().enumerate() {
@@ -399,13 +615,131 @@ fn effectors(
                                 }
                             });
                         add_knob(ui, frequency, 20.0..10000.0, is_voice, || setter(i, 0));
-                        add_knob(ui, q, 0.7..10.0, is_voice, || setter(i, 1));
+                        let q_range = if filter_type.is_resonant() {
+                            0.0..1.1
+                        } else {
+                            0.7..10.0
+                        };
+                        add_knob(ui, q, q_range, is_voice, || setter(i, 1));
                         add_knob(ui, gain, -20.0..20.0, is_voice, || setter(i, 2));
                     }
                     Effector::Phaser => {}
                     Effector::Chorus => {}
-                    Effector::Delay => {}
+                    Effector::Delay {
+                        sync,
+                        time,
+                        feedback,
+                        cross_feed,
+                        low_cut,
+                        high_cut,
+                        drive,
+                        wow,
+                        flutter,
+                        duck,
+                        mix,
+                        offset,
+                    } => {
+                        egui::ComboBox::from_label("sync")
+                            .selected_text(sync.map_or("Free", |d| d.name()))
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(sync, None, "Free");
+                                for d in &Division::ALL {
+                                    ui.selectable_value(sync, Some(*d), d.name());
+                                }
+                            });
+                        if sync.is_none() {
+                            add_knob(ui, time, 0.001..2.0, is_voice, || setter(i, 0));
+                        }
+                        add_knob(ui, feedback, 0.0..1.0, is_voice, || setter(i, 1));
+                        add_knob(ui, cross_feed, 0.0..1.0, is_voice, || setter(i, 2));
+                        add_knob(ui, low_cut, 0.0..2000.0, is_voice, || setter(i, 3));
+                        add_knob(ui, high_cut, 500.0..20000.0, is_voice, || setter(i, 4));
+                        add_knob(ui, drive, 0.0..8.0, is_voice, || setter(i, 5));
+                        add_knob(ui, wow, 0.0..1.0, is_voice, || setter(i, 6));
+                        add_knob(ui, flutter, 0.0..1.0, is_voice, || setter(i, 7));
+                        add_knob(ui, duck, 0.0..1.0, is_voice, || setter(i, 8));
+                        add_knob(ui, mix, 0.0..1.0, is_voice, || setter(i, 9));
+                        add_knob(ui, offset, -0.05..0.05, is_voice, || setter(i, 10));
+                    }
                     Effector::Reverb => {}
+                    Effector::FdnReverb {
+                        pre_delay,
+                        size,
+                        rt60_low,
+                        rt60_high,
+                        crossover,
+                        modulation,
+                        mix,
+                        matrix,
+                    } => {
+                        add_knob(ui, pre_delay, 0.0..0.5, is_voice, || setter(i, 0));
+                        add_knob(ui, size, 0.5..2.0, is_voice, || setter(i, 1));
+                        add_knob(ui, rt60_low, 0.1..20.0, is_voice, || setter(i, 2));
+                        add_knob(ui, rt60_high, 0.1..20.0, is_voice, || setter(i, 3));
+                        add_knob(ui, crossover, 200.0..10000.0, is_voice, || setter(i, 4));
+                        add_knob(ui, modulation, 0.0..1.0, is_voice, || setter(i, 5));
+                        add_knob(ui, mix, 0.0..1.0, is_voice, || setter(i, 6));
+                        egui::ComboBox::from_label("matrix")
+                            .selected_text(format!("{:?}", matrix))
+                            .show_ui(ui, |ui| {
+                                for m in [Matrix::Hadamard, Matrix::Householder] {
+                                    ui.selectable_value(matrix, m, format!("{:?}", m));
+                                }
+                            });
+                    }
+                    Effector::PlateReverb {
+                        pre_delay,
+                        size,
+                        decay,
+                        damping,
+                        modulation,
+                        width,
+                        mix,
+                        freeze,
+                    } => {
+                        add_knob(ui, pre_delay, 0.0..0.5, is_voice, || setter(i, 0));
+                        add_knob(ui, size, 0.5..2.0, is_voice, || setter(i, 1));
+                        add_knob(ui, decay, 0.0..0.99, is_voice, || setter(i, 2));
+                        add_knob(ui, damping, 0.0..1.0, is_voice, || setter(i, 3));
+                        add_knob(ui, modulation, 0.0..1.0, is_voice, || setter(i, 4));
+                        add_knob(ui, width, 0.0..1.0, is_voice, || setter(i, 5));
+                        add_knob(ui, mix, 0.0..1.0, is_voice, || setter(i, 6));
+                        ui.checkbox(freeze, "freeze");
+                    }
+                    Effector::Convolution {
+                        path,
+                        trim,
+                        normalize,
+                        pre_delay,
+                        mix,
+                    } => {
+                        // The file is loaded when the path is committed, not on every keystroke.
+                        let id = egui::Id::new(("ir path", is_voice, i));
+                        let mut text = ui
+                            .data_mut(|d| d.get_temp::<String>(id))
+                            .unwrap_or_else(|| path.clone());
+                        let response =
+                            ui.add(egui::TextEdit::singleline(&mut text).hint_text("IR .wav"));
+                        if response.lost_focus() {
+                            *path = text;
+                            ui.data_mut(|d| d.remove::<String>(id));
+                        } else if response.has_focus() {
+                            ui.data_mut(|d| d.insert_temp(id, text));
+                        }
+                        ui.add(
+                            egui::widgets::DragValue::new(trim)
+                                .clamp_range(-120.0..=0.0)
+                                .suffix(" dB"),
+                        );
+                        ui.checkbox(normalize, "normalize");
+                        ui.add(
+                            egui::widgets::DragValue::new(pre_delay)
+                                .clamp_range(0.0..=0.5)
+                                .speed(0.001)
+                                .suffix(" s"),
+                        );
+                        add_knob(ui, mix, 0.0..1.0, is_voice, || setter(i, 0));
+                    }
                     Effector::Gain { gain } => {
                         // ui.add(egui::widgets::Slider::new(gain, 0.0..=1.5));
                         add_knob(ui, gain, 0.0..1.5, is_voice, || setter(i, 0));
@@ -413,18 +747,95 @@ fn effectors(
                     Effector::Compressor {
                         threshold,
                         ratio,
+                        knee,
                         attack,
                         release,
-                        gain,
+                        makeup,
+                        sidechain_low_cut,
+                        detection,
+                        lookahead,
+                        sidechain,
+                    } => {
+                        add_knob(ui, threshold, -60.0..0.0, is_voice, || setter(i, 0));
+                        add_knob(ui, ratio, 1.0..20.0, is_voice, || setter(i, 1));
+                        add_knob(ui, knee, 0.0..24.0, is_voice, || setter(i, 2));
+                        add_knob(ui, attack, 0.0001..0.2, is_voice, || setter(i, 3));
+                        add_knob(ui, release, 0.001..1.0, is_voice, || setter(i, 4));
+                        add_knob(ui, makeup, 0.0..24.0, is_voice, || setter(i, 5));
+                        add_knob(ui, sidechain_low_cut, 0.0..500.0, is_voice, || setter(i, 6));
+                        add_knob(ui, lookahead, 0.0..0.01, is_voice, || setter(i, 7));
+                        detection_ui(ui, detection);
+                        if !is_voice {
+                            ui.checkbox(sidechain, "sidechain");
+                        }
+                    }
+                    Effector::Expander {
+                        threshold,
+                        ratio,
+                        knee,
+                        range,
+                        attack,
+                        release,
+                        sidechain_low_cut,
+                        detection,
+                        sidechain,
+                    } => {
+                        add_knob(ui, threshold, -80.0..0.0, is_voice, || setter(i, 0));
+                        add_knob(ui, ratio, 1.0..100.0, is_voice, || setter(i, 1));
+                        add_knob(ui, knee, 0.0..24.0, is_voice, || setter(i, 2));
+                        add_knob(ui, range, 0.0..80.0, is_voice, || setter(i, 3));
+                        add_knob(ui, attack, 0.0001..0.2, is_voice, || setter(i, 4));
+                        add_knob(ui, release, 0.001..1.0, is_voice, || setter(i, 5));
+                        add_knob(ui, sidechain_low_cut, 0.0..500.0, is_voice, || setter(i, 6));
+                        detection_ui(ui, detection);
+                        if !is_voice {
+                            ui.checkbox(sidechain, "sidechain");
+                        }
+                    }
+                    Effector::TransientShaper { attack, sustain } => {
+                        add_knob(ui, attack, -1.0..1.0, is_voice, || setter(i, 0));
+                        add_knob(ui, sustain, -1.0..1.0, is_voice, || setter(i, 1));
+                    }
+                    Effector::Limiter { ceiling, release } => {
+                        add_knob(ui, ceiling, -12.0..0.0, is_voice, || setter(i, 0));
+                        add_knob(ui, release, 0.001..1.0, is_voice, || setter(i, 1));
+                    }
+                    Effector::MultibandCompressor {
+                        low_cross,
+                        high_cross,
+                        low_threshold,
+                        mid_threshold,
+                        high_threshold,
+                        ratio,
+                        attack,
+                        release,
+                        low_makeup,
+                        mid_makeup,
+                        high_makeup,
+                        lookahead,
                     } => {
-                        add_knob(ui, threshold, 0.0..1.0, is_voice, || setter(i, 0));
-                        add_knob(ui, ratio, 0.0..1.0, is_voice, || setter(i, 1));
-                        add_knob(ui, attack, 0.001..1.0, is_voice, || setter(i, 2));
-                        add_knob(ui, release, 0.001..1.0, is_voice, || setter(i, 3));
-                        add_knob(ui, gain, 0.0..1.5, is_voice, || setter(i, 4));
+                        add_knob(ui, low_cross, 20.0..1000.0, is_voice, || setter(i, 0));
+                        add_knob(ui, high_cross, 1000.0..16000.0, is_voice, || setter(i, 1));
+                        add_knob(ui, low_threshold, -60.0..0.0, is_voice, || setter(i, 2));
+                        add_knob(ui, mid_threshold, -60.0..0.0, is_voice, || setter(i, 3));
+                        add_knob(ui, high_threshold, -60.0..0.0, is_voice, || setter(i, 4));
+                        add_knob(ui, ratio, 1.0..20.0, is_voice, || setter(i, 5));
+                        add_knob(ui, attack, 0.0001..0.2, is_voice, || setter(i, 6));
+                        add_knob(ui, release, 0.001..1.0, is_voice, || setter(i, 7));
+                        add_knob(ui, low_makeup, 0.0..24.0, is_voice, || setter(i, 8));
+                        add_knob(ui, mid_makeup, 0.0..24.0, is_voice, || setter(i, 9));
+                        add_knob(ui, high_makeup, 0.0..24.0, is_voice, || setter(i, 10));
+                        add_knob(ui, lookahead, 0.0..0.01, is_voice, || setter(i, 11));
+                    }
+                    Effector::ParametricEq { bands, analyzer } => {
+                        eq_ui(ui, bands, analyzer, sample_rate, is_voice, |j| setter(i, j));
                     }
                     Effector::Tanh {} => {}
-                    Effector::Shaper { pre_gain, r#type } => {
+                    Effector::Shaper {
+                        pre_gain,
+                        r#type,
+                        oversampling,
+                    } => {
                         add_knob(ui, pre_gain, 0.0..32.0, is_voice, || setter(i, 0));
                         egui::ComboBox::from_label("type")
                             .selected_text(r#type.name())
@@ -433,6 +844,13 @@ fn effectors(
                                     ui.selectable_value(r#type, *st, st.name());
                                 }
                             });
+                        egui::ComboBox::from_label("oversampling")
+                            .selected_text(format!("{}x", oversampling))
+                            .show_ui(ui, |ui| {
+                                for n in OVERSAMPLING_RATIOS {
+                                    ui.selectable_value(oversampling, n, format!("{}x", n));
+                                }
+                            });
                     }
                 }
             });
@@ -440,6 +858,132 @@ fn effectors(
     }
 }
 
+fn eq_ui(
+    ui: &mut egui::Ui,
+    bands: &mut [EqBand],
+    analyzer: &Analyzer,
+    sample_rate: f64,
+    is_voice: bool,
+    setter: impl Fn(usize),
+) {
+    ui.vertical(|ui| {
+        egui::Frame::canvas(ui.style()).show(ui, |ui| {
+            let (_id, rect) = ui.allocate_space(egui::vec2(400.0, 150.0));
+            // Log frequency from 20 Hz to 20 kHz against dB.
+            let to_screen = emath::RectTransform::from_to(
+                egui::Rect::from_x_y_ranges(0.0..=1.0, -24.0..=24.0),
+                rect,
+            );
+            let frequency = |x: f64| 20.0 * 1000.0f64.powf(x);
+            let w = rect.width() as usize;
+
+            if !is_voice {
+                let spectrum =
+                    FFT_PLANNER.with(|planner| analyzer.spectrum(&mut planner.borrow_mut()));
+                let bin = sample_rate / ANALYZER_LEN as f64;
+                let points = (0..=w)
+                    .map(|i| {
+                        let x = i as f64 / w as f64;
+                        let k = ((frequency(x) / bin).round() as usize).min(spectrum.len() - 1);
+                        // Shifted so that a full-scale sine sits at the top.
+                        let db = (spectrum[k] + 24.0).max(-24.0);
+                        to_screen * egui::pos2(x as f32, -db as f32)
+                    })
+                    .collect();
+                ui.painter().add(egui::Shape::line(
+                    points,
+                    egui::Stroke::new(1.0, egui::Color32::DARK_GRAY),
+                ));
+                ui.ctx().request_repaint();
+            }
+
+            let curve = bands
+                .iter()
+                .map(|band| Band {
+                    enabled: band.enabled,
+                    r#type: band.r#type,
+                    placement: band.placement,
+                    frequency: band.frequency.value,
+                    gain: band.gain.value,
+                    q: band.q.value,
+                })
+                .collect::<Vec<_>>();
+            let points = (0..=w)
+                .map(|i| {
+                    let x = i as f64 / w as f64;
+                    let db = response(&curve, sample_rate, frequency(x)).clamp(-24.0, 24.0);
+                    to_screen * egui::pos2(x as f32, -db as f32)
+                })
+                .collect();
+            ui.painter().add(egui::Shape::line(
+                points,
+                egui::Stroke::new(1.0, egui::Color32::LIGHT_BLUE),
+            ));
+        });
+
+        for (k, band) in bands.iter_mut().enumerate() {
+            ui.push_id(("band", k), |ui| {
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut band.enabled, format!("{}", k + 1));
+                    egui::ComboBox::from_label("type")
+                        .selected_text(band_type_name(band.r#type))
+                        .show_ui(ui, |ui| {
+                            let mut types = vec![
+                                BandType::Bell,
+                                BandType::LowShelf,
+                                BandType::HighShelf,
+                                BandType::Notch,
+                            ];
+                            for slope in Slope::ALL {
+                                types.push(BandType::LowPass(slope));
+                                types.push(BandType::HighPass(slope));
+                            }
+                            for t in types {
+                                ui.selectable_value(&mut band.r#type, t, band_type_name(t));
+                            }
+                        });
+                    egui::ComboBox::from_label("channels")
+                        .selected_text(format!("{:?}", band.placement))
+                        .show_ui(ui, |ui| {
+                            for p in [Placement::Stereo, Placement::Mid, Placement::Side] {
+                                ui.selectable_value(&mut band.placement, p, format!("{:?}", p));
+                            }
+                        });
+                    add_knob(ui, &mut band.frequency, 20.0..20000.0, is_voice, || {
+                        setter(k * 3)
+                    });
+                    add_knob(ui, &mut band.gain, -24.0..24.0, is_voice, || {
+                        setter(k * 3 + 1)
+                    });
+                    add_knob(ui, &mut band.q, 0.1..10.0, is_voice, || setter(k * 3 + 2));
+                });
+            });
+        }
+    });
+}
+
+fn detection_ui(ui: &mut egui::Ui, detection: &mut Detection) {
+    egui::ComboBox::from_label("detection")
+        .selected_text(format!("{:?}", detection))
+        .show_ui(ui, |ui| {
+            for d in [Detection::Peak, Detection::Rms] {
+                ui.selectable_value(detection, d, format!("{:?}", d));
+            }
+        });
+}
+
+fn band_type_name(r#type: BandType) -> String {
+    let slope = |slope: Slope| (slope.sections() * 12).to_string();
+    match r#type {
+        BandType::Bell => "bell".to_string(),
+        BandType::LowShelf => "low shelf".to_string(),
+        BandType::HighShelf => "high shelf".to_string(),
+        BandType::LowPass(s) => format!("low pass {} dB", slope(s)),
+        BandType::HighPass(s) => format!("high pass {} dB", slope(s)),
+        BandType::Notch => "notch".to_string(),
+    }
+}
+
 fn add_knob(
     ui: &mut egui::Ui,
     param: &mut crate::synth::param_f64::ParamF64,
@@ -6,13 +6,20 @@ use corus_v2::{event_queue::EventQueue, signal::Stereo};
 use editor_ui::EffectorsLocation;
 use nih_plug::prelude::*;
 use nih_plug_egui::{create_egui_editor, EguiState};
-use std::sync::{Arc, Mutex};
-use synth::{MyEvent, MySynth};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
+use synth::{state_swap::StateSwap, MyEvent, MySynth, StateLayout, StatePatch};
 
 pub struct MyPlugin {
     params: Arc<MyPluginParams>,
     context: corus_v2::ProcessContext,
     event_queue: EventQueue<MyEvent>,
+    /// Reported to the host, in samples.
+    latency: u32,
+    /// Owned by the audio thread. Updated through `MyPluginParams::state_swap`.
+    synth_state: Box<synth::State>,
 }
 
 #[derive(Params)]
@@ -21,7 +28,11 @@ pub struct MyPluginParams {
     editor_state: Arc<EguiState>,
     #[persist = "synth"]
     synth: Arc<Mutex<MySynth>>,
-    synth_state: Arc<Mutex<synth::State>>,
+    state_swap: StateSwap<StatePatch>,
+    /// Layout of the last published patch. Held while a patch is built.
+    state_layout: Mutex<StateLayout>,
+    /// Set by the audio thread when it has asked for `Task::SyncState`.
+    sync_requested: AtomicBool,
     envelope_location: Mutex<usize>,
     effectors_location: Mutex<EffectorsLocation>,
     wavetable_lab: Mutex<widgets::wavetable_lab::WavetableLab>,
@@ -36,22 +47,29 @@ pub struct MyPluginParams {
 
 impl Default for MyPlugin {
     fn default() -> Self {
+        let params = Arc::new(MyPluginParams::default());
+        let layout = params.state_layout.lock().unwrap().clone();
+        let synth_state = Box::new(synth::State::new(layout));
         Self {
-            params: Arc::new(MyPluginParams::default()),
+            params,
             context: corus_v2::ProcessContext::new(44100.0),
-            event_queue: EventQueue::new(),
+            event_queue: EventQueue::with_capacity(1024),
+            latency: 0,
+            synth_state,
         }
     }
 }
 
 impl Default for MyPluginParams {
     fn default() -> Self {
-        let synth = Arc::new(Mutex::new(MySynth::new()));
-        let synth_state = Arc::new(Mutex::new(synth::State::new(&synth.lock().unwrap())));
+        let mut synth = MySynth::new();
+        let state_layout = Mutex::new(StateLayout::new(&mut synth, 44100.0));
         Self {
             editor_state: EguiState::from_size(400, 400),
-            synth,
-            synth_state,
+            synth: Arc::new(Mutex::new(synth)),
+            state_swap: StateSwap::new(),
+            state_layout,
+            sync_requested: AtomicBool::new(false),
             envelope_location: Mutex::new(0),
             effectors_location: Mutex::new(EffectorsLocation::Master),
             wavetable_lab: Mutex::new(widgets::wavetable_lab::WavetableLab::new()),
@@ -94,6 +112,33 @@ impl Default for MyPluginParams {
     }
 }
 
+impl MyPluginParams {
+    /// Builds a patch for the parts of the synth state that the settings no longer match and hands
+    /// it to the audio thread, which keeps playing the old state until then.
+    /// This allocates and may load files, so call it from the GUI or a background thread only.
+    pub fn sync_state(&self) {
+        self.sync_requested.store(false, Ordering::Relaxed);
+        self.state_swap.collect();
+        let mut layout = self.state_layout.lock().unwrap();
+        // A patch applies to the layout it was built from; wait for the audio thread to take
+        // the last one.
+        if self.state_swap.is_pending() {
+            return;
+        }
+        let target = {
+            let mut synth = self.synth.lock().unwrap();
+            if layout.matches(&synth) {
+                return;
+            }
+            StateLayout::new(&mut synth, layout.sample_rate())
+        };
+        // The audio thread needs `synth`, so it is not held while building.
+        let patch = StatePatch::new(&layout, target.clone());
+        *layout = target;
+        self.state_swap.publish(Box::new(patch));
+    }
+}
+
 impl Plugin for MyPlugin {
     const NAME: &'static str = "corus example";
     const VENDOR: &'static str = "author";
@@ -106,10 +151,13 @@ impl Plugin for MyPlugin {
         main_input_channels: NonZeroU32::new(0),
         main_output_channels: NonZeroU32::new(2),
 
-        aux_input_ports: &[],
+        aux_input_ports: &[new_nonzero_u32(2)],
         aux_output_ports: &[],
 
-        names: PortNames::const_default(),
+        names: PortNames {
+            aux_inputs: &["Sidechain"],
+            ..PortNames::const_default()
+        },
     }];
 
     const MIDI_INPUT: MidiConfig = MidiConfig::MidiCCs;
@@ -118,7 +166,7 @@ impl Plugin for MyPlugin {
     const SAMPLE_ACCURATE_AUTOMATION: bool = true;
 
     type SysExMessage = ();
-    type BackgroundTask = ();
+    type BackgroundTask = Task;
 
     fn params(&self) -> Arc<dyn Params> {
         self.params.clone()
@@ -127,12 +175,17 @@ impl Plugin for MyPlugin {
     fn initialize(
         &mut self,
         _audio_io_layout: &AudioIOLayout,
-        _buffer_config: &BufferConfig,
+        buffer_config: &BufferConfig,
         _context: &mut impl InitContext<Self>,
     ) -> bool {
-        // Resize buffers and perform other potentially expensive initialization operations here.
-        // The `reset()` function is always called right after this function. You can remove this
-        // function if you do not need it.
+        // The synth settings may have been replaced by a loaded preset.
+        let mut layout = self.params.state_layout.lock().unwrap();
+        self.params.state_swap.discard();
+        *layout = StateLayout::new(
+            &mut self.params.synth.lock().unwrap(),
+            buffer_config.sample_rate as f64,
+        );
+        self.synth_state = Box::new(synth::State::new(layout.clone()));
         true
     }
 
@@ -141,6 +194,13 @@ impl Plugin for MyPlugin {
         // allocate. You can remove this function if you do not need it.
     }
 
+    fn task_executor(&mut self) -> TaskExecutor<Self> {
+        let params = self.params.clone();
+        Box::new(move |task| match task {
+            Task::SyncState => params.sync_state(),
+        })
+    }
+
     fn editor(&mut self, _async_executor: AsyncExecutor<Self>) -> Option<Box<dyn Editor>> {
         // let peak_meter = self.peak_meter.clone();
         create_egui_editor(
@@ -154,7 +214,7 @@ impl Plugin for MyPlugin {
     fn process(
         &mut self,
         buffer: &mut Buffer,
-        _aux: &mut AuxiliaryBuffers,
+        aux: &mut AuxiliaryBuffers,
         context: &mut impl ProcessContext<Self>,
     ) -> ProcessStatus {
         let sample_rate = context.transport().sample_rate as f64;
@@ -224,8 +284,7 @@ impl Plugin for MyPlugin {
                     let time = self.context.current_time() + timing as f64 / sample_rate;
                     match cc {
                         control_change::MODULATION_MSB => {
-                            // let value = value as f64 / 12.0;
-                            // self.event_queue.push(time, MyEvent::SetModLevel(value));
+                            self.params.synth.lock().unwrap().modulation = value as f64;
                         }
                         //     control_change::SOUND_CONTROLLER_5 => {
                         //         // cutoff
@@ -273,22 +332,44 @@ impl Plugin for MyPlugin {
             }
         }
 
+        let synth_state = &mut self.synth_state;
+        self.params
+            .state_swap
+            .receive(|patch| synth_state.apply(patch));
         let mut synth = self.params.synth.lock().unwrap();
-        let mut synth_state = self.params.synth_state.lock().unwrap();
-        synth.ensure_state(&mut synth_state);
+        if let Some(tempo) = context.transport().tempo {
+            synth.tempo = tempo;
+        }
+        let synth_state = &mut **synth_state;
+
+        if synth_state.layout().matches(&synth) {
+            let latency = synth.latency(synth_state).round() as u32;
+            if latency != self.latency {
+                context.set_latency_samples(latency);
+                self.latency = latency;
+            }
+        } else if !self.params.sync_requested.swap(true, Ordering::Relaxed) {
+            // Keeps playing the old state meanwhile, even with the editor closed.
+            context.execute_background(Task::SyncState);
+        }
 
         // apply params
         // synth.frequency = self.params.frequency.value() as f64;
         // synth.q = self.params.resonance.value() as f64;
 
-        for mut channel_samples in buffer.iter_samples() {
+        let sidechain = aux.inputs.first().map(|input| input.as_slice_immutable());
+        for (i, mut channel_samples) in buffer.iter_samples().enumerate() {
             self.event_queue
                 .dispatch(self.context.current_time(), |_eq, time, event| {
-                    synth.handle_event(&mut synth_state, event, time)
+                    synth.handle_event(synth_state, event, time)
                 });
             // Smoothing is optionally built into the parameters themselves
             let gain = self.params.gain.smoothed.next();
-            let x = synth.process(&mut synth_state, &self.context);
+            let sidechain = sidechain.map(|channels| {
+                let [l, r] = [&channels[0], &channels[channels.len() - 1]];
+                corus_v2::signal::StereoF64::from_lr(l[i] as f64, r[i] as f64)
+            });
+            let x = synth.process(synth_state, &self.context, sidechain);
 
             *channel_samples.get_mut(0).unwrap() = gain * x.get_l() as f32;
             *channel_samples.get_mut(1).unwrap() = gain * x.get_r() as f32;
@@ -300,6 +381,11 @@ impl Plugin for MyPlugin {
     }
 }
 
+pub enum Task {
+    /// See `MyPluginParams::sync_state`.
+    SyncState,
+}
+
 impl ClapPlugin for MyPlugin {
     const CLAP_ID: &'static str = "corus example";
     const CLAP_DESCRIPTION: Option<&'static str> = Some("corus example");
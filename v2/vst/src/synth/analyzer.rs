@@ -0,0 +1,58 @@
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+use rustfft::num_complex::Complex64;
+
+/// Samples kept for the spectrum, also the FFT size.
+pub const ANALYZER_LEN: usize = 4096;
+
+/// Keeps the latest samples of the audio thread for the GUI thread to draw a spectrum from,
+/// without locking nor allocating on the audio thread.
+pub struct Analyzer {
+    samples: Box<[AtomicU64]>,
+    position: AtomicUsize,
+}
+
+impl Analyzer {
+    pub fn new() -> Self {
+        Self {
+            samples: (0..ANALYZER_LEN).map(|_| AtomicU64::new(0)).collect(),
+            position: AtomicUsize::new(0),
+        }
+    }
+
+    /// Audio thread.
+    pub fn push(&self, x: f64) {
+        let position = self.position.load(Ordering::Relaxed);
+        self.samples[position].store(x.to_bits(), Ordering::Relaxed);
+        self.position
+            .store((position + 1) % ANALYZER_LEN, Ordering::Release);
+    }
+
+    /// GUI thread. Magnitudes in dB of the Hann-windowed latest samples, from DC to Nyquist.
+    pub fn spectrum(&self, planner: &mut rustfft::FftPlanner<f64>) -> Vec<f64> {
+        let position = self.position.load(Ordering::Acquire);
+        let mut buffer = (0..ANALYZER_LEN)
+            .map(|i| {
+                let x = f64::from_bits(
+                    self.samples[(position + i) % ANALYZER_LEN].load(Ordering::Relaxed),
+                );
+                let window =
+                    0.5 - 0.5 * (std::f64::consts::TAU * i as f64 / ANALYZER_LEN as f64).cos();
+                Complex64::from(x * window)
+            })
+            .collect::<Vec<_>>();
+        planner.plan_fft_forward(ANALYZER_LEN).process(&mut buffer);
+        // The Hann window halves the amplitude.
+        let scale = 4.0 / ANALYZER_LEN as f64;
+        buffer[..=ANALYZER_LEN / 2]
+            .iter()
+            .map(|x| 20.0 * (x.norm() * scale).max(1e-10).log10())
+            .collect()
+    }
+}
+
+impl Default for Analyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,92 @@
+use corus_v2::{
+    contrib::benihora::BenihoraVoice, nodes::poly_synth::NoteHandler, signal::StereoF64,
+    ProcessContext, Producer,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{param_f64::ParamF64, param_pool::ParamPool};
+
+/// Upper bound of `BenihoraSettings::constrictions`; more are ignored.
+pub const MAX_CONSTRICTIONS: usize = 8;
+
+/// Settings of the benihora voice type.
+#[derive(Serialize, Deserialize)]
+pub struct BenihoraSettings {
+    /// Inverse of the tract length.
+    pub sound_speed: f64,
+    /// See `BenihoraVoice::vowel_to_tongue`.
+    pub vowel: (f64, f64),
+    /// `(index, diameter)` pairs.
+    pub constrictions: Vec<(f64, f64)>,
+    pub velum: f64,
+    /// Raised towards 1 by the modulation wheel.
+    pub tenseness: f64,
+    pub level: ParamF64,
+}
+
+pub struct BenihoraState {
+    voice: BenihoraVoice,
+    /// Last frequency sent to the voice.
+    frequency: f64,
+}
+
+impl BenihoraSettings {
+    pub fn new() -> Self {
+        Self {
+            sound_speed: 2.0,
+            vowel: (0.0, 0.3),
+            constrictions: vec![],
+            velum: 0.01,
+            tenseness: 0.6,
+            level: ParamF64::new(0.7),
+        }
+    }
+
+    /// The bits of `sound_speed`, which the state is built from.
+    pub fn state_key(&self) -> u64 {
+        self.sound_speed.to_bits()
+    }
+
+    /// Allocates. `key` is from `state_key`; `index` tells the voices of the `VoiceManager` apart.
+    /// The seeds sit between those of `Vocal`'s choirs.
+    pub fn build_state(key: u64, sample_rate: f64, index: usize) -> BenihoraState {
+        BenihoraState {
+            voice: BenihoraVoice::new(f64::from_bits(key), sample_rate, (index * 8 + 4) as u32),
+            frequency: 0.0,
+        }
+    }
+
+    pub fn note_on(&self, state: &mut BenihoraState, time: f64, frequency: f64, velocity: f64) {
+        state.frequency = frequency;
+        state.voice.note_on(time, (frequency, velocity));
+    }
+
+    pub fn note_off(&self, state: &mut BenihoraState, time: f64) {
+        state.voice.note_off(time, ());
+    }
+
+    pub fn process(
+        &self,
+        state: &mut BenihoraState,
+        ctx: &ProcessContext,
+        param_pools: &[&ParamPool; 2],
+        frequency: f64,
+        modulation: f64,
+    ) -> StereoF64 {
+        if state.frequency != frequency {
+            state.frequency = frequency;
+            state.voice.set_frequency(frequency);
+        }
+        state.voice.set_vowel(self.vowel.0, self.vowel.1);
+        let constrictions = &self.constrictions[..self.constrictions.len().min(MAX_CONSTRICTIONS)];
+        state.voice.set_constrictions(constrictions);
+        state.voice.set_velum(self.velum);
+        state
+            .voice
+            .set_tenseness(self.tenseness + (1.0 - self.tenseness) * modulation);
+
+        let x = state.voice.process(ctx);
+        let level = self.level.compute(param_pools).clamp(0.0, 1.0);
+        StereoF64::from(x * level)
+    }
+}
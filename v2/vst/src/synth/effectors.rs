@@ -1,21 +1,86 @@
 use corus_v2::{
     nodes::{
         biquad_filter::BiquadFilter,
+        convolver::Partitioning,
         effects::{
             chorus::Chorus,
             compressor::{Compressor, Param as CompressorParam},
+            convolution_reverb::{ConvolutionReverb, ImpulseResponse},
+            dynamics::{amp_to_db, Detection},
+            expander::{Expander, Param as ExpanderParam},
+            fdn_reverb::{FdnReverb, Matrix, Param as FdnReverbParam},
+            limiter::{Limiter, Param as LimiterParam},
+            multiband::Multiband,
+            parametric_eq::{Band, BandType, ParametricEq, Placement},
             phaser::Phaser,
-            DelayFx, EarlyReflections, SchroederReverb,
+            plate_reverb::{Param as PlateReverbParam, PlateReverb},
+            stereo_delay::{
+                beats_to_seconds, Param as StereoDelayParam, StereoDelay, FLUTTER_EXCURSION,
+                WOW_EXCURSION,
+            },
+            transient_shaper::{Param as TransientShaperParam, TransientShaper},
+            EarlyReflections, SchroederReverb,
         },
         mix::mix,
+        oversample::Oversample,
+        zdf_filter::{LadderFilter, Ms20Filter, StateVariableFilter},
     },
+    ring_buffer::{len_for_delay, RingBuffer},
     signal::StereoF64,
     ProcessContext,
 };
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use wavetables::shapers;
 
-use super::{param_f64, param_pool::ParamPool};
+use super::{analyzer::Analyzer, param_f64, param_pool::ParamPool};
+
+/// Taps per phase of the half-band filters used for oversampling.
+const OVERSAMPLE_TAPS: usize = 16;
+
+/// In seconds.
+const MAX_PRE_DELAY: f64 = 0.5;
+
+const MAX_DELAY_TIME: f64 = 2.0;
+const DUCK_RELEASE: f64 = 0.25;
+
+const LIMITER_LOOKAHEAD: f64 = 0.005;
+/// Of the compressors, in seconds.
+const MAX_LOOKAHEAD: f64 = 0.01;
+/// In dB.
+const MULTIBAND_KNEE: f64 = 6.0;
+
+/// Latency of the convolution reverb, which delays only the wet signal.
+const CONVOLUTION_BLOCK: usize = 128;
+const CONVOLUTION_MAX_BLOCK: usize = 8192;
+
+pub const EQ_BANDS: usize = 8;
+const EQ_PARAM_NAMES: [&str; EQ_BANDS * 3] = [
+    "1 frequency",
+    "1 gain",
+    "1 q",
+    "2 frequency",
+    "2 gain",
+    "2 q",
+    "3 frequency",
+    "3 gain",
+    "3 q",
+    "4 frequency",
+    "4 gain",
+    "4 q",
+    "5 frequency",
+    "5 gain",
+    "5 q",
+    "6 frequency",
+    "6 gain",
+    "6 q",
+    "7 frequency",
+    "7 gain",
+    "7 q",
+    "8 frequency",
+    "8 gain",
+    "8 q",
+];
 
 // pub enum MonoEffector {
 //     Filter { frequency: f64, q: f64 },
@@ -55,6 +120,60 @@ pub enum FilterType {
     Peaking,
     Notch,
     AllPass,
+    SvfLowPass,
+    SvfBandPass,
+    SvfHighPass,
+    SvfNotch,
+    Ladder,
+    Ms20,
+}
+
+/// Note length a delay syncs to.
+#[derive(Clone, Copy, PartialEq, PartialOrd, Eq, Serialize, Deserialize)]
+pub enum Division {
+    Whole,
+    Half,
+    Quarter,
+    Eighth,
+    Sixteenth,
+    DottedQuarter,
+    DottedEighth,
+    TripletQuarter,
+    TripletEighth,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct EqBand {
+    pub enabled: bool,
+    pub r#type: BandType,
+    pub placement: Placement,
+    pub frequency: param_f64::ParamF64,
+    pub gain: param_f64::ParamF64,
+    pub q: param_f64::ParamF64,
+}
+
+impl EqBand {
+    pub fn new(r#type: BandType, frequency: f64) -> Self {
+        Self {
+            enabled: false,
+            r#type,
+            placement: Placement::Stereo,
+            frequency: param_f64::ParamF64::new(frequency),
+            gain: param_f64::ParamF64::new(0.0),
+            q: param_f64::ParamF64::new(std::f64::consts::FRAC_1_SQRT_2),
+        }
+    }
+
+    fn compute(&self, param_pools: &[&ParamPool]) -> Band<f64> {
+        Band {
+            enabled: self.enabled,
+            r#type: self.r#type,
+            placement: self.placement,
+            frequency: self.frequency.compute(param_pools).clamp(20.0, 20000.0),
+            gain: self.gain.compute(param_pools),
+            q: self.q.compute(param_pools).max(0.1),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -67,28 +186,243 @@ pub enum Effector {
     },
     Phaser,
     Chorus,
-    Delay,
+    Delay {
+        /// `None` takes `time` in seconds.
+        sync: Option<Division>,
+        time: param_f64::ParamF64,
+        feedback: param_f64::ParamF64,
+        cross_feed: param_f64::ParamF64,
+        low_cut: param_f64::ParamF64,
+        high_cut: param_f64::ParamF64,
+        drive: param_f64::ParamF64,
+        wow: param_f64::ParamF64,
+        flutter: param_f64::ParamF64,
+        duck: param_f64::ParamF64,
+        mix: param_f64::ParamF64,
+        /// Added to the right channel's time, in seconds.
+        #[serde(default = "default_zero")]
+        offset: param_f64::ParamF64,
+    },
     Reverb,
+    FdnReverb {
+        pre_delay: param_f64::ParamF64,
+        size: param_f64::ParamF64,
+        rt60_low: param_f64::ParamF64,
+        rt60_high: param_f64::ParamF64,
+        crossover: param_f64::ParamF64,
+        modulation: param_f64::ParamF64,
+        mix: param_f64::ParamF64,
+        matrix: Matrix,
+    },
+    PlateReverb {
+        pre_delay: param_f64::ParamF64,
+        size: param_f64::ParamF64,
+        decay: param_f64::ParamF64,
+        damping: param_f64::ParamF64,
+        modulation: param_f64::ParamF64,
+        width: param_f64::ParamF64,
+        mix: param_f64::ParamF64,
+        freeze: bool,
+    },
+    Convolution {
+        /// WAV file of the impulse response.
+        path: String,
+        trim: f64, // in dB below the peak
+        normalize: bool,
+        pre_delay: f64, // in seconds
+        mix: param_f64::ParamF64,
+    },
     Gain {
         gain: param_f64::ParamF64,
     },
     Compressor {
         threshold: param_f64::ParamF64,
         ratio: param_f64::ParamF64,
+        knee: param_f64::ParamF64,
         attack: param_f64::ParamF64,
         release: param_f64::ParamF64,
-        gain: param_f64::ParamF64,
+        makeup: param_f64::ParamF64,
+        sidechain_low_cut: param_f64::ParamF64,
+        detection: Detection,
+        /// In seconds, delays the audio.
+        #[serde(default = "default_zero")]
+        lookahead: param_f64::ParamF64,
+        /// Keyed by the aux input on the master chain.
+        #[serde(default)]
+        sidechain: bool,
+    },
+    /// Downward expander, or a gate with a large ratio.
+    Expander {
+        threshold: param_f64::ParamF64,
+        ratio: param_f64::ParamF64,
+        knee: param_f64::ParamF64,
+        range: param_f64::ParamF64,
+        attack: param_f64::ParamF64,
+        release: param_f64::ParamF64,
+        sidechain_low_cut: param_f64::ParamF64,
+        detection: Detection,
+        /// Keyed by the aux input on the master chain.
+        sidechain: bool,
+    },
+    TransientShaper {
+        attack: param_f64::ParamF64,
+        sustain: param_f64::ParamF64,
+    },
+    Limiter {
+        ceiling: param_f64::ParamF64,
+        release: param_f64::ParamF64,
+    },
+    /// Three-band compressor split at `low_cross` and `high_cross`.
+    MultibandCompressor {
+        low_cross: param_f64::ParamF64,
+        high_cross: param_f64::ParamF64,
+        low_threshold: param_f64::ParamF64,
+        mid_threshold: param_f64::ParamF64,
+        high_threshold: param_f64::ParamF64,
+        ratio: param_f64::ParamF64,
+        attack: param_f64::ParamF64,
+        release: param_f64::ParamF64,
+        low_makeup: param_f64::ParamF64,
+        mid_makeup: param_f64::ParamF64,
+        high_makeup: param_f64::ParamF64,
+        /// In seconds, delays the audio.
+        #[serde(default = "default_zero")]
+        lookahead: param_f64::ParamF64,
+    },
+    ParametricEq {
+        bands: [EqBand; EQ_BANDS],
+        /// Fed with the output on the master chain.
+        #[serde(skip)]
+        analyzer: Arc<Analyzer>,
     },
     Tanh,
     Shaper {
         pre_gain: param_f64::ParamF64,
         r#type: ShaperType,
+        /// One of `OVERSAMPLING_RATIOS`.
+        #[serde(
+            default = "default_oversampling",
+            deserialize_with = "deserialize_oversampling"
+        )]
+        oversampling: usize,
+    },
+}
+
+/// Ratios `Oversample` supports.
+pub const OVERSAMPLING_RATIOS: [usize; 4] = [1, 2, 4, 8];
+
+fn default_oversampling() -> usize {
+    1
+}
+
+fn default_zero() -> param_f64::ParamF64 {
+    param_f64::ParamF64::new(0.0)
+}
+
+/// Rounds a ratio that `Oversample` doesn't support, as from an edited preset, down to one it does.
+fn deserialize_oversampling<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<usize, D::Error> {
+    let ratio = usize::deserialize(deserializer)?;
+    Ok(OVERSAMPLING_RATIOS
+        .into_iter()
+        .rev()
+        .find(|r| *r <= ratio)
+        .unwrap_or(1))
+}
+
+/// An `Effector` in a preset, which may have been saved by an older version.
+/// Only lives while a preset loads.
+#[allow(clippy::large_enum_variant)]
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SavedEffector {
+    Current(Effector),
+    Legacy(LegacyEffector),
+}
+
+/// Effectors whose shape has changed since.
+#[allow(clippy::large_enum_variant)]
+#[derive(Deserialize)]
+enum LegacyEffector {
+    /// A fixed feedback delay.
+    Delay,
+    /// Took linear levels and a ratio of 0.0 ~ 1.0, the slope above the threshold.
+    Compressor {
+        threshold: param_f64::ParamF64,
+        ratio: param_f64::ParamF64,
+        attack: param_f64::ParamF64,
+        release: param_f64::ParamF64,
+        gain: param_f64::ParamF64,
     },
 }
 
+impl From<SavedEffector> for Effector {
+    fn from(saved: SavedEffector) -> Self {
+        let legacy = match saved {
+            SavedEffector::Current(effector) => return effector,
+            SavedEffector::Legacy(legacy) => legacy,
+        };
+        let param = param_f64::ParamF64::new;
+        match legacy {
+            LegacyEffector::Delay => Effector::Delay {
+                sync: None,
+                time: param(0.5),
+                feedback: param(0.3),
+                cross_feed: param(0.0),
+                low_cut: param(0.0),
+                high_cut: param(6000.0),
+                drive: param(0.0),
+                wow: param(0.0),
+                flutter: param(0.0),
+                duck: param(0.0),
+                mix: param(0.5),
+                offset: param(0.0),
+            },
+            LegacyEffector::Compressor {
+                mut threshold,
+                mut ratio,
+                attack,
+                release,
+                gain: mut makeup,
+            } => {
+                threshold.value = amp_to_db(threshold.value);
+                ratio.value = 1.0 / ratio.value.clamp(0.01, 1.0);
+                makeup.value = amp_to_db(makeup.value);
+                Effector::Compressor {
+                    threshold,
+                    ratio,
+                    knee: param(0.0),
+                    attack,
+                    release,
+                    makeup,
+                    sidechain_low_cut: param(0.0),
+                    detection: Detection::Peak,
+                    lookahead: param(0.0),
+                    sidechain: false,
+                }
+            }
+        }
+    }
+}
+
+/// Migrates the effectors of presets saved by older versions.
+pub fn deserialize_effectors<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<(bool, Effector)>, D::Error> {
+    let effectors = Vec::<(bool, SavedEffector)>::deserialize(deserializer)?;
+    Ok(effectors
+        .into_iter()
+        .map(|(enabled, effector)| (enabled, effector.into()))
+        .collect())
+}
+
 pub enum State {
     Filter {
         filter: BiquadFilter<2, StereoF64>,
+        svf: StateVariableFilter<StereoF64>,
+        ladder: LadderFilter<StereoF64>,
+        ms20: Ms20Filter<StereoF64>,
     },
     Phaser {
         phaser: Phaser<StereoF64>,
@@ -97,21 +431,77 @@ pub enum State {
         chorus: Chorus<StereoF64>,
     },
     Delay {
-        delay: DelayFx<StereoF64>,
+        delay: StereoDelay<StereoF64>,
     },
     Reverb {
         reverb: SchroederReverb<StereoF64>,
         er: EarlyReflections<StereoF64>,
     },
+    FdnReverb {
+        reverb: FdnReverb<StereoF64>,
+    },
+    PlateReverb {
+        reverb: PlateReverb<StereoF64>,
+    },
+    Convolution {
+        /// `None` when the impulse response failed to load.
+        reverb: Option<ConvolutionReverb<f64>>,
+        pre_delay: RingBuffer<StereoF64>,
+    },
     Gain,
     Compressor {
-        compressor: Compressor<f64>,
+        compressor: Compressor<2, f64>,
+    },
+    Expander {
+        expander: Expander<2, f64>,
+    },
+    TransientShaper {
+        shaper: TransientShaper<2, f64>,
+    },
+    Limiter {
+        limiter: Limiter<2, f64>,
+    },
+    MultibandCompressor {
+        multiband: Multiband<StereoF64, Compressor<2, f64>>,
+    },
+    ParametricEq {
+        eq: ParametricEq<f64>,
     },
     Tanh,
-    Shaper,
+    Shaper {
+        oversample: Oversample<StereoF64>,
+    },
     None,
 }
 
+/// What an effector's state is built from. Effectors with equal keys can share a state.
+#[derive(Clone, PartialEq)]
+pub enum StateKey {
+    Filter,
+    Phaser,
+    Chorus,
+    Delay,
+    Reverb,
+    FdnReverb,
+    PlateReverb,
+    Convolution {
+        path: String,
+        trim: f64,
+        normalize: bool,
+    },
+    Gain,
+    Compressor,
+    Expander,
+    TransientShaper,
+    Limiter,
+    MultibandCompressor,
+    ParametricEq,
+    Tanh,
+    Shaper {
+        oversampling: usize,
+    },
+}
+
 impl Effector {
     pub fn name(&self) -> &'static str {
         match self {
@@ -120,8 +510,16 @@ impl Effector {
             Effector::Chorus { .. } => "Chorus",
             Effector::Delay { .. } => "Delay",
             Effector::Reverb { .. } => "Reverb",
+            Effector::FdnReverb { .. } => "FDN Reverb",
+            Effector::PlateReverb { .. } => "Plate Reverb",
+            Effector::Convolution { .. } => "Convolution",
             Effector::Gain { .. } => "Gain",
             Effector::Compressor { .. } => "Compressor",
+            Effector::Expander { .. } => "Expander",
+            Effector::TransientShaper { .. } => "Transient Shaper",
+            Effector::Limiter { .. } => "Limiter",
+            Effector::MultibandCompressor { .. } => "Multiband Compressor",
+            Effector::ParametricEq { .. } => "Parametric EQ",
             Effector::Tanh => "Tanh",
             Effector::Shaper { .. } => "Shaper",
         }
@@ -132,37 +530,261 @@ impl Effector {
             Effector::Filter { .. } => &["frequency", "q"],
             Effector::Phaser { .. } => &[],
             Effector::Chorus { .. } => &[],
-            Effector::Delay { .. } => &[],
+            Effector::Delay { .. } => &[
+                "time",
+                "feedback",
+                "cross_feed",
+                "low_cut",
+                "high_cut",
+                "drive",
+                "wow",
+                "flutter",
+                "duck",
+                "mix",
+                "offset",
+            ],
             Effector::Reverb { .. } => &[],
+            Effector::FdnReverb { .. } => &[
+                "pre_delay",
+                "size",
+                "rt60_low",
+                "rt60_high",
+                "crossover",
+                "modulation",
+                "mix",
+            ],
+            Effector::PlateReverb { .. } => &[
+                "pre_delay",
+                "size",
+                "decay",
+                "damping",
+                "modulation",
+                "width",
+                "mix",
+            ],
+            Effector::Convolution { .. } => &["mix"],
             Effector::Gain { .. } => &["gain"],
-            Effector::Compressor { .. } => &["threshold", "ratio", "attack", "release", "gain"],
+            Effector::Compressor { .. } => &[
+                "threshold",
+                "ratio",
+                "knee",
+                "attack",
+                "release",
+                "makeup",
+                "sidechain_low_cut",
+                "lookahead",
+            ],
+            Effector::Expander { .. } => &[
+                "threshold",
+                "ratio",
+                "knee",
+                "range",
+                "attack",
+                "release",
+                "sidechain_low_cut",
+            ],
+            Effector::TransientShaper { .. } => &["attack", "sustain"],
+            Effector::Limiter { .. } => &["ceiling", "release"],
+            Effector::MultibandCompressor { .. } => &[
+                "low_cross",
+                "high_cross",
+                "low_threshold",
+                "mid_threshold",
+                "high_threshold",
+                "ratio",
+                "attack",
+                "release",
+                "low_makeup",
+                "mid_makeup",
+                "high_makeup",
+                "lookahead",
+            ],
+            Effector::ParametricEq { .. } => &EQ_PARAM_NAMES,
             Effector::Tanh => &[],
             Effector::Shaper { .. } => &["pre_gain"],
         }
     }
 
-    pub fn param_muts<'a>(&'a mut self) -> Vec<&'a mut param_f64::ParamF64> {
+    /// Allocates for `Convolution`.
+    pub fn state_key(&self) -> StateKey {
+        match self {
+            Effector::Filter { .. } => StateKey::Filter,
+            Effector::Phaser => StateKey::Phaser,
+            Effector::Chorus => StateKey::Chorus,
+            Effector::Delay { .. } => StateKey::Delay,
+            Effector::Reverb => StateKey::Reverb,
+            Effector::FdnReverb { .. } => StateKey::FdnReverb,
+            Effector::PlateReverb { .. } => StateKey::PlateReverb,
+            Effector::Convolution {
+                path,
+                trim,
+                normalize,
+                ..
+            } => StateKey::Convolution {
+                path: path.clone(),
+                trim: *trim,
+                normalize: *normalize,
+            },
+            Effector::Gain { .. } => StateKey::Gain,
+            Effector::Compressor { .. } => StateKey::Compressor,
+            Effector::Expander { .. } => StateKey::Expander,
+            Effector::TransientShaper { .. } => StateKey::TransientShaper,
+            Effector::Limiter { .. } => StateKey::Limiter,
+            Effector::MultibandCompressor { .. } => StateKey::MultibandCompressor,
+            Effector::ParametricEq { .. } => StateKey::ParametricEq,
+            Effector::Tanh => StateKey::Tanh,
+            Effector::Shaper { oversampling, .. } => StateKey::Shaper {
+                oversampling: *oversampling,
+            },
+        }
+    }
+
+    pub fn param_muts(&mut self) -> Vec<&mut param_f64::ParamF64> {
+        let mut params = vec![];
+        self.for_each_param_mut(|param| params.push(param));
+        params
+    }
+
+    /// Visits the params without allocating.
+    pub fn for_each_param_mut<'a>(&'a mut self, f: impl FnMut(&'a mut param_f64::ParamF64)) {
         match self {
             Effector::Filter {
                 filter_type: _,
                 frequency,
                 q,
                 gain,
-            } => vec![frequency, q, gain],
-            Effector::Phaser { .. } => vec![],
-            Effector::Chorus { .. } => vec![],
-            Effector::Delay { .. } => vec![],
-            Effector::Reverb { .. } => vec![],
-            Effector::Gain { gain } => vec![gain],
+            } => [frequency, q, gain].into_iter().for_each(f),
+            Effector::Phaser { .. } => {}
+            Effector::Chorus { .. } => {}
+            Effector::Delay {
+                sync: _,
+                time,
+                feedback,
+                cross_feed,
+                low_cut,
+                high_cut,
+                drive,
+                wow,
+                flutter,
+                duck,
+                mix,
+                offset,
+            } => [
+                time, feedback, cross_feed, low_cut, high_cut, drive, wow, flutter, duck, mix,
+                offset,
+            ]
+            .into_iter()
+            .for_each(f),
+            Effector::Reverb { .. } => {}
+            Effector::FdnReverb {
+                pre_delay,
+                size,
+                rt60_low,
+                rt60_high,
+                crossover,
+                modulation,
+                mix,
+                ..
+            } => [
+                pre_delay, size, rt60_low, rt60_high, crossover, modulation, mix,
+            ]
+            .into_iter()
+            .for_each(f),
+            Effector::PlateReverb {
+                pre_delay,
+                size,
+                decay,
+                damping,
+                modulation,
+                width,
+                mix,
+                ..
+            } => [pre_delay, size, decay, damping, modulation, width, mix]
+                .into_iter()
+                .for_each(f),
+            Effector::Convolution { mix, .. } => [mix].into_iter().for_each(f),
+            Effector::Gain { gain } => [gain].into_iter().for_each(f),
             Effector::Compressor {
                 threshold,
                 ratio,
+                knee,
                 attack,
                 release,
-                gain,
-            } => vec![threshold, ratio, attack, release, gain],
-            Effector::Tanh => vec![],
-            Effector::Shaper { pre_gain, .. } => vec![pre_gain],
+                makeup,
+                sidechain_low_cut,
+                lookahead,
+                ..
+            } => [
+                threshold,
+                ratio,
+                knee,
+                attack,
+                release,
+                makeup,
+                sidechain_low_cut,
+                lookahead,
+            ]
+            .into_iter()
+            .for_each(f),
+            Effector::Expander {
+                threshold,
+                ratio,
+                knee,
+                range,
+                attack,
+                release,
+                sidechain_low_cut,
+                ..
+            } => [
+                threshold,
+                ratio,
+                knee,
+                range,
+                attack,
+                release,
+                sidechain_low_cut,
+            ]
+            .into_iter()
+            .for_each(f),
+            Effector::TransientShaper { attack, sustain } => {
+                [attack, sustain].into_iter().for_each(f)
+            }
+            Effector::Limiter { ceiling, release } => [ceiling, release].into_iter().for_each(f),
+            Effector::MultibandCompressor {
+                low_cross,
+                high_cross,
+                low_threshold,
+                mid_threshold,
+                high_threshold,
+                ratio,
+                attack,
+                release,
+                low_makeup,
+                mid_makeup,
+                high_makeup,
+                lookahead,
+            } => [
+                low_cross,
+                high_cross,
+                low_threshold,
+                mid_threshold,
+                high_threshold,
+                ratio,
+                attack,
+                release,
+                low_makeup,
+                mid_makeup,
+                high_makeup,
+                lookahead,
+            ]
+            .into_iter()
+            .for_each(f),
+            Effector::ParametricEq { bands, .. } => bands
+                .iter_mut()
+                .flat_map(|band| [&mut band.frequency, &mut band.gain, &mut band.q])
+                .for_each(f),
+            Effector::Tanh => {}
+            Effector::Shaper { pre_gain, .. } => [pre_gain].into_iter().for_each(f),
         }
     }
 
@@ -171,6 +793,8 @@ impl Effector {
         state: &mut State,
         ctx: &ProcessContext,
         param_pools: &[&ParamPool],
+        tempo: f64,
+        sidechain: Option<StereoF64>,
         x: StereoF64,
     ) -> StereoF64 {
         match (self, state) {
@@ -181,8 +805,17 @@ impl Effector {
                     q,
                     gain,
                 },
-                State::Filter { filter },
+                State::Filter {
+                    filter,
+                    svf,
+                    ladder,
+                    ms20,
+                },
             ) => {
+                let frequency = frequency.compute(param_pools).clamp(20.0, 20000.0);
+                let q = q.compute(param_pools);
+                // Resonant filters take `q` as resonance and `gain` as drive in dB.
+                let drive = || 10.0f64.powf(gain.compute(param_pools) / 20.0);
                 let t = match filter_type {
                     FilterType::LowPass => corus_v2::nodes::biquad_filter::FilterType::LowPass,
                     FilterType::HighPass => corus_v2::nodes::biquad_filter::FilterType::HighPass,
@@ -198,117 +831,305 @@ impl Effector {
                     ),
                     FilterType::Notch => corus_v2::nodes::biquad_filter::FilterType::Notch,
                     FilterType::AllPass => corus_v2::nodes::biquad_filter::FilterType::AllPass,
+                    FilterType::SvfLowPass => return svf.process(ctx, frequency, q, x).low,
+                    FilterType::SvfBandPass => return svf.process(ctx, frequency, q, x).band,
+                    FilterType::SvfHighPass => return svf.process(ctx, frequency, q, x).high,
+                    FilterType::SvfNotch => return svf.process(ctx, frequency, q, x).notch,
+                    FilterType::Ladder => return ladder.process(ctx, frequency, q, drive(), x),
+                    FilterType::Ms20 => return ms20.process(ctx, frequency, q, drive(), x),
                 };
-                filter.update_coefficients(
-                    ctx,
-                    t,
-                    frequency.compute(param_pools).clamp(20.0, 20000.0),
-                    q.compute(param_pools),
-                );
+                filter.update_coefficients(ctx, t, frequency, q);
                 filter.process(x)
             }
             (Effector::Phaser, State::Phaser { phaser }) => phaser.process(ctx, x),
             (Effector::Chorus, State::Chorus { chorus }) => chorus.process(ctx, 0.001, 0.001, x),
-            (Effector::Delay, State::Delay { delay }) => delay.process(ctx, x, 0.5, 0.3, 0.2),
+            (
+                Effector::Delay {
+                    sync,
+                    time,
+                    feedback,
+                    cross_feed,
+                    low_cut,
+                    high_cut,
+                    drive,
+                    wow,
+                    flutter,
+                    duck,
+                    mix,
+                    offset,
+                },
+                State::Delay { delay },
+            ) => {
+                let time = match sync {
+                    Some(division) => beats_to_seconds(division.beats(), tempo),
+                    None => time.compute(param_pools),
+                }
+                .clamp(0.001, MAX_DELAY_TIME);
+                let time_r = (time + offset.compute(param_pools)).clamp(0.001, MAX_DELAY_TIME);
+                let mix = mix.compute(param_pools).clamp(0.0, 1.0);
+                let y = delay.process(
+                    &StereoDelayParam {
+                        time_l: time,
+                        time_r,
+                        feedback: feedback.compute(param_pools).clamp(0.0, 1.0),
+                        cross_feed: cross_feed.compute(param_pools).clamp(0.0, 1.0),
+                        low_cut: low_cut.compute(param_pools).clamp(0.0, 20000.0),
+                        high_cut: high_cut.compute(param_pools).clamp(20.0, 20000.0),
+                        drive: drive.compute(param_pools).max(0.0),
+                        wow: wow.compute(param_pools),
+                        flutter: flutter.compute(param_pools),
+                        duck: duck.compute(param_pools).clamp(0.0, 1.0),
+                        duck_release: DUCK_RELEASE,
+                    },
+                    ctx,
+                    x,
+                );
+                x * (1.0 - mix) + y * mix
+            }
             (Effector::Reverb, State::Reverb { reverb, er }) => mix(&[
                 (0.8, x),
                 (0.3, er.process(ctx, x)),
                 (0.2, reverb.process(ctx, x)),
             ]),
+            (
+                Effector::FdnReverb {
+                    pre_delay,
+                    size,
+                    rt60_low,
+                    rt60_high,
+                    crossover,
+                    modulation,
+                    mix,
+                    matrix,
+                },
+                State::FdnReverb { reverb },
+            ) => {
+                let mix = mix.compute(param_pools).clamp(0.0, 1.0);
+                let y = reverb.process(
+                    &FdnReverbParam {
+                        pre_delay: pre_delay.compute(param_pools).clamp(0.0, MAX_PRE_DELAY),
+                        size: size.compute(param_pools).clamp(0.5, 2.0),
+                        rt60_low: rt60_low.compute(param_pools),
+                        rt60_high: rt60_high.compute(param_pools),
+                        crossover: crossover.compute(param_pools).clamp(20.0, 20000.0),
+                        modulation: modulation.compute(param_pools),
+                        matrix: *matrix,
+                    },
+                    ctx,
+                    x,
+                );
+                x * (1.0 - mix) + y * mix
+            }
+            (
+                Effector::PlateReverb {
+                    pre_delay,
+                    size,
+                    decay,
+                    damping,
+                    modulation,
+                    width,
+                    mix,
+                    freeze,
+                },
+                State::PlateReverb { reverb },
+            ) => {
+                let mix = mix.compute(param_pools).clamp(0.0, 1.0);
+                let y = reverb.process(
+                    &PlateReverbParam {
+                        pre_delay: pre_delay.compute(param_pools).clamp(0.0, MAX_PRE_DELAY),
+                        size: size.compute(param_pools).clamp(0.5, 2.0),
+                        decay: decay.compute(param_pools).clamp(0.0, 0.99),
+                        damping: damping.compute(param_pools).clamp(0.0, 1.0),
+                        modulation: modulation.compute(param_pools),
+                        width: width.compute(param_pools),
+                        freeze: *freeze,
+                    },
+                    ctx,
+                    x,
+                );
+                x * (1.0 - mix) + y * mix
+            }
+            (
+                Effector::Convolution { pre_delay, mix, .. },
+                State::Convolution {
+                    reverb,
+                    pre_delay: buffer,
+                },
+            ) => {
+                let Some(reverb) = reverb else {
+                    return x;
+                };
+                buffer.push(x);
+                let y = reverb.process(buffer.tap(ctx, pre_delay.clamp(0.0, MAX_PRE_DELAY)));
+                let mix = mix.compute(param_pools).clamp(0.0, 1.0);
+                x * (1.0 - mix) + y * mix
+            }
             (Effector::Gain { gain }, State::Gain) => x * gain.compute(param_pools),
             (
                 Effector::Compressor {
                     threshold,
                     ratio,
+                    knee,
                     attack,
                     release,
-                    gain,
+                    makeup,
+                    sidechain_low_cut,
+                    detection,
+                    lookahead,
+                    sidechain: keyed,
                 },
                 State::Compressor { compressor },
-            ) => {
-                let threshold = threshold.compute(param_pools);
-                let ratio = ratio.compute(param_pools);
-                let attack = attack.compute(param_pools);
-                let release = release.compute(param_pools);
-                let gain = gain.compute(param_pools);
-                compressor.process(
-                    &CompressorParam {
-                        threshold,
-                        ratio,
-                        attack,
-                        release,
-                        gain,
+            ) => compressor.process_sidechain(
+                &CompressorParam {
+                    threshold: threshold.compute(param_pools),
+                    ratio: ratio.compute(param_pools).max(1.0),
+                    knee: knee.compute(param_pools).max(0.0),
+                    attack: attack.compute(param_pools).max(0.0),
+                    release: release.compute(param_pools).max(0.0),
+                    makeup: makeup.compute(param_pools),
+                    lookahead: lookahead.compute(param_pools).clamp(0.0, MAX_LOOKAHEAD),
+                    sidechain_low_cut: sidechain_low_cut.compute(param_pools).max(0.0),
+                    detection: *detection,
+                },
+                ctx,
+                x,
+                sidechain.filter(|_| *keyed).unwrap_or(x),
+            ),
+            (
+                Effector::Expander {
+                    threshold,
+                    ratio,
+                    knee,
+                    range,
+                    attack,
+                    release,
+                    sidechain_low_cut,
+                    detection,
+                    sidechain: keyed,
+                },
+                State::Expander { expander },
+            ) => expander.process_sidechain(
+                &ExpanderParam {
+                    threshold: threshold.compute(param_pools),
+                    ratio: ratio.compute(param_pools).max(1.0),
+                    knee: knee.compute(param_pools).max(0.0),
+                    range: range.compute(param_pools).max(0.0),
+                    attack: attack.compute(param_pools).max(0.0),
+                    release: release.compute(param_pools).max(0.0),
+                    sidechain_low_cut: sidechain_low_cut.compute(param_pools).max(0.0),
+                    detection: *detection,
+                },
+                ctx,
+                x,
+                sidechain.filter(|_| *keyed).unwrap_or(x),
+            ),
+            (Effector::TransientShaper { attack, sustain }, State::TransientShaper { shaper }) => {
+                shaper.process(
+                    &TransientShaperParam {
+                        attack: attack.compute(param_pools).clamp(-1.0, 1.0),
+                        sustain: sustain.compute(param_pools).clamp(-1.0, 1.0),
                     },
                     ctx,
                     x,
                 )
             }
+            (Effector::Limiter { ceiling, release }, State::Limiter { limiter }) => limiter
+                .process(
+                    &LimiterParam {
+                        ceiling: ceiling.compute(param_pools).min(0.0),
+                        release: release.compute(param_pools).max(0.0),
+                    },
+                    ctx,
+                    x,
+                ),
+            (
+                Effector::MultibandCompressor {
+                    low_cross,
+                    high_cross,
+                    low_threshold,
+                    mid_threshold,
+                    high_threshold,
+                    ratio,
+                    attack,
+                    release,
+                    low_makeup,
+                    mid_makeup,
+                    high_makeup,
+                    lookahead,
+                },
+                State::MultibandCompressor { multiband },
+            ) => {
+                let low_cross = low_cross.compute(param_pools).clamp(20.0, 20000.0);
+                let high_cross = high_cross.compute(param_pools).clamp(low_cross, 20000.0);
+                let thresholds = [low_threshold, mid_threshold, high_threshold];
+                let makeups = [low_makeup, mid_makeup, high_makeup];
+                let ratio = ratio.compute(param_pools).max(1.0);
+                let attack = attack.compute(param_pools).max(0.0);
+                let release = release.compute(param_pools).max(0.0);
+                let lookahead = lookahead.compute(param_pools).clamp(0.0, MAX_LOOKAHEAD);
+                multiband.process(ctx, &[low_cross, high_cross], x, |i, compressor, x| {
+                    compressor.process(
+                        &CompressorParam {
+                            threshold: thresholds[i].compute(param_pools),
+                            ratio,
+                            knee: MULTIBAND_KNEE,
+                            attack,
+                            release,
+                            makeup: makeups[i].compute(param_pools),
+                            lookahead,
+                            sidechain_low_cut: 0.0,
+                            detection: Detection::Rms,
+                        },
+                        ctx,
+                        x,
+                    )
+                })
+            }
+            (Effector::ParametricEq { bands, .. }, State::ParametricEq { eq }) => {
+                let bands: [Band<f64>; EQ_BANDS] =
+                    std::array::from_fn(|i| bands[i].compute(param_pools));
+                eq.process(ctx, &bands, x)
+            }
             (Effector::Tanh, State::Tanh) => x.map(|x| x.tanh()),
-            (Effector::Shaper { pre_gain, r#type }, State::Shaper) => {
+            (
+                Effector::Shaper {
+                    pre_gain, r#type, ..
+                },
+                State::Shaper { oversample },
+            ) => {
                 let pre_gain = pre_gain.compute(param_pools);
-                let x = x * pre_gain;
-                match r#type {
-                    ShaperType::HardClip => x.map(|x| shapers::hard_clip(x)),
-                    ShaperType::Tanh => x.map(|x| shapers::tanh(x)),
-                    ShaperType::Sin => x.map(|x| shapers::sin(x)),
-                    ShaperType::Wrap => x.map(|x| shapers::wrap(x)),
-                    ShaperType::Triangle => x.map(|x| shapers::triangle(x)),
-                }
+                oversample.process(ctx, x * pre_gain, |_, x| r#type.process(x))
             }
-            _ => unreachable!("invalid state"),
+            // The state is rebuilt off the audio thread; bypass until it arrives.
+            _ => x,
         }
     }
+}
 
-    pub fn ensure_state(&self, state: &mut State) {
-        match (self, state) {
-            (Effector::Filter { .. }, State::Filter { .. }) => {}
-            (Effector::Phaser, State::Phaser { .. }) => {}
-            (Effector::Chorus, State::Chorus { .. }) => {}
-            (Effector::Delay, State::Delay { .. }) => {}
-            (Effector::Reverb, State::Reverb { .. }) => {}
-            (Effector::Gain { .. }, State::Gain) => {}
-            (Effector::Compressor { .. }, State::Compressor { .. }) => {}
-            (Effector::Tanh, State::Tanh) => {}
-            (Effector::Filter { .. }, state) => {
-                *state = State::Filter {
-                    filter: BiquadFilter::new(),
-                }
-            }
-            (Effector::Phaser, state) => {
-                *state = State::Phaser {
-                    phaser: Phaser::new(),
-                }
-            }
-            (Effector::Chorus, state) => {
-                *state = State::Chorus {
-                    chorus: Chorus::new(),
-                }
-            }
-            (Effector::Delay, state) => {
-                *state = State::Delay {
-                    delay: DelayFx::new(48000),
-                }
-            }
-            (Effector::Reverb, state) => {
-                *state = State::Reverb {
-                    reverb: SchroederReverb::new(48000),
-                    er: EarlyReflections::new(),
-                }
-            }
-            (Effector::Gain { .. }, state) => {
-                *state = State::Gain;
-            }
-            (Effector::Compressor { .. }, state) => {
-                *state = State::Compressor {
-                    compressor: Compressor::new(),
-                }
-            }
-            (Effector::Tanh, state) => {
-                *state = State::Tanh;
-            }
-            (Effector::Shaper { .. }, state) => {
-                *state = State::Shaper;
-            }
+impl Effector {
+    /// Hands the output of the master chain to the analyzer, if any.
+    pub fn analyze(&self, x: StereoF64) {
+        if let Effector::ParametricEq { analyzer, .. } = self {
+            analyzer.push((x[0] + x[1]) * 0.5);
+        }
+    }
+}
+
+impl State {
+    /// Latency in samples at the base rate.
+    pub fn latency(&self) -> f64 {
+        match self {
+            State::Shaper { oversample } => oversample.latency(),
+            State::Limiter { limiter } => limiter.latency() as f64,
+            State::Compressor { compressor } => compressor.latency() as f64,
+            // The bands share the lookahead.
+            State::MultibandCompressor { multiband } => multiband
+                .bands()
+                .iter()
+                .map(|compressor| compressor.latency())
+                .max()
+                .unwrap_or_default() as f64,
+            _ => 0.0,
         }
     }
 }
@@ -319,6 +1140,169 @@ impl Default for State {
     }
 }
 
+/// Impulse responses by path, resampled to the sample rate. `None` for files that failed to load.
+pub type ImpulseResponses = Vec<(String, Option<Arc<ImpulseResponse>>)>;
+
+/// Takes the impulse responses of `keys` from `loaded`, and loads the others once per path.
+pub fn load_impulse_responses<'a>(
+    keys: impl Iterator<Item = &'a StateKey>,
+    loaded: &[(String, Option<Arc<ImpulseResponse>>)],
+    sample_rate: f64,
+) -> ImpulseResponses {
+    let mut impulse_responses: ImpulseResponses = vec![];
+    for key in keys {
+        let StateKey::Convolution { path, .. } = key else {
+            continue;
+        };
+        if impulse_responses.iter().any(|(p, _)| p == path) {
+            continue;
+        }
+        let ir = match loaded.iter().find(|(p, _)| p == path) {
+            Some((_, ir)) => ir.clone(),
+            None if path.is_empty() => None,
+            None => match ImpulseResponse::from_wav(path) {
+                Ok(ir) => Some(Arc::new(ir.resample(sample_rate))),
+                Err(err) => {
+                    nih_plug::nih_log!("failed to load {}: {}", path, err);
+                    None
+                }
+            },
+        };
+        impulse_responses.push((path.clone(), ir));
+    }
+    impulse_responses
+}
+
+impl StateKey {
+    /// Same as `*self == effector.state_key()` but doesn't allocate.
+    pub fn matches(&self, effector: &Effector) -> bool {
+        match (self, effector) {
+            (
+                StateKey::Convolution {
+                    path,
+                    trim,
+                    normalize,
+                },
+                Effector::Convolution {
+                    path: p,
+                    trim: t,
+                    normalize: n,
+                    ..
+                },
+            ) => path == p && trim == t && normalize == n,
+            (
+                StateKey::Shaper { oversampling },
+                Effector::Shaper {
+                    oversampling: o, ..
+                },
+            ) => oversampling == o,
+            (StateKey::Filter, Effector::Filter { .. })
+            | (StateKey::Phaser, Effector::Phaser)
+            | (StateKey::Chorus, Effector::Chorus)
+            | (StateKey::Delay, Effector::Delay { .. })
+            | (StateKey::Reverb, Effector::Reverb)
+            | (StateKey::FdnReverb, Effector::FdnReverb { .. })
+            | (StateKey::PlateReverb, Effector::PlateReverb { .. })
+            | (StateKey::Gain, Effector::Gain { .. })
+            | (StateKey::Compressor, Effector::Compressor { .. })
+            | (StateKey::Expander, Effector::Expander { .. })
+            | (StateKey::TransientShaper, Effector::TransientShaper { .. })
+            | (StateKey::Limiter, Effector::Limiter { .. })
+            | (StateKey::MultibandCompressor, Effector::MultibandCompressor { .. })
+            | (StateKey::ParametricEq, Effector::ParametricEq { .. })
+            | (StateKey::Tanh, Effector::Tanh) => true,
+            _ => false,
+        }
+    }
+
+    /// Allocates. `Convolution` takes its impulse response from `impulse_responses`.
+    pub fn build(&self, sample_rate: f64, impulse_responses: &ImpulseResponses) -> State {
+        let lookahead_len = (MAX_LOOKAHEAD * sample_rate).ceil() as usize;
+        match self {
+            StateKey::Filter => State::Filter {
+                filter: BiquadFilter::new(),
+                svf: StateVariableFilter::new(),
+                ladder: LadderFilter::new(),
+                ms20: Ms20Filter::new(),
+            },
+            StateKey::Phaser => State::Phaser {
+                phaser: Phaser::new(),
+            },
+            StateKey::Chorus => State::Chorus {
+                chorus: Chorus::new(),
+            },
+            StateKey::Delay => State::Delay {
+                delay: StereoDelay::new(len_for_delay(
+                    MAX_DELAY_TIME + WOW_EXCURSION + FLUTTER_EXCURSION,
+                    sample_rate,
+                )),
+            },
+            StateKey::Reverb => State::Reverb {
+                reverb: SchroederReverb::new(48000),
+                er: EarlyReflections::new(),
+            },
+            StateKey::FdnReverb => State::FdnReverb {
+                reverb: FdnReverb::new(sample_rate, MAX_PRE_DELAY),
+            },
+            StateKey::PlateReverb => State::PlateReverb {
+                reverb: PlateReverb::new(sample_rate, MAX_PRE_DELAY),
+            },
+            StateKey::Convolution {
+                path,
+                trim,
+                normalize,
+            } => {
+                let reverb = impulse_responses
+                    .iter()
+                    .find(|(p, _)| p == path)
+                    .and_then(|(_, ir)| ir.as_deref())
+                    .map(|ir| {
+                        let mut ir = ir.clone();
+                        ir.trim(*trim);
+                        if *normalize {
+                            ir.normalize();
+                        }
+                        ConvolutionReverb::new(
+                            &ir,
+                            sample_rate,
+                            CONVOLUTION_BLOCK,
+                            Partitioning::NonUniform {
+                                max_block: CONVOLUTION_MAX_BLOCK,
+                            },
+                        )
+                    });
+                State::Convolution {
+                    reverb,
+                    pre_delay: RingBuffer::new(len_for_delay(MAX_PRE_DELAY, sample_rate)),
+                }
+            }
+            StateKey::Gain => State::Gain,
+            StateKey::Compressor => State::Compressor {
+                compressor: Compressor::new(lookahead_len),
+            },
+            StateKey::Expander => State::Expander {
+                expander: Expander::new(),
+            },
+            StateKey::TransientShaper => State::TransientShaper {
+                shaper: TransientShaper::new(),
+            },
+            StateKey::Limiter => State::Limiter {
+                limiter: Limiter::new((LIMITER_LOOKAHEAD * sample_rate).round() as usize),
+            },
+            StateKey::MultibandCompressor => State::MultibandCompressor {
+                multiband: Multiband::new((0..3).map(|_| Compressor::new(lookahead_len)).collect()),
+            },
+            StateKey::ParametricEq => State::ParametricEq {
+                eq: ParametricEq::new(EQ_BANDS),
+            },
+            StateKey::Tanh => State::Tanh,
+            StateKey::Shaper { oversampling } => State::Shaper {
+                oversample: Oversample::new(*oversampling, OVERSAMPLE_TAPS),
+            },
+        }
+    }
+}
+
 impl ShaperType {
     pub const ALL: [ShaperType; 5] = [
         ShaperType::HardClip,
@@ -328,6 +1312,16 @@ impl ShaperType {
         ShaperType::Triangle,
     ];
 
+    pub fn process(&self, x: StereoF64) -> StereoF64 {
+        match self {
+            ShaperType::HardClip => x.map(|x| shapers::hard_clip(x)),
+            ShaperType::Tanh => x.map(|x| shapers::tanh(x)),
+            ShaperType::Sin => x.map(|x| shapers::sin(x)),
+            ShaperType::Wrap => x.map(|x| shapers::wrap(x)),
+            ShaperType::Triangle => x.map(|x| shapers::triangle(x)),
+        }
+    }
+
     pub fn name(&self) -> &'static str {
         match self {
             ShaperType::HardClip => "HardClip",
@@ -339,8 +1333,50 @@ impl ShaperType {
     }
 }
 
+impl Division {
+    pub const ALL: [Division; 9] = [
+        Division::Whole,
+        Division::Half,
+        Division::Quarter,
+        Division::Eighth,
+        Division::Sixteenth,
+        Division::DottedQuarter,
+        Division::DottedEighth,
+        Division::TripletQuarter,
+        Division::TripletEighth,
+    ];
+
+    pub fn beats(&self) -> f64 {
+        match self {
+            Division::Whole => 4.0,
+            Division::Half => 2.0,
+            Division::Quarter => 1.0,
+            Division::Eighth => 0.5,
+            Division::Sixteenth => 0.25,
+            Division::DottedQuarter => 1.5,
+            Division::DottedEighth => 0.75,
+            Division::TripletQuarter => 2.0 / 3.0,
+            Division::TripletEighth => 1.0 / 3.0,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Division::Whole => "1/1",
+            Division::Half => "1/2",
+            Division::Quarter => "1/4",
+            Division::Eighth => "1/8",
+            Division::Sixteenth => "1/16",
+            Division::DottedQuarter => "1/4.",
+            Division::DottedEighth => "1/8.",
+            Division::TripletQuarter => "1/4T",
+            Division::TripletEighth => "1/8T",
+        }
+    }
+}
+
 impl FilterType {
-    pub const ALL: [FilterType; 8] = [
+    pub const ALL: [FilterType; 14] = [
         FilterType::LowPass,
         FilterType::HighPass,
         FilterType::BandPass,
@@ -349,8 +1385,19 @@ impl FilterType {
         FilterType::Peaking,
         FilterType::Notch,
         FilterType::AllPass,
+        FilterType::SvfLowPass,
+        FilterType::SvfBandPass,
+        FilterType::SvfHighPass,
+        FilterType::SvfNotch,
+        FilterType::Ladder,
+        FilterType::Ms20,
     ];
 
+    /// Whether `q` is a resonance amount in 0.0 ~ 1.1 rather than a Q factor.
+    pub fn is_resonant(&self) -> bool {
+        matches!(self, FilterType::Ladder | FilterType::Ms20)
+    }
+
     pub fn name(&self) -> &'static str {
         match self {
             FilterType::LowPass => "LowPass",
@@ -361,6 +1408,76 @@ impl FilterType {
             FilterType::Peaking => "Peaking",
             FilterType::Notch => "Notch",
             FilterType::AllPass => "AllPass",
+            FilterType::SvfLowPass => "SVF LowPass",
+            FilterType::SvfBandPass => "SVF BandPass",
+            FilterType::SvfHighPass => "SVF HighPass",
+            FilterType::SvfNotch => "SVF Notch",
+            FilterType::Ladder => "Ladder",
+            FilterType::Ms20 => "MS-20",
+        }
+    }
+}
+
+#[test]
+fn test_deserialize_oversampling() {
+    use serde::de::{value::Error, IntoDeserializer};
+
+    for (ratio, expected) in [(0, 1), (1, 1), (3, 2), (4, 4), (100, 8)] {
+        let deserializer = IntoDeserializer::<Error>::into_deserializer(ratio as usize);
+        assert_eq!(deserialize_oversampling(deserializer).unwrap(), expected);
+    }
+}
+
+#[test]
+fn test_reverb_buffers() {
+    use corus_v2::nodes::effects::{fdn_reverb, plate_reverb};
+
+    for sample_rate in [96000.0, 192000.0] {
+        let mut states = [
+            StateKey::FdnReverb.build(sample_rate, &vec![]),
+            StateKey::PlateReverb.build(sample_rate, &vec![]),
+        ];
+        for state in &mut states {
+            let mut ctx = ProcessContext::new(sample_rate);
+            let mut energy = [0.0; 2];
+            for i in 0..((MAX_PRE_DELAY + 0.2) * sample_rate) as usize {
+                let x = StereoF64::from([if i == 0 { 1.0 } else { 0.0 }; 2]);
+                let y = match state {
+                    State::FdnReverb { reverb } => reverb.process(
+                        &FdnReverbParam {
+                            pre_delay: MAX_PRE_DELAY,
+                            size: fdn_reverb::MAX_SIZE,
+                            rt60_low: 1.0,
+                            rt60_high: 1.0,
+                            crossover: 2000.0,
+                            modulation: 1.0,
+                            matrix: Matrix::Hadamard,
+                        },
+                        &ctx,
+                        x,
+                    ),
+                    State::PlateReverb { reverb } => reverb.process(
+                        &PlateReverbParam {
+                            pre_delay: MAX_PRE_DELAY,
+                            size: plate_reverb::MAX_SIZE,
+                            decay: 0.5,
+                            damping: 0.0,
+                            modulation: 1.0,
+                            width: 1.0,
+                            freeze: false,
+                        },
+                        &ctx,
+                        x,
+                    ),
+                    _ => unreachable!(),
+                };
+                let delayed = i as f64 >= MAX_PRE_DELAY * sample_rate;
+                energy[delayed as usize] += y[0].powi(2) + y[1].powi(2);
+                ctx.next();
+            }
+            // Silent through the pre-delay, then the tail.
+            assert_eq!(energy[0], 0.0);
+            assert!(energy[1] > 1e-3, "{} {:?}", sample_rate, energy);
         }
     }
 }
@@ -1,7 +1,11 @@
+pub mod analyzer;
 pub mod bender;
+pub mod benihora_voice;
 pub mod effectors;
 pub mod param_f64;
 pub mod param_pool;
+pub mod state_swap;
+pub mod vocal;
 pub mod wavetable;
 
 use std::sync::Arc;
@@ -10,16 +14,28 @@ use serde::{Deserialize, Serialize};
 
 use corus_v2::{
     nodes::{
-        envelope::Envelope, first_order_filter::HighPassFilter, phase::Phase, unison::Unison,
+        effects::{
+            dynamics::Detection,
+            fdn_reverb::Matrix,
+            parametric_eq::{BandType, Slope},
+        },
+        envelope::Envelope,
+        first_order_filter::HighPassFilter,
+        phase::Phase,
+        smoother::Smoother,
+        unison::Unison,
         voice_manager::VoiceManager,
     },
     signal::{IntoStereo, StereoF64},
     ProcessContext,
 };
 
-use effectors::Effector;
-use param_f64::{EnvelopeState, ParamF64};
-use wavetable::WavetableSettings;
+use analyzer::Analyzer;
+use benihora_voice::{BenihoraSettings, BenihoraState};
+use effectors::{Effector, EqBand, ImpulseResponses, StateKey};
+use param_f64::{EnvelopeState, ParamF64, ParamSmoothing};
+use vocal::{Vocal, VocalState};
+use wavetable::{WTRead, WavetableSettings, WT};
 
 use self::{
     effectors::ShaperType,
@@ -32,32 +48,108 @@ pub struct MySynth {
     gain: f64,
     pan: f64,
     pub pitch: f64,
+    /// Modulation wheel, 0..1.
+    #[serde(skip)]
+    pub modulation: f64,
+    /// Host tempo in beats per minute.
+    #[serde(skip, default = "default_tempo")]
+    pub tempo: f64,
+    #[serde(default)]
+    pub param_smoothing: ParamSmoothing,
     pub voice: Voice,
+    #[serde(deserialize_with = "effectors::deserialize_effectors")]
     pub effectors: Vec<(bool, Effector)>,
     pub lfos: Vec<Lfo>,
 }
 
+fn default_tempo() -> f64 {
+    120.0
+}
+
+/// Number of voices of `State`.
+const VOICES: usize = 8;
+
 pub struct State {
     voices: VoiceManager<u8, VoiceState>,
     effectors: Vec<effectors::State>,
     params: ParamPool,
     lfos: Vec<Phase<f64>>,
+    layout: StateLayout,
+}
+
+/// The shape of `MySynth` that a `State` was built for, with what to build it from.
+#[derive(Clone)]
+pub struct StateLayout {
+    effectors: Vec<StateKey>,
+    voice_effectors: Vec<StateKey>,
+    oscs: Vec<OscLayout>,
+    envs: usize,
+    lfos: usize,
+    voice_lfos: usize,
+    vocal: Option<(usize, u64, u64)>,
+    benihora: Option<u64>, // sound speed
+    /// Loaded by `StatePatch::new` for the `Convolution` effectors.
+    impulse_responses: ImpulseResponses,
+    sample_rate: f64,
+}
+
+#[derive(Clone)]
+struct OscLayout {
+    unison: usize,
+    revision: u64,
+    use_buffer: bool,
+    wavetable: WT,
+    /// Made from `wavetable` by `StatePatch::new`.
+    generator: Option<WTRead>,
+}
+
+/// Turns a `State` into one for another layout, keeping the voices and the states that both
+/// layouts share, such as effector tails.
+///
+/// Built off the audio thread. `State::apply` only moves values, and the replaced ones are left
+/// in the patch to be freed with it.
+pub struct StatePatch {
+    layout: StateLayout,
+    effectors: Slots<effectors::State>,
+    lfos: Slots<Phase<f64>>,
+    params: ParamPool,
+    voices: Vec<VoicePatch>,
+}
+
+struct VoicePatch {
+    oscs: Slots<OscState>,
+    effectors: Slots<effectors::State>,
+    /// `Some` replaces the state.
+    benihora: Option<Option<BenihoraState>>,
+    vocal: Option<Option<VocalState>>,
+    lfos: Slots<Phase<f64>>,
+    params: ParamPool,
+}
+
+/// New contents of a `Vec`, some of which take over an old element.
+struct Slots<T> {
+    /// Index of the old element to take over, for each new one.
+    keep: Vec<Option<usize>>,
+    values: Vec<T>,
 }
 
 impl State {
-    pub fn new(synth: &MySynth) -> Self {
-        let producers: Vec<_> = synth
-            .lfos
-            .iter()
-            .enumerate()
-            .map(|(i, _)| ProducerId::new(i))
-            .collect();
-        Self {
-            voices: VoiceManager::new(|| VoiceState::default(), 8),
+    /// Allocates; build states off the audio thread. Running states are updated with `StatePatch`.
+    pub fn new(layout: StateLayout) -> Self {
+        let mut state = Self {
+            voices: VoiceManager::new(VoiceState::default, VOICES),
             effectors: vec![],
-            params: ParamPool::new(&producers),
-            lfos: synth.lfos.iter().map(|_| Phase::new()).collect(),
-        }
+            params: ParamPool::new(&[]),
+            lfos: vec![],
+            layout: StateLayout::empty(layout.sample_rate),
+        };
+        let mut patch = StatePatch::new(&state.layout, layout);
+        state.apply(&mut patch);
+        state
+    }
+
+    pub fn layout(&self) -> &StateLayout {
+        &self.layout
     }
 
     pub fn voice_num(&self) -> usize {
@@ -67,6 +159,231 @@ impl State {
     pub fn set_voice_num(&mut self, voice_num: usize) {
         self.voices.set_voice_num(voice_num);
     }
+
+    /// Doesn't allocate nor deallocate. `patch` must have been built from the current layout.
+    pub fn apply(&mut self, patch: &mut StatePatch) {
+        patch.effectors.apply(&mut self.effectors, std::mem::swap);
+        patch.lfos.apply(&mut self.lfos, std::mem::swap);
+        std::mem::swap(&mut self.params, &mut patch.params);
+        for (voice, patch) in self.voices.iter_mut().zip(patch.voices.iter_mut()) {
+            // Oscillators keep their phases.
+            patch.oscs.apply(&mut voice.oscs, |new, old| {
+                std::mem::swap(&mut new.unison, &mut old.unison)
+            });
+            patch
+                .effectors
+                .apply(&mut voice.effector_states, std::mem::swap);
+            if let Some(benihora) = &mut patch.benihora {
+                std::mem::swap(&mut voice.benihora, benihora);
+            }
+            if let Some(vocal) = &mut patch.vocal {
+                std::mem::swap(&mut voice.vocal, vocal);
+            }
+            patch.lfos.apply(&mut voice.lfos, std::mem::swap);
+            std::mem::swap(&mut voice.params, &mut patch.params);
+        }
+        std::mem::swap(&mut self.layout, &mut patch.layout);
+    }
+}
+
+impl StateLayout {
+    /// Takes the wavetables, which are generated and cached on first use.
+    pub fn new(synth: &mut MySynth, sample_rate: f64) -> Self {
+        Self {
+            effectors: synth.effectors.iter().map(|(_, e)| e.state_key()).collect(),
+            voice_effectors: synth
+                .voice
+                .effectors
+                .iter()
+                .map(|(_, e)| e.state_key())
+                .collect(),
+            oscs: synth
+                .voice
+                .oscs
+                .iter_mut()
+                .map(|osc| OscLayout {
+                    unison: osc.unison_settings.num,
+                    revision: osc.wavetable_settings.revision(),
+                    use_buffer: osc.wavetable_settings.use_buffer,
+                    wavetable: osc.wavetable_settings.wavetable(),
+                    generator: None,
+                })
+                .collect(),
+            envs: synth.voice.envs.len(),
+            lfos: synth.lfos.len(),
+            voice_lfos: synth.voice.lfos.len(),
+            vocal: synth.voice.vocal.as_ref().map(Vocal::state_key),
+            benihora: synth.voice.benihora_key(),
+            impulse_responses: vec![],
+            sample_rate,
+        }
+    }
+
+    fn empty(sample_rate: f64) -> Self {
+        Self {
+            effectors: vec![],
+            voice_effectors: vec![],
+            oscs: vec![],
+            envs: 0,
+            lfos: 0,
+            voice_lfos: 0,
+            vocal: None,
+            benihora: None,
+            impulse_responses: vec![],
+            sample_rate,
+        }
+    }
+
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+
+    /// Doesn't allocate, so the audio thread can check whether its state is still up to date.
+    pub fn matches(&self, synth: &MySynth) -> bool {
+        self.effectors.len() == synth.effectors.len()
+            && self
+                .effectors
+                .iter()
+                .zip(synth.effectors.iter())
+                .all(|(k, (_, e))| k.matches(e))
+            && self.voice_effectors.len() == synth.voice.effectors.len()
+            && self
+                .voice_effectors
+                .iter()
+                .zip(synth.voice.effectors.iter())
+                .all(|(k, (_, e))| k.matches(e))
+            && self.oscs.len() == synth.voice.oscs.len()
+            && self
+                .oscs
+                .iter()
+                .zip(synth.voice.oscs.iter())
+                .all(|(o, osc)| {
+                    o.unison == osc.unison_settings.num
+                        && o.revision == osc.wavetable_settings.revision()
+                        && o.use_buffer == osc.wavetable_settings.use_buffer
+                })
+            && self.envs == synth.voice.envs.len()
+            && self.lfos == synth.lfos.len()
+            && self.voice_lfos == synth.voice.lfos.len()
+            && self.vocal == synth.voice.vocal.as_ref().map(Vocal::state_key)
+            && self.benihora == synth.voice.benihora_key()
+    }
+}
+
+impl StatePatch {
+    /// Allocates, and may load files for the effectors.
+    pub fn new(from: &StateLayout, mut to: StateLayout) -> Self {
+        for (i, osc) in to.oscs.iter_mut().enumerate() {
+            let generator = from
+                .oscs
+                .get(i)
+                .filter(|o| o.revision == osc.revision && o.use_buffer == osc.use_buffer)
+                .and_then(|o| o.generator.clone())
+                .unwrap_or_else(|| wavetable::generator(osc.wavetable.clone(), osc.use_buffer));
+            osc.generator = Some(generator);
+        }
+        let sample_rate = to.sample_rate;
+        // Everything depends on the sample rate.
+        let same_rate = from.sample_rate == sample_rate;
+        // Each file is loaded once, for all voices, and only when its path changes.
+        let loaded = if same_rate {
+            from.impulse_responses.as_slice()
+        } else {
+            &[]
+        };
+        to.impulse_responses = effectors::load_impulse_responses(
+            to.effectors.iter().chain(&to.voice_effectors),
+            loaded,
+            sample_rate,
+        );
+        let keep_effectors = |from: &[StateKey], to: &[StateKey]| -> Vec<Option<usize>> {
+            let mut taken = vec![!same_rate; from.len()];
+            to.iter()
+                .map(|key| {
+                    let i = (0..from.len()).find(|&i| !taken[i] && from[i] == *key)?;
+                    taken[i] = true;
+                    Some(i)
+                })
+                .collect()
+        };
+        let build_effectors = |keys: &[StateKey], keep: &[Option<usize>]| Slots {
+            keep: keep.to_vec(),
+            values: keys
+                .iter()
+                .zip(keep)
+                .map(|(key, keep)| match keep {
+                    Some(_) => effectors::State::None,
+                    None => key.build(sample_rate, &to.impulse_responses),
+                })
+                .collect(),
+        };
+        let params =
+            |size: usize| ParamPool::new(&(0..size).map(ProducerId::new).collect::<Vec<_>>());
+
+        let keep = keep_effectors(&from.effectors, &to.effectors);
+        let effectors = build_effectors(&to.effectors, &keep);
+        let voice_keep = keep_effectors(&from.voice_effectors, &to.voice_effectors);
+        let voices = (0..VOICES)
+            .map(|index| VoicePatch {
+                oscs: Slots {
+                    keep: (0..to.oscs.len())
+                        .map(|i| {
+                            from.oscs
+                                .get(i)
+                                .filter(|o| o.unison == to.oscs[i].unison)
+                                .map(|_| i)
+                        })
+                        .collect(),
+                    values: to
+                        .oscs
+                        .iter()
+                        .map(|o| OscState {
+                            unison: Unison::new(o.unison),
+                            wt: o.generator.clone().unwrap(),
+                        })
+                        .collect(),
+                },
+                effectors: build_effectors(&to.voice_effectors, &voice_keep),
+                benihora: (!same_rate || from.benihora != to.benihora).then(|| {
+                    to.benihora
+                        .map(|key| BenihoraSettings::build_state(key, sample_rate, index))
+                }),
+                vocal: (!same_rate || from.vocal != to.vocal).then(|| {
+                    to.vocal
+                        .map(|key| Vocal::build_state(key, sample_rate, index))
+                }),
+                lfos: Slots::resize(from.voice_lfos, to.voice_lfos, Phase::new),
+                params: params(to.envs + to.voice_lfos),
+            })
+            .collect();
+        Self {
+            effectors,
+            lfos: Slots::resize(from.lfos, to.lfos, Phase::new),
+            params: params(to.lfos),
+            voices,
+            layout: to,
+        }
+    }
+}
+
+impl<T> Slots<T> {
+    /// Keeps the first `from.min(to)` elements.
+    fn resize(from: usize, to: usize, f: impl Fn() -> T) -> Self {
+        Self {
+            keep: (0..to).map(|i| (i < from).then_some(i)).collect(),
+            values: (0..to).map(|_| f()).collect(),
+        }
+    }
+
+    /// `merge` takes over an old element into a new one.
+    fn apply(&mut self, current: &mut Vec<T>, mut merge: impl FnMut(&mut T, &mut T)) {
+        for (value, keep) in self.values.iter_mut().zip(self.keep.iter()) {
+            if let Some(old) = keep.and_then(|i| current.get_mut(i)) {
+                merge(value, old);
+            }
+        }
+        std::mem::swap(&mut self.values, current);
+    }
 }
 
 impl MySynth {
@@ -75,18 +392,17 @@ impl MySynth {
             gain: 1.0,
             pan: 0.0,
             pitch: 1.0,
+            modulation: 0.0,
+            tempo: default_tempo(),
+            param_smoothing: ParamSmoothing::default(),
             voice: Voice {
+                voice_type: VoiceType::Wavetable,
                 oscs: vec![
                     Osc {
                         wavetable_settings: WavetableSettings::new(1),
                         bender: bender::Bender::None,
                         bend_level: ParamF64::new(0.0),
-                        unison_settings: UnisonSettings {
-                            num: 1,
-                            detune: 0.02,
-                            stereo_width: 0.95,
-                            phase_reset: false,
-                        },
+                        unison_settings: UnisonSettings::new(1, 0.02, 0.95, false),
                         level: ParamF64::new(0.7),
                         detune: ParamF64::new(0.0),
                     },
@@ -94,22 +410,20 @@ impl MySynth {
                         wavetable_settings: WavetableSettings::new(1),
                         bender: bender::Bender::None,
                         bend_level: ParamF64::new(0.0),
-                        unison_settings: UnisonSettings {
-                            num: 1,
-                            detune: 0.02,
-                            stereo_width: 0.95,
-                            phase_reset: false,
-                        },
+                        unison_settings: UnisonSettings::new(1, 0.02, 0.95, false),
                         level: ParamF64::new(0.7),
                         detune: ParamF64::new(0.0),
                     },
                 ],
+                benihora: BenihoraSettings::new(),
+                vocal: None,
                 effectors: vec![
                     (
                         false,
                         Effector::Shaper {
                             pre_gain: ParamF64::new(1.0),
                             r#type: ShaperType::Tanh,
+                            oversampling: 4,
                         },
                     ),
                     (
@@ -152,12 +466,65 @@ impl MySynth {
                     Effector::Shaper {
                         pre_gain: ParamF64::new(1.0),
                         r#type: ShaperType::Tanh,
+                        oversampling: 4,
                     },
                 ),
                 (false, Effector::Phaser),
                 (false, Effector::Chorus),
-                (false, Effector::Delay),
+                (
+                    false,
+                    Effector::Delay {
+                        sync: Some(effectors::Division::DottedEighth),
+                        time: ParamF64::new(0.3),
+                        feedback: ParamF64::new(0.4),
+                        cross_feed: ParamF64::new(0.0),
+                        low_cut: ParamF64::new(100.0),
+                        high_cut: ParamF64::new(6000.0),
+                        drive: ParamF64::new(0.0),
+                        wow: ParamF64::new(0.0),
+                        flutter: ParamF64::new(0.0),
+                        duck: ParamF64::new(0.0),
+                        mix: ParamF64::new(0.3),
+                        offset: ParamF64::new(0.0),
+                    },
+                ),
                 (false, Effector::Reverb),
+                (
+                    false,
+                    Effector::FdnReverb {
+                        pre_delay: ParamF64::new(0.02),
+                        size: ParamF64::new(1.0),
+                        rt60_low: ParamF64::new(2.0),
+                        rt60_high: ParamF64::new(1.0),
+                        crossover: ParamF64::new(3000.0),
+                        modulation: ParamF64::new(0.3),
+                        mix: ParamF64::new(0.25),
+                        matrix: Matrix::Hadamard,
+                    },
+                ),
+                (
+                    false,
+                    Effector::PlateReverb {
+                        pre_delay: ParamF64::new(0.01),
+                        size: ParamF64::new(1.0),
+                        decay: ParamF64::new(0.5),
+                        damping: ParamF64::new(0.0005),
+                        modulation: ParamF64::new(0.5),
+                        width: ParamF64::new(1.0),
+                        mix: ParamF64::new(0.25),
+                        freeze: false,
+                    },
+                ),
+                (
+                    false,
+                    Effector::Convolution {
+                        path: String::new(),
+                        trim: -80.0,
+                        normalize: true,
+                        pre_delay: 0.0,
+                        mix: ParamF64::new(0.25),
+                    },
+                ),
                 (
                     true,
                     Effector::Gain {
@@ -167,11 +534,77 @@ impl MySynth {
                 (
                     true,
                     Effector::Compressor {
-                        threshold: ParamF64::new(0.8),
-                        ratio: ParamF64::new(0.5),
+                        threshold: ParamF64::new(-2.0),
+                        ratio: ParamF64::new(2.0),
+                        knee: ParamF64::new(6.0),
                         attack: ParamF64::new(0.01),
                         release: ParamF64::new(0.03),
-                        gain: ParamF64::new(1.0),
+                        makeup: ParamF64::new(0.0),
+                        sidechain_low_cut: ParamF64::new(0.0),
+                        detection: Detection::Peak,
+                        lookahead: ParamF64::new(0.0),
+                        sidechain: false,
+                    },
+                ),
+                (
+                    false,
+                    Effector::Expander {
+                        threshold: ParamF64::new(-50.0),
+                        ratio: ParamF64::new(4.0),
+                        knee: ParamF64::new(6.0),
+                        range: ParamF64::new(40.0),
+                        attack: ParamF64::new(0.001),
+                        release: ParamF64::new(0.1),
+                        sidechain_low_cut: ParamF64::new(0.0),
+                        detection: Detection::Rms,
+                        sidechain: false,
+                    },
+                ),
+                (
+                    false,
+                    Effector::TransientShaper {
+                        attack: ParamF64::new(0.0),
+                        sustain: ParamF64::new(0.0),
+                    },
+                ),
+                (
+                    false,
+                    Effector::Limiter {
+                        ceiling: ParamF64::new(-0.3),
+                        release: ParamF64::new(0.05),
+                    },
+                ),
+                (
+                    false,
+                    Effector::MultibandCompressor {
+                        low_cross: ParamF64::new(200.0),
+                        high_cross: ParamF64::new(3000.0),
+                        low_threshold: ParamF64::new(-12.0),
+                        mid_threshold: ParamF64::new(-12.0),
+                        high_threshold: ParamF64::new(-12.0),
+                        ratio: ParamF64::new(3.0),
+                        attack: ParamF64::new(0.01),
+                        release: ParamF64::new(0.1),
+                        low_makeup: ParamF64::new(0.0),
+                        mid_makeup: ParamF64::new(0.0),
+                        high_makeup: ParamF64::new(0.0),
+                        lookahead: ParamF64::new(0.0),
+                    },
+                ),
+                (
+                    false,
+                    Effector::ParametricEq {
+                        bands: [
+                            EqBand::new(BandType::HighPass(Slope::Db24), 30.0),
+                            EqBand::new(BandType::LowShelf, 100.0),
+                            EqBand::new(BandType::Bell, 250.0),
+                            EqBand::new(BandType::Bell, 600.0),
+                            EqBand::new(BandType::Bell, 1500.0),
+                            EqBand::new(BandType::Bell, 4000.0),
+                            EqBand::new(BandType::HighShelf, 8000.0),
+                            EqBand::new(BandType::LowPass(Slope::Db24), 18000.0),
+                        ],
+                        analyzer: Arc::new(Analyzer::new()),
                     },
                 ),
             ],
@@ -188,68 +621,79 @@ impl MySynth {
         }
     }
 
-    pub fn process(&mut self, state: &mut State, ctx: &ProcessContext) -> StereoF64 {
+    /// `sidechain` is the aux input, which keys the master effectors that ask for it.
+    pub fn process(
+        &mut self,
+        state: &mut State,
+        ctx: &ProcessContext,
+        sidechain: Option<StereoF64>,
+    ) -> StereoF64 {
         let pitch = self.pitch;
-        for (i, lfo) in self.lfos.iter().enumerate() {
-            state.params.set(ProducerId::new(i), {
-                let phase = state.lfos[i].process(ctx, lfo.frequency);
-                lfo.compute(phase)
-            });
+        let modulation = self.modulation;
+        let tempo = self.tempo;
+        self.smooth_params(ctx);
+        // The state may lag behind the settings until the next patch; zips skip what it lacks.
+        for (i, (lfo, phase)) in self.lfos.iter().zip(state.lfos.iter_mut()).enumerate() {
+            let phase = phase.process(ctx, lfo.frequency);
+            state.params.set(ProducerId::new(i), lfo.compute(phase));
         }
 
         let mut x = StereoF64::default();
         for voice in state.voices.iter_mut() {
-            x = x + self.voice.process(voice, ctx, &state.params, pitch);
+            x = x + self
+                .voice
+                .process(voice, ctx, &state.params, pitch, modulation, tempo);
         }
         for ((enabled, effector), e_state) in self.effectors.iter().zip(state.effectors.iter_mut())
         {
             if *enabled {
-                x = effector.process(e_state, ctx, &[&state.params], x);
+                x = effector.process(e_state, ctx, &[&state.params], tempo, sidechain, x);
+                effector.analyze(x);
             }
         }
         x = (x * self.gain.into_stereo()).into_stereo_with_pan(self.pan);
         x
     }
 
-    pub fn ensure_state(&mut self, state: &mut State) {
-        state
+    fn smooth_params(&mut self, ctx: &ProcessContext) {
+        let smoothing = self.param_smoothing;
+        let mut smooth = |param: &mut ParamF64| param.smooth(ctx, smoothing);
+        for (_, effector) in self
             .effectors
-            .resize_with(self.effectors.len(), || effectors::State::None);
-        for ((_, effector), state) in self.effectors.iter().zip(state.effectors.iter_mut()) {
-            effector.ensure_state(state);
+            .iter_mut()
+            .chain(self.voice.effectors.iter_mut())
+        {
+            effector.for_each_param_mut(&mut smooth);
         }
+        for osc in self.voice.oscs.iter_mut() {
+            smooth(&mut osc.bend_level);
+            smooth(&mut osc.level);
+            smooth(&mut osc.detune);
+            osc.unison_settings.smooth(ctx, smoothing);
+        }
+        smooth(&mut self.voice.benihora.level);
+        if let Some(vocal) = &mut self.voice.vocal {
+            smooth(&mut vocal.level);
+        }
+    }
 
-        for voice in state.voices.iter_mut() {
-            voice
-                .oscs
-                .resize_with(self.voice.oscs.len(), OscState::default);
-
-            for i in 0..self.voice.oscs.len() {
-                voice.oscs[i]
-                    .unison
-                    .set_voice_num(self.voice.oscs[i].unison_settings.num);
-                voice.oscs[i].wt = self.voice.oscs[i].wavetable_settings.generator();
-            }
-
-            voice
-                .effector_states
-                .resize_with(self.voice.effectors.len(), || effectors::State::None);
-            for ((_, effector), state) in self
-                .voice
-                .effectors
+    /// Latency of the enabled effectors in samples at the base rate.
+    pub fn latency(&self, state: &State) -> f64 {
+        let effectors_latency = |effectors: &[(bool, Effector)], states: &[effectors::State]| {
+            effectors
                 .iter()
-                .zip(voice.effector_states.iter_mut())
-            {
-                effector.ensure_state(state);
-            }
-
-            voice.lfos.resize_with(self.voice.lfos.len(), Phase::new);
-            let params_size = self.voice.envs.len() + self.voice.lfos.len();
-            if voice.params.params.len() != params_size {
-                voice.params =
-                    ParamPool::new(&(0..params_size).map(ProducerId::new).collect::<Vec<_>>());
-            }
-        }
+                .zip(states.iter())
+                .filter(|((enabled, _), _)| *enabled)
+                .map(|(_, state)| state.latency())
+                .sum::<f64>()
+        };
+        let voice_latency = state
+            .voices
+            .iter()
+            .next()
+            .map(|voice| effectors_latency(&self.voice.effectors, &voice.effector_states))
+            .unwrap_or_default();
+        voice_latency + effectors_latency(&self.effectors, &state.effectors)
     }
 
     pub fn handle_event(&self, state: &mut State, event: MyEvent, time: f64) {
@@ -258,16 +702,30 @@ impl MySynth {
                 let v = state.voices.note_on(notenum);
                 v.frequency = 440.0 * 2.0f64.powf((notenum as f64 - 69.0) / 12.0);
                 v.velocity = velocity;
-                for i in 0..self.voice.oscs.len() {
-                    if self.voice.oscs[i].unison_settings.phase_reset {
-                        v.oscs[i].unison.reset();
+                for (osc, osc_state) in self.voice.oscs.iter().zip(v.oscs.iter_mut()) {
+                    if osc.unison_settings.phase_reset {
+                        osc_state.unison.reset();
                     }
                 }
                 v.note_time = Some((time, f64::INFINITY));
+                if let Some(benihora) = &mut v.benihora {
+                    self.voice
+                        .benihora
+                        .note_on(benihora, time, v.frequency * self.pitch, velocity);
+                }
+                if let (Some(vocal), Some(vocal_state)) = (&self.voice.vocal, &mut v.vocal) {
+                    vocal.note_on(vocal_state, time, v.frequency * self.pitch);
+                }
             }
             MyEvent::NoteOff(notenum) => {
                 if let Some(v) = state.voices.note_off(notenum) {
                     v.note_time.iter_mut().for_each(|x| x.1 = time);
+                    if let Some(benihora) = &mut v.benihora {
+                        self.voice.benihora.note_off(benihora, time);
+                    }
+                    if let (Some(vocal), Some(vocal_state)) = (&self.voice.vocal, &mut v.vocal) {
+                        vocal.note_off(vocal_state, time);
+                    }
                 }
             }
         }
@@ -279,9 +737,25 @@ pub enum MyEvent {
     NoteOff(u8),
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum VoiceType {
+    #[default]
+    Wavetable,
+    Benihora,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct Voice {
+    /// Whether notes play `oscs` or `benihora`.
+    #[serde(default)]
+    pub voice_type: VoiceType,
     pub oscs: Vec<Osc>,
+    #[serde(default = "BenihoraSettings::new")]
+    pub benihora: BenihoraSettings,
+    /// Sung alongside the oscillators.
+    #[serde(default)]
+    pub vocal: Option<Vocal>,
+    #[serde(deserialize_with = "effectors::deserialize_effectors")]
     pub effectors: Vec<(bool, Effector)>,
     pub envs: Vec<Envelope>,
     pub lfos: Vec<Lfo>,
@@ -289,6 +763,8 @@ pub struct Voice {
 
 pub struct VoiceState {
     oscs: Vec<OscState>,
+    benihora: Option<BenihoraState>,
+    vocal: Option<VocalState>,
     frequency: f64,
     velocity: f64,
     note_time: Option<(f64, f64)>,
@@ -302,6 +778,8 @@ impl Default for VoiceState {
     fn default() -> Self {
         Self {
             oscs: vec![],
+            benihora: None,
+            vocal: None,
             frequency: 440.0,
             velocity: 0.0,
             note_time: None,
@@ -314,12 +792,18 @@ impl Default for VoiceState {
 }
 
 impl Voice {
+    fn benihora_key(&self) -> Option<u64> {
+        (self.voice_type == VoiceType::Benihora).then(|| self.benihora.state_key())
+    }
+
     pub fn process(
         &self,
         state: &mut VoiceState,
         ctx: &ProcessContext,
         param_pool: &ParamPool,
         pitch: f64,
+        modulation: f64,
+        tempo: f64,
     ) -> StereoF64 {
         let env_state = if let Some((start_time, end_time)) = state.note_time {
             EnvelopeState {
@@ -338,22 +822,30 @@ impl Voice {
             );
         }
 
-        for (i, lfo) in self.lfos.iter().enumerate() {
-            state.params.set(ProducerId::new(env_len + i), {
-                let phase = state.lfos[i].process(ctx, lfo.frequency);
-                lfo.compute(phase)
-            });
+        for (i, (lfo, phase)) in self.lfos.iter().zip(state.lfos.iter_mut()).enumerate() {
+            let phase = phase.process(ctx, lfo.frequency);
+            state
+                .params
+                .set(ProducerId::new(env_len + i), lfo.compute(phase));
         }
 
         let frequency = state.frequency * pitch;
         let mut x = StereoF64::default();
-        for i in 0..self.oscs.len() {
-            x = x + self.oscs[i].process(
-                &mut state.oscs[i],
+        if let Some(benihora) = &mut state.benihora {
+            x = self.benihora.process(
+                benihora,
                 ctx,
                 &[param_pool, &state.params],
                 frequency,
+                modulation,
             );
+        } else {
+            for (osc, osc_state) in self.oscs.iter().zip(state.oscs.iter_mut()) {
+                x = x + osc.process(osc_state, ctx, &[param_pool, &state.params], frequency);
+            }
+        }
+        if let (Some(vocal), Some(vocal_state)) = (&self.vocal, &mut state.vocal) {
+            x = x + vocal.process(vocal_state, ctx, &[param_pool, &state.params], frequency);
         }
 
         // DC offset cancel
@@ -363,7 +855,7 @@ impl Voice {
             self.effectors.iter().zip(state.effector_states.iter_mut())
         {
             if *enabled {
-                x = effector.process(e_state, ctx, &[param_pool, &state.params], x);
+                x = effector.process(e_state, ctx, &[param_pool, &state.params], tempo, None, x);
             }
         }
 
@@ -382,8 +874,9 @@ pub struct Osc {
     pub detune: ParamF64,
 }
 
+/// The synth runs in f64 throughout, like the wavetables it plays.
 pub struct OscState {
-    unison: Unison,
+    unison: Unison<f64>,
     wt: Arc<dyn Fn(f64, f64) -> f64 + Send + Sync>,
 }
 
@@ -411,8 +904,8 @@ impl Osc {
         let x = state.unison.process_range(
             ctx,
             frequency,
-            self.unison_settings.detune,
-            self.unison_settings.stereo_width,
+            self.unison_settings.smoothed_detune(),
+            self.unison_settings.smoothed_stereo_width(),
             |phase, next_phase| {
                 (state.wt)(
                     self.bender.process(bend_amount, phase),
@@ -433,4 +926,143 @@ pub struct UnisonSettings {
     pub detune: f64,
     pub stereo_width: f64,
     pub phase_reset: bool,
+    #[serde(skip, default = "Smoother::new")]
+    detune_smoother: Smoother<f64>,
+    #[serde(skip, default = "Smoother::new")]
+    stereo_width_smoother: Smoother<f64>,
+}
+
+impl UnisonSettings {
+    pub fn new(num: usize, detune: f64, stereo_width: f64, phase_reset: bool) -> Self {
+        Self {
+            num,
+            detune,
+            stereo_width,
+            phase_reset,
+            detune_smoother: Smoother::new(),
+            stereo_width_smoother: Smoother::new(),
+        }
+    }
+
+    /// Call once per sample, like `ParamF64::smooth`.
+    pub fn smooth(&mut self, ctx: &ProcessContext, smoothing: ParamSmoothing) {
+        self.detune_smoother
+            .process(ctx, smoothing.mode, smoothing.time, self.detune);
+        self.stereo_width_smoother
+            .process(ctx, smoothing.mode, smoothing.time, self.stereo_width);
+    }
+
+    pub fn smoothed_detune(&self) -> f64 {
+        self.detune_smoother.value().unwrap_or(self.detune)
+    }
+
+    pub fn smoothed_stereo_width(&self) -> f64 {
+        self.stereo_width_smoother
+            .value()
+            .unwrap_or(self.stereo_width)
+    }
+}
+
+#[test]
+fn test_process_does_not_allocate() {
+    // `nih_plug`'s `assert_process_allocs` feature installs the allocator that makes this abort.
+    let mut synth = MySynth::new();
+    for (enabled, _) in synth
+        .effectors
+        .iter_mut()
+        .chain(synth.voice.effectors.iter_mut())
+    {
+        *enabled = true;
+    }
+    synth.voice.vocal = Some(Vocal::new());
+    let mut state = State::new(StateLayout::new(&mut synth, 48000.0));
+    let mut ctx = ProcessContext::new(48000.0);
+    assert!(state.layout().matches(&synth));
+
+    assert_no_alloc::assert_no_alloc(|| {
+        synth.handle_event(&mut state, MyEvent::NoteOn(60, 1.0), ctx.current_time());
+        for _ in 0..4800 {
+            synth.process(&mut state, &ctx, None);
+            ctx.next();
+        }
+        synth.handle_event(&mut state, MyEvent::NoteOff(60), ctx.current_time());
+        for _ in 0..4800 {
+            synth.process(&mut state, &ctx, None);
+            ctx.next();
+        }
+    });
+
+    synth.voice.voice_type = VoiceType::Benihora;
+    synth.voice.benihora.constrictions = vec![(36.0, 0.45)];
+    assert!(!state.layout().matches(&synth));
+    let mut state = State::new(StateLayout::new(&mut synth, 48000.0));
+    let mut ctx = ProcessContext::new(48000.0);
+    assert!(state.layout().matches(&synth));
+
+    assert_no_alloc::assert_no_alloc(|| {
+        synth.handle_event(&mut state, MyEvent::NoteOn(60, 1.0), ctx.current_time());
+        for i in 0..4800 {
+            // Dragged through the range that makes turbulence, a step per 10 ms.
+            let t = (i / 480) as f64 / 10.0;
+            synth.voice.benihora.constrictions[0] = (36.0 - t * 4.0, 0.3 + t * 0.4);
+            synth.process(&mut state, &ctx, None);
+            ctx.next();
+        }
+        synth.handle_event(&mut state, MyEvent::NoteOff(60), ctx.current_time());
+        for _ in 0..4800 {
+            synth.process(&mut state, &ctx, None);
+            ctx.next();
+        }
+    });
+
+    synth.voice.oscs[0].wavetable_settings.set_seed(10);
+    assert!(!state.layout().matches(&synth));
+}
+
+#[test]
+fn test_patch() {
+    let mut synth = MySynth::new();
+    synth.effectors[4].0 = true; // delay
+    let mut state = State::new(StateLayout::new(&mut synth, 48000.0));
+    let mut ctx = ProcessContext::new(48000.0);
+    synth.handle_event(&mut state, MyEvent::NoteOn(60, 1.0), ctx.current_time());
+    for _ in 0..4800 {
+        synth.process(&mut state, &ctx, None);
+        ctx.next();
+    }
+
+    // The state keeps working while it lags behind the settings.
+    synth.effectors.insert(0, (true, Effector::Tanh));
+    synth.voice.oscs[0].unison_settings.num = 3;
+    synth.voice.vocal = Some(Vocal::new());
+    synth.voice.lfos.pop();
+    assert!(!state.layout().matches(&synth));
+    assert_no_alloc::assert_no_alloc(|| {
+        for _ in 0..480 {
+            synth.process(&mut state, &ctx, None);
+            ctx.next();
+        }
+    });
+
+    let mut patch = StatePatch::new(
+        state.layout(),
+        StateLayout::new(&mut synth, state.layout().sample_rate()),
+    );
+    // The delay and its tail move to the new position.
+    assert_eq!(patch.effectors.keep[0], None);
+    assert_eq!(patch.effectors.keep[5], Some(4));
+    assert_no_alloc::assert_no_alloc(|| state.apply(&mut patch));
+    assert!(state.layout().matches(&synth));
+    assert!(matches!(state.effectors[5], effectors::State::Delay { .. }));
+
+    // The note is still on.
+    let mut energy = 0.0;
+    assert_no_alloc::assert_no_alloc(|| {
+        for _ in 0..4800 {
+            let x = synth.process(&mut state, &ctx, None);
+            energy += x[0] * x[0];
+            ctx.next();
+        }
+    });
+    assert!(energy > 0.0);
 }
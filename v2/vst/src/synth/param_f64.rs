@@ -1,3 +1,7 @@
+use corus_v2::{
+    nodes::smoother::{Smoother, Smoothing},
+    ProcessContext,
+};
 use serde::{Deserialize, Serialize};
 
 use super::param_pool::{Consumer, ParamPool};
@@ -7,6 +11,16 @@ pub struct ParamF64 {
     pub value: f64,
     pub consumer: Consumer,
     pub voice_consumer: Consumer,
+    /// Follows `value`, which the GUI may change abruptly.
+    #[serde(skip, default = "Smoother::new")]
+    smoother: Smoother<f64>,
+}
+
+/// How every `ParamF64` follows edits.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct ParamSmoothing {
+    pub mode: Smoothing,
+    pub time: f64, // in seconds
 }
 
 #[derive(Serialize, Deserialize)]
@@ -27,18 +41,35 @@ impl ParamF64 {
             value,
             consumer: Consumer::new(),
             voice_consumer: Consumer::new(),
+            smoother: Smoother::new(),
         }
     }
 
+    /// Call once per sample, before `compute`.
+    pub fn smooth(&mut self, ctx: &ProcessContext, smoothing: ParamSmoothing) {
+        self.smoother
+            .process(ctx, smoothing.mode, smoothing.time, self.value);
+    }
+
     pub fn compute(&self, param_pools: &[&ParamPool]) -> f64 {
+        let value = self.smoother.value().unwrap_or(self.value);
         match param_pools {
-            [ps1] => self.value + self.consumer.get(ps1),
-            [ps1, ps2] => self.value + self.consumer.get(ps1) + self.voice_consumer.get(ps2),
+            [ps1] => value + self.consumer.get(ps1),
+            [ps1, ps2] => value + self.consumer.get(ps1) + self.voice_consumer.get(ps2),
             _ => panic!("Invalid param_pools"),
         }
     }
 }
 
+impl Default for ParamSmoothing {
+    fn default() -> Self {
+        Self {
+            mode: Smoothing::OnePole,
+            time: 0.01,
+        }
+    }
+}
+
 impl Lfo {
     pub fn compute(&self, phase: f64) -> f64 {
         wavetables::primitives::sin(phase) * self.amp
@@ -6,15 +6,21 @@ pub struct ParamPool {
 
 impl ParamPool {
     pub fn new(producer_ids: &[ProducerId]) -> Self {
-        Self { params: vec![0.0; producer_ids.len()] }
+        Self {
+            params: vec![0.0; producer_ids.len()],
+        }
     }
 
+    /// Ignores unknown producers, which a pool built for an older layout may lack.
     pub fn set(&mut self, producer_id: ProducerId, value: f64) {
-        self.params[producer_id.0] = value;
+        if let Some(param) = self.params.get_mut(producer_id.0) {
+            *param = value;
+        }
     }
 
+    /// 0 for unknown producers.
     pub fn get(&self, producer_id: ProducerId) -> f64 {
-        self.params[producer_id.0]
+        self.params.get(producer_id.0).copied().unwrap_or_default()
     }
 }
 
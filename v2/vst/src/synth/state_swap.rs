@@ -0,0 +1,95 @@
+use std::{
+    ptr,
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+/// Hands boxed values from the GUI thread to the audio thread without locking.
+///
+/// The GUI thread publishes a new value and later frees the one the audio thread retired,
+/// so the audio thread never allocates nor deallocates.
+pub struct StateSwap<T> {
+    pending: AtomicPtr<T>,
+    retired: AtomicPtr<T>,
+}
+
+impl<T> StateSwap<T> {
+    pub fn new() -> Self {
+        Self {
+            pending: AtomicPtr::new(ptr::null_mut()),
+            retired: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+
+    /// GUI thread only. Replaces a value that the audio thread has not picked up yet.
+    pub fn publish(&self, value: Box<T>) {
+        let old = self.pending.swap(Box::into_raw(value), Ordering::AcqRel);
+        free(old);
+    }
+
+    /// GUI thread only. Frees the value retired by the audio thread.
+    pub fn collect(&self) {
+        free(self.retired.swap(ptr::null_mut(), Ordering::Acquire));
+    }
+
+    /// GUI thread only. Drops both the pending and the retired value.
+    pub fn discard(&self) {
+        free(self.pending.swap(ptr::null_mut(), Ordering::AcqRel));
+        self.collect();
+    }
+
+    /// Whether a published value is waiting for the audio thread.
+    pub fn is_pending(&self) -> bool {
+        !self.pending.load(Ordering::Acquire).is_null()
+    }
+
+    /// Audio thread. Hands the published value, if there is one, to `f` and retires it.
+    /// The retired value is kept until the GUI thread collects it; until then no new value is taken.
+    pub fn receive(&self, f: impl FnOnce(&mut T)) -> bool {
+        if !self.retired.load(Ordering::Acquire).is_null() {
+            return false;
+        }
+        let new = self.pending.swap(ptr::null_mut(), Ordering::AcqRel);
+        if new.is_null() {
+            return false;
+        }
+        f(unsafe { &mut *new });
+        self.retired.store(new, Ordering::Release);
+        true
+    }
+}
+
+impl<T> Drop for StateSwap<T> {
+    fn drop(&mut self) {
+        self.discard();
+    }
+}
+
+unsafe impl<T: Send> Send for StateSwap<T> {}
+unsafe impl<T: Send> Sync for StateSwap<T> {}
+
+fn free<T>(p: *mut T) {
+    if !p.is_null() {
+        drop(unsafe { Box::from_raw(p) });
+    }
+}
+
+#[test]
+fn test() {
+    let swap = StateSwap::new();
+    let mut current = 1;
+    let mut take = |x: &mut i32| std::mem::swap(&mut current, x);
+
+    assert!(!swap.receive(&mut take));
+    swap.publish(Box::new(2));
+    swap.publish(Box::new(3));
+    assert!(swap.is_pending());
+    assert!(assert_no_alloc::assert_no_alloc(|| swap.receive(&mut take)));
+    assert!(!swap.is_pending());
+
+    // The retired value has not been collected yet.
+    swap.publish(Box::new(4));
+    assert!(!swap.receive(&mut take));
+    swap.collect();
+    assert!(swap.receive(&mut take));
+    assert_eq!(current, 4);
+}
@@ -0,0 +1,122 @@
+use benihora::{
+    choir::{Choir, ChoirSettings},
+    phoneme::{self, Articulation, Manner, Phoneme},
+    singer::Vocalist,
+};
+use corus_v2::{signal::StereoF64, ProcessContext};
+use serde::{Deserialize, Serialize};
+
+use super::{param_f64::ParamF64, param_pool::ParamPool};
+
+/// Upper bound of `Vocal::voices`, which keeps the benihora seeds apart.
+pub const MAX_VOICES: usize = 8;
+
+/// A benihora choir holding a vowel for as long as the note is on.
+#[derive(Serialize, Deserialize)]
+pub struct Vocal {
+    pub voices: usize,
+    /// Symbol of a phoneme from `vowels()`.
+    pub vowel: String,
+    /// Cents.
+    pub detune: f64,
+    pub stereo_spread: f64,
+    pub level: ParamF64,
+}
+
+pub struct VocalState {
+    choir: Choir,
+    /// Last frequency sent to the choir.
+    frequency: f64,
+    vowel: &'static str,
+    sounding: bool,
+}
+
+impl Vocal {
+    pub fn new() -> Self {
+        Self {
+            voices: 4,
+            vowel: "a".to_string(),
+            detune: 10.0,
+            stereo_spread: 0.8,
+            level: ParamF64::new(0.7),
+        }
+    }
+
+    /// Phonemes that can be sustained.
+    pub fn vowels() -> impl Iterator<Item = &'static Phoneme> {
+        phoneme::PHONEMES
+            .iter()
+            .filter(|p| matches!(p.manner, Manner::Vowel | Manner::Nasal))
+    }
+
+    /// `(voices, detune, stereo_spread)`, which the state is built from.
+    pub fn state_key(&self) -> (usize, u64, u64) {
+        (
+            self.voices,
+            self.detune.to_bits(),
+            self.stereo_spread.to_bits(),
+        )
+    }
+
+    /// Allocates. `key` is from `state_key`; `index` tells the voices of the `VoiceManager` apart.
+    pub fn build_state(key: (usize, u64, u64), sample_rate: f64, index: usize) -> VocalState {
+        let (voices, detune, stereo_spread) = key;
+        let settings = ChoirSettings {
+            voices: voices.clamp(1, MAX_VOICES),
+            detune: f64::from_bits(detune),
+            stereo_spread: f64::from_bits(stereo_spread),
+            ..Default::default()
+        };
+        VocalState {
+            choir: Choir::new(&settings, sample_rate, (index * MAX_VOICES * 8) as u32),
+            frequency: 0.0,
+            vowel: "",
+            sounding: false,
+        }
+    }
+
+    pub fn note_on(&self, state: &mut VocalState, time: f64, frequency: f64) {
+        state.frequency = frequency;
+        state.choir.set_frequency(time, frequency);
+        state.sounding = true;
+        self.update_vowel(state, time);
+    }
+
+    pub fn note_off(&self, state: &mut VocalState, time: f64) {
+        state.sounding = false;
+        state.choir.set_sound(time, false);
+    }
+
+    pub fn process(
+        &self,
+        state: &mut VocalState,
+        ctx: &ProcessContext,
+        param_pools: &[&ParamPool; 2],
+        frequency: f64,
+    ) -> StereoF64 {
+        let time = ctx.current_time();
+        if state.frequency != frequency {
+            state.frequency = frequency;
+            state.choir.set_frequency(time, frequency);
+        }
+        if state.vowel != self.vowel {
+            self.update_vowel(state, time);
+        }
+
+        let [l, r] = state.choir.process(time);
+        let level = self.level.compute(param_pools).clamp(0.0, 1.0);
+        StereoF64::from([l * level, r * level])
+    }
+
+    /// An unknown vowel keeps the previous one.
+    fn update_vowel(&self, state: &mut VocalState, time: f64) {
+        if let Some(p) = Self::vowels().find(|p| p.symbol == self.vowel) {
+            state.vowel = p.symbol;
+            let articulation = Articulation {
+                voicing: if state.sounding { 1.0 } else { 0.0 },
+                ..p.articulation()
+            };
+            state.choir.set_articulation(time, &articulation);
+        }
+    }
+}
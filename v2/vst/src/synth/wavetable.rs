@@ -13,9 +13,9 @@ pub struct WavetableSettings {
     custome_wt_tree: Option<wavetables::tree::Tree>,
     #[serde(skip)]
     custome_wt: Option<WT>,
-    #[serde(skip)]
-    buffer: Option<WTRead>,
     pub use_buffer: bool,
+    #[serde(skip)]
+    revision: u64,
 }
 
 impl WavetableSettings {
@@ -25,8 +25,8 @@ impl WavetableSettings {
             wt_cache: None,
             custome_wt_tree: None,
             custome_wt: None,
-            buffer: None,
             use_buffer: true,
+            revision: 0,
         }
     }
 
@@ -36,13 +36,18 @@ impl WavetableSettings {
 
     pub fn set_seed(&mut self, seed: u64) {
         self.seed = seed;
-        self.buffer = None;
+        self.revision += 1;
+    }
+
+    /// Incremented whenever the wavetable changes.
+    pub fn revision(&self) -> u64 {
+        self.revision
     }
 
     pub fn set_custom_wavetable(&mut self, wt: wavetables::tree::Tree) {
         self.custome_wt_tree = Some(wt);
         self.custome_wt = None;
-        self.buffer = None;
+        self.revision += 1;
     }
 
     pub fn is_custom_wavetable(&self) -> bool {
@@ -52,34 +57,7 @@ impl WavetableSettings {
     pub fn clear_custom_wavetable(&mut self) {
         self.custome_wt_tree = None;
         self.custome_wt = None;
-        self.buffer = None;
-    }
-
-    pub fn generator(&mut self) -> WTRead {
-        if self.use_buffer {
-            if let Some(buffer) = &self.buffer {
-                buffer.clone()
-            } else {
-                let wt = self.wavetable();
-                let buffer = {
-                    let resolution = 2048;
-                    let buffer: Vec<_> = (0..resolution)
-                        .map(|i| wt(i as f64 / resolution as f64))
-                        .collect();
-                    // Arc::new(move |_, x| {
-                    //     corus_v2::interpolate_get(x * buffer.len() as f64, |i| buffer[i % buffer.len()])
-                    // })
-                    let buf =
-                        corus_v2::contrib::integrated_buffer::IntegratedBuffer::from_slice(&buffer);
-                    Arc::new(move |x, y| buf.get_by_normalized_f64_with_linear_interpolation(x..y))
-                };
-                self.buffer = Some(buffer.clone());
-                buffer
-            }
-        } else {
-            let wt = self.wavetable();
-            Arc::new(move |x, _| wt(x))
-        }
+        self.revision += 1;
     }
 
     pub fn wavetable(&mut self) -> Arc<dyn Fn(f64) -> f64 + Send + Sync> {
@@ -101,6 +79,23 @@ impl WavetableSettings {
     }
 }
 
+/// Allocates. With `use_buffer`, samples `wt` into a buffer read with linear interpolation.
+pub fn generator(wt: WT, use_buffer: bool) -> WTRead {
+    if use_buffer {
+        let resolution = 2048;
+        let buffer: Vec<_> = (0..resolution)
+            .map(|i| wt(i as f64 / resolution as f64))
+            .collect();
+        // Arc::new(move |_, x| {
+        //     corus_v2::interpolate_get(x * buffer.len() as f64, |i| buffer[i % buffer.len()])
+        // })
+        let buf = corus_v2::contrib::integrated_buffer::IntegratedBuffer::from_slice(&buffer);
+        Arc::new(move |x, y| buf.get_by_normalized_f64_with_linear_interpolation(x..y))
+    } else {
+        Arc::new(move |x, _| wt(x))
+    }
+}
+
 pub fn generate_wavetable(seed: u64) -> Box<dyn Fn(f64) -> f64 + Send + Sync + 'static> {
     match seed {
         0 => wavetables::tree::Tree::Sin.build(),